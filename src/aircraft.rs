@@ -1,26 +1,48 @@
 use crate::atc::{AtcReply, AtcRequest};
 use crate::camera::Camera;
-use crate::command::AtcCommand;
+use crate::command::{AtcCommand, Condition};
 use crate::geom::*;
+use crate::hold::{Hold, HoldEntry, HoldState};
+use crate::pending::PendingInstruction;
+use crate::performance::Performance;
 use crate::{math::*, units};
 use ggez::{
     graphics::{self, Color},
     Context, GameResult,
 };
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug)]
-pub struct AircraftDefinition {
-    max_speed: u32,
-    min_speed: u32,
-}
-
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum AircraftStatus {
+    /// holding short of the departure runway
     Taxi,
+    /// lined up on the departure runway, waiting for takeoff clearance
+    LinedUp,
+    /// takeoff roll and initial climb
     Takeoff,
     Landing,
     Landed,
+    /// flying the missed approach, until level at its altitude
+    GoAround,
     Flight,
+    /// offered by the adjacent unit, not under our control until accepted
+    Inbound,
+    /// transferred to another unit, no longer under our control
+    HandedOff,
+}
+
+/// Progress of an ILS approach, each state following the previous.
+#[derive(Copy, Clone, Debug, PartialEq, PartialOrd, Serialize, Deserialize)]
+pub enum ApproachState {
+    /// cleared for the approach, waiting to intercept the localizer
+    LocalizerArmed,
+    /// turning onto the localizer
+    LocalizerCaptured,
+    /// established on the localizer, waiting to intercept the glideslope
+    GlideslopeArmed,
+    /// descending on the glidepath
+    GlideslopeCaptured,
 }
 
 // only encodes flight callsigns, not aircraft
@@ -43,8 +65,13 @@ impl Callsign {
     pub fn from_string(s: String) -> Option<Self> {
         let s = s.to_uppercase();
 
-        if s.len() > 3 {
-            // TODO: Check number is actually valid
+        let is_valid = |code: &str, number: &str| {
+            code.chars().all(|c| c.is_ascii_alphabetic())
+                && number.starts_with(|c: char| c.is_ascii_digit())
+                && number.chars().all(|c| c.is_ascii_alphanumeric())
+        };
+
+        if s.len() > 3 && s.is_char_boundary(3) && is_valid(&s[..3], &s[3..]) {
             let (code, number) = s.split_at(3);
             Some(Self {
                 name: String::from(""), // TODO: fetch from DB
@@ -69,7 +96,7 @@ impl PartialEq for Callsign {
     }
 }
 
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum TurnDirection {
     Left,
     Right,
@@ -88,12 +115,27 @@ impl std::fmt::Display for TurnDirection {
     }
 }
 
+/// How to climb or descend to the cleared altitude, kept until level at it.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum VerticalInstruction {
+    /// feet per minute, as far as the aircraft can
+    Rate(u32),
+    /// as fast as the aircraft can
+    Expedite,
+    /// at least as fast as needed to be level by the fix, in feet per minute
+    /// once worked out
+    ReachBy { fix: String, rate: Option<f32> },
+}
+
 #[derive(Clone, Debug)]
 pub struct HeadingParameter {
     intended: f32,
     // FIXME: hide
     pub current: f32,
-    interpolator: Option<Interpolator>,
+    /// degrees, positive when banked right
+    pub bank: f32,
+    /// requested turn direction, kept until it is also the shorter way round
+    direction: Option<TurnDirection>,
 }
 
 impl HeadingParameter {
@@ -101,58 +143,50 @@ impl HeadingParameter {
         Self {
             current,
             intended: current,
-            interpolator: None,
+            bank: 0.,
+            direction: None,
         }
     }
 
-    fn change(&mut self, intended: f32, duration: f32) {
-        if self.intended != intended {
-            self.intended = intended;
-
-            let initial_diff = short_angle_distance(self.intended, self.current).abs();
-            let duration = initial_diff * duration;
-            self.interpolator = Some(Interpolator::with_fn(
-                self.current,
-                self.intended,
-                duration,
-                angle_lerp,
-            ));
-        }
+    pub fn intended(&self) -> f32 {
+        self.intended
     }
 
-    fn change_with_turn(&mut self, intended: f32, duration: f32, direction: TurnDirection) {
-        if self.intended != intended {
-            self.intended = intended;
-
-            let initial_diff = short_angle_distance(self.intended, self.current);
-            let should_flip = match direction {
-                TurnDirection::Left => initial_diff < 0.0,
-                TurnDirection::Right => initial_diff > 0.0,
-            };
+    fn change(&mut self, intended: f32, direction: Option<TurnDirection>) {
+        self.intended = intended;
+        self.direction = direction;
+    }
 
-            let duration_fn = if should_flip {
-                long_angle_distance
-            } else {
-                short_angle_distance
-            };
-            let duration = duration_fn(self.intended, self.current).abs() * duration;
-
-            self.interpolator = Some(Interpolator::with_fn(
-                self.current,
-                self.intended,
-                duration,
-                if should_flip {
-                    long_angle_lerp
-                } else {
-                    angle_lerp
-                },
-            ));
+    /// Degrees left to turn, positive to the right.
+    fn remaining(&self) -> f32 {
+        let shortest = short_angle_distance(self.current, self.intended);
+        match self.direction {
+            Some(TurnDirection::Right) if shortest < 0. => shortest + 360.,
+            Some(TurnDirection::Left) if shortest > 0. => shortest - 360.,
+            _ => shortest,
         }
     }
 
-    pub fn current(&mut self, dt: f32) -> f32 {
-        if let Some(interpolator) = self.interpolator.as_mut().filter(|x| !x.is_finished()) {
-            self.current = interpolator.update(dt);
+    /// Roll towards the bank needed to reach the intended heading, at most
+    /// `max_bank` degrees, and turn at the rate that bank gives at `speed`
+    /// meters per second.
+    pub fn current(&mut self, max_bank: f32, speed: f32, dt: f32) -> f32 {
+        let remaining = self.remaining();
+        if remaining == short_angle_distance(self.current, self.intended) {
+            self.direction = None;
+        }
+
+        // bank in proportion to the turn left, rolling out towards the end
+        let target_bank = clamp(remaining * BANK_PER_DEGREE, -max_bank, max_bank);
+        let roll = ROLL_RATE * dt;
+        self.bank += clamp(target_bank - self.bank, -roll, roll);
+
+        if remaining.abs() < HEADING_TOLERANCE {
+            self.current = self.intended;
+            self.bank = 0.;
+        } else if speed > 0. {
+            let turn_rate = GRAVITY * self.bank.to_radians().tan() / speed;
+            self.current = degrees_normalize(self.current + turn_rate.to_degrees() * dt);
         }
         self.current
     }
@@ -163,39 +197,88 @@ pub struct AircraftParameter {
     intended: f32,
     // FIXME: hide
     pub current: f32,
-    interpolator: Option<Interpolator>,
 }
 
 impl AircraftParameter {
     pub fn new(current: f32) -> Self {
         Self {
-            current: current,
+            current,
             intended: current,
-            interpolator: None,
         }
     }
 
-    /// duration is time per single value
-    fn change(&mut self, intended: f32, duration: f32) {
-        if self.intended != intended {
-            self.intended = intended;
+    pub fn intended(&self) -> f32 {
+        self.intended
+    }
 
-            let initial_diff = self.intended - self.current;
-            // let initial_diff = short_angle_distance(self.intended, self.current);
-            let duration = initial_diff.abs() * duration;
-            self.interpolator = Some(Interpolator::new(self.current, self.intended, duration));
-        }
+    fn change(&mut self, intended: f32) {
+        self.intended = intended;
+    }
+
+    pub fn is_increasing(&self) -> bool {
+        self.intended > self.current
     }
 
-    pub fn current(&mut self, dt: f32) -> f32 {
-        if let Some(interpolator) = self.interpolator.as_mut().filter(|x| !x.is_finished()) {
-            self.current = interpolator.update(dt);
+    /// Move towards the intended value at `rate` per second.
+    pub fn current(&mut self, rate: f32, dt: f32) -> f32 {
+        let diff = self.intended - self.current;
+        let step = rate * dt;
+        if diff.abs() <= step {
+            self.current = self.intended;
+        } else {
+            self.current += step * diff.signum();
         }
         self.current
     }
 }
 
-const TURN_RATE: f32 = 0.1;
+/// m/s^2
+const GRAVITY: f32 = 9.81;
+/// degrees
+const MAX_BANK_ANGLE: f32 = 25.;
+/// degrees per second
+const ROLL_RATE: f32 = 5.;
+/// degrees of bank per degree of heading left to turn
+const BANK_PER_DEGREE: f32 = 2.;
+/// degrees, within which the heading is reached
+const HEADING_TOLERANCE: f32 = 0.5;
+
+/// multiple of the usual climb or descent rate, when expediting
+const EXPEDITE_FACTOR: f32 = 1.5;
+/// multiple of the rate needed to reach an altitude by a fix, to be level
+/// a little before it
+const REACH_BY_MARGIN: f32 = 1.1;
+
+/// knots
+const ROTATE_SPEED: f32 = 150.;
+/// knots per second
+const TAKEOFF_ACCELERATION: f32 = 4.;
+/// knots
+const DEPARTURE_SPEED: u32 = 210;
+/// feet, above which departures are ordinary traffic
+const DEPARTURE_ALTITUDE: f32 = 1000.;
+
+/// meters off the localizer centreline per degree of heading correction
+const LOCALIZER_GAIN: f32 = 30.;
+/// degrees
+const MAX_LOCALIZER_CORRECTION: f32 = 30.;
+/// meters off the centreline, within which the localizer is established
+const LOCALIZER_ESTABLISHED: f32 = 100.;
+/// feet above or below the glidepath, within which it is captured
+const GLIDESLOPE_CAPTURE: f32 = 50.;
+
+/// meters from a fix, within which the aircraft is overhead
+const FIX_OVERHEAD: f32 = 1000.;
+
+/// seconds
+const HOLD_LEG_TIME: f32 = 60.;
+/// seconds, above `HIGH_HOLD_ALTITUDE`
+const HIGH_HOLD_LEG_TIME: f32 = 90.;
+/// feet
+const HIGH_HOLD_ALTITUDE: f32 = 14000.;
+/// degrees off the outbound course, within which the turn onto it is done
+/// and the leg is timed
+const HOLD_TURN_COMPLETE: f32 = 5.;
 
 #[derive(Clone, Debug)]
 pub struct Aircraft {
@@ -206,70 +289,615 @@ pub struct Aircraft {
     pub heading: HeadingParameter,
     /// feet
     pub altitude: AircraftParameter,
-    /// knots
+    /// feet per minute over the last step, positive when climbing
+    pub vertical_speed: f32,
+    pub vertical: Option<VerticalInstruction>,
+    /// indicated airspeed, knots
     pub speed: AircraftParameter,
+    pub performance: Performance,
+    /// wind at the aircraft, meters per second, set by the simulation
+    pub wind: glm::Vec2,
     pub status: AircraftStatus,
     pub cleared_to_land: bool,
+    pub cleared_to_line_up: bool,
+    pub cleared_for_takeoff: bool,
+    /// instructed to go around, the missed approach is flown from the next
+    /// step
+    pub go_around_instructed: bool,
+    /// assigned runway designator
+    pub runway: Option<String>,
+    pub approach: Option<ApproachState>,
+    /// name of the fix being flown direct to
+    pub direct: Option<String>,
+    pub hold: Option<Hold>,
+    /// instructions waiting for their conditions, in the order given
+    pub pending: Vec<PendingInstruction>,
 }
 
 impl Aircraft {
+    pub fn new(
+        callsign: Callsign,
+        performance: Performance,
+        position: glm::Vec2,
+        heading: f32,
+        altitude: f32,
+        speed: f32,
+    ) -> Self {
+        Self {
+            position,
+            callsign,
+            heading: HeadingParameter::new(heading),
+            altitude: AircraftParameter::new(altitude),
+            vertical_speed: 0.,
+            vertical: None,
+            speed: AircraftParameter::new(speed),
+            performance,
+            wind: glm::zero(),
+            status: AircraftStatus::Flight,
+            cleared_to_land: false,
+            cleared_to_line_up: false,
+            cleared_for_takeoff: false,
+            go_around_instructed: false,
+            runway: None,
+            approach: None,
+            direct: None,
+            hold: None,
+            pending: Vec::new(),
+        }
+    }
+
     pub fn change_heading(&mut self, course: f32, direction: Option<TurnDirection>) {
-        // time in seconds for 1 degree change
-        let duration = TURN_RATE;
-        // FIXME: don't use clamp, use rem_euclid (maybe)
-        let course = clamp(course, 0., 360.);
+        self.heading.change(degrees_normalize(course), direction);
+    }
 
-        match direction {
-            Some(direction) => self.heading.change_with_turn(course, duration, direction),
-            None => self.heading.change(course, duration),
-        }
+    /// Meters per second.
+    pub fn true_airspeed(&self) -> f32 {
+        self.speed.current * units::KT_TO_MS as f32
+    }
+
+    /// Bank angle for a standard rate turn, limited to the maximum bank.
+    pub fn max_bank(&self) -> f32 {
+        let turn_rate = self.performance.turn_rate.to_radians();
+        let bank = (self.true_airspeed() * turn_rate / GRAVITY).atan().to_degrees();
+        bank.min(MAX_BANK_ANGLE)
+    }
+
+    /// Radius of a turn at the maximum bank, in meters.
+    pub fn turn_radius(&self) -> f32 {
+        self.true_airspeed().powi(2) / (GRAVITY * self.max_bank().to_radians().tan())
     }
 
+    /// Clear the aircraft to a new altitude, at the usual rates until told
+    /// otherwise.
     pub fn change_altitude(&mut self, new_altitude: u32) {
-        // seconds per 1000 feet
-        let duration = 30.0 / 1000.0;
-        self.altitude.change(new_altitude as f32, duration);
+        self.altitude.change(new_altitude as f32);
+        self.vertical = None;
     }
 
     pub fn change_speed(&mut self, new_speed: u32) {
-        // time for 1kt change
-        let duration = 1.0;
-        // TODO: depends on aircraft type
         self.speed
-            .change(clamp(new_speed, 150, 250) as f32, duration);
+            .change(self.performance.limit_speed(new_speed) as f32);
+    }
+
+    /// Advance heading, altitude and speed by `dt` seconds, moving the
+    /// aircraft along its heading and with the wind.
+    pub fn update(&mut self, dt: f32) {
+        if !self.is_grounded() {
+            let acceleration = if self.is_rolling() {
+                TAKEOFF_ACCELERATION
+            } else if self.speed.is_increasing() {
+                self.performance.acceleration
+            } else {
+                self.performance.deceleration
+            };
+            self.speed.current(acceleration, dt);
+
+            // hold the runway heading and stay on the ground until rotation
+            if !self.is_rolling() {
+                let (max_bank, speed) = (self.max_bank(), self.true_airspeed());
+                self.heading.current(max_bank, speed, dt);
+
+                let altitude = self.altitude.current;
+                self.altitude.current(self.vertical_rate() / 60., dt);
+                self.vertical_speed = (self.altitude.current - altitude) / dt * 60.;
+                if self.altitude.current == self.altitude.intended {
+                    self.vertical = None;
+                }
+            }
+            self.position += self.velocity() * dt;
+        }
+    }
+
+    /// Feet per minute to climb or descend at, as instructed and as far as
+    /// the aircraft can.
+    fn vertical_rate(&self) -> f32 {
+        let altitude = self.altitude.current;
+        let usual = if self.altitude.is_increasing() {
+            self.performance.climb_rate(altitude)
+        } else {
+            self.performance.descent_rate(altitude)
+        };
+        let max = usual * EXPEDITE_FACTOR;
+        match &self.vertical {
+            Some(VerticalInstruction::Rate(rate)) => (*rate as f32).min(max),
+            Some(VerticalInstruction::Expedite) => max,
+            Some(VerticalInstruction::ReachBy {
+                rate: Some(rate), ..
+            }) => rate.clamp(usual, max),
+            _ => usual,
+        }
+    }
+
+    /// Work out the rate needed to reach the cleared altitude by the fix at
+    /// `position`, keeping the last one once past it.
+    pub fn reach_by(&mut self, position: glm::Vec2) {
+        let offset = position - self.position;
+        let velocity = self.velocity();
+        let remaining = (self.altitude.intended - self.altitude.current).abs();
+        if let Some(VerticalInstruction::ReachBy { rate, .. }) = &mut self.vertical {
+            if glm::dot(&offset, &velocity) > 0. {
+                let minutes = glm::length(&offset) / glm::length(&velocity) / 60.;
+                *rate = Some(remaining / minutes * REACH_BY_MARGIN);
+            }
+        }
+    }
+
+    /// Carry out the pending instructions whose conditions are met after
+    /// another `dt` seconds. `position_of` gives the position of the fix or
+    /// threshold a condition refers to.
+    pub fn check_pending(
+        &mut self,
+        dt: f32,
+        position_of: impl Fn(&Condition) -> Option<glm::Vec2>,
+    ) {
+        let (altitude, position) = (self.altitude.current, self.position);
+        let mut due = Vec::new();
+        for mut instruction in std::mem::take(&mut self.pending) {
+            let target = position_of(&instruction.condition);
+            if instruction.update(dt, altitude, position, target) {
+                due.push(instruction.command);
+            } else {
+                self.pending.push(instruction);
+            }
+        }
+        for command in due {
+            self.command(AtcRequest(command));
+        }
+    }
+
+    /// Move onto the runway threshold, facing down the runway.
+    pub fn line_up(&mut self, threshold: glm::Vec2, runway_heading: f32) {
+        self.position = threshold;
+        self.heading = HeadingParameter::new(runway_heading);
+        self.speed = AircraftParameter::new(0.);
+        self.status = AircraftStatus::LinedUp;
+    }
+
+    pub fn start_takeoff(&mut self) {
+        self.speed.change(ROTATE_SPEED);
+        self.status = AircraftStatus::Takeoff;
+    }
+
+    /// Rotate once at takeoff speed and climb out, unless already cleared
+    /// to another altitude, becoming ordinary traffic once clear of the
+    /// runway.
+    pub fn climb_out(&mut self, initial_altitude: u32) {
+        if self.is_rolling() {
+            return;
+        }
+
+        if self.altitude.intended < 1. {
+            self.change_altitude(initial_altitude);
+            self.change_speed(DEPARTURE_SPEED);
+        }
+        if self.altitude.current >= DEPARTURE_ALTITUDE {
+            self.status = AircraftStatus::Flight;
+            self.runway = None;
+        }
+    }
+
+    /// Break off the approach and fly the missed approach `procedure`,
+    /// climbing to its altitude unless already higher.
+    pub fn go_around(&mut self, procedure: &MissedApproach) {
+        self.approach = None;
+        self.cleared_to_land = false;
+        self.go_around_instructed = false;
+        self.status = AircraftStatus::GoAround;
+
+        let altitude = procedure.altitude.max(self.altitude.current as u32);
+        self.change_altitude(altitude);
+        self.change_speed(DEPARTURE_SPEED);
+        match &procedure.route {
+            MissedApproachRoute::Heading(heading) => self.change_heading(*heading as f32, None),
+            MissedApproachRoute::Fix(fix) => self.direct = Some(fix.clone()),
+        }
+    }
+
+    /// Back under radar control once level at the end of the missed
+    /// approach.
+    pub fn fly_missed_approach(&mut self) {
+        if self.status == AircraftStatus::GoAround
+            && self.altitude.current >= self.altitude.intended
+        {
+            self.status = AircraftStatus::Flight;
+            self.runway = None;
+        }
+    }
+
+    /// Fly the next step of an ILS approach to the runway of `ils`. The
+    /// localizer is intercepted once it is close enough to turn onto it,
+    /// the glideslope once established and level with the glidepath.
+    pub fn fly_approach(&mut self, ils: &ILS) {
+        use ApproachState::*;
+        let state = match self.approach {
+            Some(state) => state,
+            None => return,
+        };
+        // only the localizer of the runway cleared for
+        if self.runway.as_ref() != Some(&ils.runway.name) {
+            return;
+        }
+
+        let crosstrack = ils.crosstrack_distance(&self.position);
+        let glidepath = ils.glideslope_altitude(&self.position);
+        match state {
+            LocalizerArmed => {
+                let intercept_angle = short_angle_distance(self.track(), ils.course());
+                let is_inbound = intercept_angle.abs() <= 90.;
+                if is_inbound
+                    && ils.is_in_coverage(&self.position)
+                    && self.altitude.current <= ils.runway.ils_max_altitude as f32
+                    && crosstrack.abs() <= self.turn_lead(intercept_angle)
+                {
+                    self.approach = Some(LocalizerCaptured);
+                    if self.status != AircraftStatus::HandedOff {
+                        self.status = AircraftStatus::Landing;
+                    }
+                    self.direct = None;
+                    self.hold = None;
+                }
+            }
+            LocalizerCaptured => {
+                if crosstrack.abs() < LOCALIZER_ESTABLISHED {
+                    self.approach = Some(GlideslopeArmed);
+                }
+            }
+            GlideslopeArmed => {
+                if (self.altitude.current - glidepath).abs() <= GLIDESLOPE_CAPTURE {
+                    self.approach = Some(GlideslopeCaptured);
+                }
+            }
+            GlideslopeCaptured => {
+                // descending with the glidepath at the speed over the ground
+                let descent = glm::length(&self.velocity()) * GLIDESLOPE_ANGLE.to_radians().tan();
+                self.vertical_speed = -descent / units::FT_TO_M as f32 * 60.;
+                self.altitude = AircraftParameter::new(glidepath);
+            }
+        }
+
+        if self.approach > Some(LocalizerArmed) {
+            // steer back to the centreline, in proportion to the distance off it
+            let correction = clamp(
+                crosstrack / LOCALIZER_GAIN,
+                -MAX_LOCALIZER_CORRECTION,
+                MAX_LOCALIZER_CORRECTION,
+            );
+            let heading = ils.course() + self.wind_correction_angle(ils.course()) - correction;
+            self.change_heading(degrees_normalize(heading), None);
+        }
+    }
+
+    /// Steer towards the fix at `position`, correcting for the wind, until
+    /// overhead. Passing it, or unable to turn tightly enough to reach it,
+    /// the aircraft carries on along its heading.
+    pub fn fly_direct(&mut self, position: glm::Vec2) {
+        if self.is_grounded() || self.is_rolling() {
+            return;
+        }
+
+        if self.steer_direct(position) {
+            self.direct = None;
+        }
+    }
+
+    /// Steer towards `position` unless already overhead, returning whether
+    /// it is.
+    fn steer_direct(&mut self, position: glm::Vec2) -> bool {
+        let offset = position - self.position;
+        let distance = glm::length(&offset);
+        let bearing = degrees_normalize(offset.x.atan2(offset.y).to_degrees());
+        let is_behind = short_angle_distance(self.track(), bearing).abs() > 90.;
+        if distance < FIX_OVERHEAD || (is_behind && distance < self.turn_radius()) {
+            true
+        } else {
+            let heading = bearing + self.wind_correction_angle(bearing);
+            self.change_heading(heading, None);
+            false
+        }
+    }
+
+    /// Turn onto `heading` the way of `direction` while more than a quarter
+    /// turn away, the shorter way round after that.
+    fn turn_towards(&mut self, heading: f32, direction: TurnDirection) {
+        let remaining = short_angle_distance(self.heading.current, heading);
+        let direction = if remaining.abs() > 90. {
+            Some(direction)
+        } else {
+            None
+        };
+        self.change_heading(heading, direction);
+    }
+
+    /// Fly the next `dt` seconds of the hold at the fix at `position`:
+    /// proceed to the fix, join with the entry for the heading on arrival,
+    /// then keep flying the racetrack.
+    pub fn fly_hold(&mut self, position: glm::Vec2, dt: f32) {
+        if self.is_grounded() || self.is_rolling() {
+            return;
+        }
+        let mut hold = match self.hold.take() {
+            Some(hold) => hold,
+            None => return,
+        };
+
+        use HoldState::*;
+        hold.state = match hold.state {
+            Entering if self.steer_direct(position) => match hold.entry(self.heading.current) {
+                HoldEntry::Direct => Outbound(0.),
+                entry => Entry(entry, 0.),
+            },
+            Entering => Entering,
+            Entry(entry, elapsed) => {
+                self.change_heading(hold.entry_heading(entry), None);
+                if elapsed + dt < hold.leg_time {
+                    Entry(entry, elapsed + dt)
+                } else if entry == HoldEntry::Parallel {
+                    Returning(hold.entry_turn(entry))
+                } else {
+                    Inbound(hold.entry_turn(entry))
+                }
+            }
+            Returning(direction) => {
+                let offset = position - self.position;
+                if glm::length(&offset) < FIX_OVERHEAD
+                    || hold.along_track_distance(position, self.position) >= 0.
+                {
+                    Outbound(0.)
+                } else {
+                    let bearing = degrees_normalize(offset.x.atan2(offset.y).to_degrees());
+                    let heading = bearing + self.wind_correction_angle(bearing);
+                    self.turn_towards(degrees_normalize(heading), direction);
+                    Returning(direction)
+                }
+            }
+            Outbound(elapsed) => {
+                let course = hold.outbound_course();
+                let heading = degrees_normalize(course + self.wind_correction_angle(course));
+                self.turn_towards(heading, hold.direction);
+                let is_turned =
+                    short_angle_distance(self.heading.current, heading).abs() < HOLD_TURN_COMPLETE;
+                let elapsed = if is_turned { elapsed + dt } else { elapsed };
+                if elapsed >= hold.leg_time {
+                    Inbound(hold.direction)
+                } else {
+                    Outbound(elapsed)
+                }
+            }
+            Inbound(direction) => {
+                if hold.along_track_distance(position, self.position) >= 0. {
+                    // over the fix, round again
+                    Outbound(0.)
+                } else {
+                    // steer onto the inbound course, as for a localizer
+                    let crosstrack = hold.crosstrack_distance(position, self.position);
+                    let correction = clamp(
+                        crosstrack / LOCALIZER_GAIN,
+                        -MAX_LOCALIZER_CORRECTION,
+                        MAX_LOCALIZER_CORRECTION,
+                    );
+                    let course = hold.inbound_course;
+                    let heading = course + self.wind_correction_angle(course) - correction;
+                    self.turn_towards(degrees_normalize(heading), direction);
+                    Inbound(direction)
+                }
+            }
+        };
+        self.hold = Some(hold);
+    }
+
+    /// Distance before a course at which to start turning onto it, so the
+    /// turn ends on the course.
+    fn turn_lead(&self, intercept_angle: f32) -> f32 {
+        let angle = intercept_angle.to_radians().abs();
+        let turn = self.turn_radius() * (1. - angle.cos());
+        // distance flown towards the course while rolling into the turn
+        let roll_in = glm::length(&self.velocity()) * (self.max_bank() / ROLL_RATE) * angle.sin();
+        (turn + roll_in).max(LOCALIZER_ESTABLISHED)
+    }
+
+    /// On the takeoff roll, below rotation speed.
+    pub fn is_rolling(&self) -> bool {
+        self.status == AircraftStatus::Takeoff && self.speed.current < ROTATE_SPEED
+    }
+
+    /// Velocity through the air in meters per second.
+    pub fn air_velocity(&self) -> glm::Vec2 {
+        let heading = self.heading.current.to_radians();
+        glm::vec2(heading.sin(), heading.cos()) * self.true_airspeed()
+    }
+
+    /// Velocity over the ground in meters per second, drifting with the
+    /// wind once airborne.
+    pub fn velocity(&self) -> glm::Vec2 {
+        if self.is_rolling() {
+            self.air_velocity()
+        } else {
+            self.air_velocity() + self.wind
+        }
     }
 
-    pub fn is_localizer_captured(&self, localizer: &ILS) -> bool {
-        is_point_in_triangle(self.position, &localizer.as_triangle())
-            && self.altitude.current as u32 <= localizer.altitude(&self.position)
+    /// Knots.
+    pub fn ground_speed(&self) -> f32 {
+        glm::length(&self.velocity()) / units::KT_TO_MS as f32
+    }
+
+    /// Bearing of the path over the ground, the heading when not moving.
+    pub fn track(&self) -> f32 {
+        let velocity = self.velocity();
+        if glm::length(&velocity) < f32::EPSILON {
+            self.heading.current
+        } else {
+            degrees_normalize(velocity.x.atan2(velocity.y).to_degrees())
+        }
+    }
+
+    /// Degrees to head into the wind to keep a track along `course`,
+    /// negative to the left.
+    pub fn wind_correction_angle(&self, course: f32) -> f32 {
+        let course = course.to_radians();
+        let right = glm::vec2(course.cos(), -course.sin());
+        let crosswind = glm::dot(&self.wind, &right);
+        let ratio = clamp(crosswind / self.true_airspeed().max(1.), -1., 1.);
+        -ratio.asin().to_degrees()
     }
 
     pub fn is_grounded(&self) -> bool {
-        self.status == AircraftStatus::Taxi || self.status == AircraftStatus::Landed
+        matches!(
+            self.status,
+            AircraftStatus::Taxi | AircraftStatus::LinedUp | AircraftStatus::Landed
+        )
+    }
+
+    /// Whether we are the unit the aircraft is talking to.
+    pub fn is_under_control(&self) -> bool {
+        !matches!(
+            self.status,
+            AircraftStatus::Inbound | AircraftStatus::HandedOff
+        )
     }
 
     pub fn cleared_to_land(&self) -> bool {
         self.cleared_to_land
     }
 
+    /// Arm the approach to `runway`, starting over if cleared for another.
+    /// Departures are cleared for approaches once airborne.
+    fn clear_approach(&mut self, runway: &str) {
+        use AircraftStatus::*;
+        if matches!(self.status, Taxi | LinedUp | Takeoff | Landed) {
+            return;
+        }
+        if self.runway.as_deref() != Some(runway) || self.status == GoAround {
+            self.runway = Some(runway.to_string());
+            self.approach = None;
+            self.cleared_to_land = false;
+            self.status = Flight;
+        }
+        if self.approach.is_none() {
+            self.approach = Some(ApproachState::LocalizerArmed);
+        }
+    }
+
     pub fn command(&mut self, cmd: AtcRequest) -> AtcReply {
         use AtcCommand::*;
-        match cmd.0 {
+        match &cmd.0 {
             ChangeHeading(heading) => {
-                self.change_heading(heading as f32, None)
+                self.direct = None;
+                self.hold = None;
+                self.change_heading(*heading as f32, None)
                 // reply
                 // TODO
             }
             ChangeHeadingWithTurnDirection(heading, direction) => {
-                self.change_heading(heading as f32, Some(direction))
+                self.direct = None;
+                self.hold = None;
+                self.change_heading(*heading as f32, Some(*direction))
+            }
+            ChangeAltitude(altitude) => self.change_altitude(*altitude),
+            // only for the climb or descent under way
+            VerticalRate(_) | Expedite | ReachBy(_)
+                if self.altitude.current == self.altitude.intended =>
+            {
+                let reason = format!("level at {} feet", self.altitude.current as u32);
+                return AtcReply::Unable(cmd.0, reason);
             }
-            ChangeAltitude(altitude) => self.change_altitude(altitude),
-            ChangeSpeed(speed) => self.change_speed(speed),
-            ClearedToLand(is_cleared) => {
-                self.cleared_to_land = is_cleared;
+            VerticalRate(rate) => self.vertical = Some(VerticalInstruction::Rate(*rate)),
+            Expedite => self.vertical = Some(VerticalInstruction::Expedite),
+            ReachBy(fix) => {
+                self.vertical = Some(VerticalInstruction::ReachBy {
+                    fix: fix.clone(),
+                    rate: None,
+                })
             }
+            ChangeSpeed(speed) => self.change_speed(*speed),
+            ClearedForApproach(runway) => self.clear_approach(runway),
+            ClearedToLand(Some(runway)) => {
+                self.clear_approach(runway);
+                self.cleared_to_land = self.approach.is_some();
+            }
+            ClearedToLand(None) => {
+                self.cleared_to_land = false;
+                self.approach = None;
+                if self.status == AircraftStatus::Landing {
+                    self.status = AircraftStatus::Flight;
+                    self.runway = None;
+                }
+            }
+            GoAround if self.approach.is_none() => {
+                return AtcReply::Unable(cmd.0, "not on an approach".into());
+            }
+            GoAround => self.go_around_instructed = true,
+            LineUp => self.cleared_to_line_up = true,
+            ClearedForTakeoff => self.cleared_for_takeoff = true,
+            DirectTo(fix) => {
+                self.hold = None;
+                self.direct = Some(fix.clone());
+            }
+            Hold {
+                fix,
+                course,
+                direction,
+                leg_time,
+            } => {
+                let leg_time = leg_time.map(|minutes| minutes * 60.).unwrap_or(
+                    if self.altitude.current > HIGH_HOLD_ALTITUDE {
+                        HIGH_HOLD_LEG_TIME
+                    } else {
+                        HOLD_LEG_TIME
+                    },
+                );
+                self.direct = None;
+                self.hold = Some(crate::hold::Hold::new(
+                    fix.clone(),
+                    *course as f32,
+                    *direction,
+                    leg_time,
+                ));
+            }
+            // once airborne and clear of the runway
+            Handoff { .. } if self.is_grounded() || self.status == AircraftStatus::Takeoff => {
+                return AtcReply::Unable(cmd.0, "not clear of the runway".into());
+            }
+            Handoff { .. } => {
+                if matches!(
+                    self.status,
+                    AircraftStatus::Flight | AircraftStatus::Landing | AircraftStatus::GoAround
+                ) {
+                    self.status = AircraftStatus::HandedOff;
+                }
+            }
+            AcceptHandoff => {
+                if self.status == AircraftStatus::Inbound {
+                    self.status = AircraftStatus::Flight;
+                }
+            }
+            Conditional { condition, command } => self.pending.push(PendingInstruction::new(
+                condition.clone(),
+                (**command).clone(),
+                self.altitude.current,
+            )),
         }
-        AtcReply(cmd.0)
+        AtcReply::Wilco(cmd.0)
     }
 }
 
@@ -291,6 +919,16 @@ pub const ONE_SECOND_IN_HOURS: f32 = 1. / 3600.;
 
 // 8nm
 pub const ILS_LENGTH: f32 = 8. * units::NM_to_KM as f32 * 1000.;
+/// degrees
+pub const GLIDESLOPE_ANGLE: f32 = 3.;
+/// height of the glidepath over the threshold, in feet
+pub const THRESHOLD_CROSSING_HEIGHT: f32 = 50.;
+// localizer coverage, 10 degrees either side of the centreline up to 18nm
+// and 35 degrees up to 10nm
+const LOCALIZER_RANGE: f32 = 18. * units::NM_to_KM as f32 * 1000.;
+const LOCALIZER_COVERAGE: f32 = 10.;
+const LOCALIZER_WIDE_RANGE: f32 = 10. * units::NM_to_KM as f32 * 1000.;
+const LOCALIZER_WIDE_COVERAGE: f32 = 35.;
 
 #[derive(Clone, Debug)]
 pub struct ILS {
@@ -335,53 +973,50 @@ impl ILS {
         ], self.heading().to_radians())
     }
 
-    pub fn crosstrack_distance(&self, position: &glm::Vec2) -> f32 {
-        distance_line_and_point(&self.as_line(), position)
+    /// Inbound course, the runway heading.
+    pub fn course(&self) -> f32 {
+        self.runway.heading as f32
     }
 
-    pub fn crosstrack_angle(&self, position: &glm::Vec2) -> f32 {
-        point_angle(&self.origin, position).to_degrees()
+    /// Distance from the threshold along the approach, in meters, negative
+    /// once past the threshold.
+    pub fn along_track_distance(&self, position: &glm::Vec2) -> f32 {
+        let outbound = heading_to_point(self.heading() as i32);
+        glm::dot(&(position - self.origin), &outbound)
     }
 
-    pub fn distance(&self, position: &glm::Vec2) -> f32 {
-        point_distance(position, &self.origin)
+    /// Distance off the centreline in meters, positive when right of it
+    /// looking down the approach towards the runway.
+    pub fn crosstrack_distance(&self, position: &glm::Vec2) -> f32 {
+        let right = heading_to_point(self.runway.heading as i32 + 90);
+        glm::dot(&(position - self.origin), &right)
     }
 
-    pub fn altitude(&self, position: &glm::Vec2) -> u32 {
-        let distance = self.distance(position);
-        let expected_alt = self.runway.ils_max_altitude as f32 * (distance / ILS_LENGTH);
-        // round to 1000
-        let rounded_alt = round_to_sf(expected_alt as f64, 3);
-        rounded_alt as u32
+    /// Altitude of the glidepath abeam the position, in feet.
+    pub fn glideslope_altitude(&self, position: &glm::Vec2) -> f32 {
+        let distance = self.along_track_distance(position).max(0.);
+        let height = distance * GLIDESLOPE_ANGLE.to_radians().tan() / units::FT_TO_M as f32;
+        THRESHOLD_CROSSING_HEIGHT + height
     }
 
-    pub fn intercept_heading(&self, aircraft: &Aircraft) -> f32 {
-        // https://github.com/openscope/openscope/blob/2860a23834ec11311cea47bac199031d0844955b/src/assets/scripts/client/aircraft/AircraftModel.js#L1868
-        let course = self.runway.heading as f32;
-        let heading = aircraft.heading.current;
-        let xtk_angle = self.crosstrack_angle(&aircraft.position);
-        let bearing_to_runway = heading + xtk_angle;
-        let angle_away_from_loc = course - bearing_to_runway;
-
-        let minimum_intercept_angle = 10.;
-        let intercept_angle = spread(
-            angle_away_from_loc, // * -severity_of_correction,
-            -minimum_intercept_angle,
-            minimum_intercept_angle
-        );
-        let intercept_heading = course + intercept_angle;
-        if heading < course {
-            intercept_heading.max(heading)
-        } else if heading > course {
-            intercept_heading.min(heading)
-        } else {
-            intercept_heading
-        }
+    /// Whether the localizer can be received at the position.
+    pub fn is_in_coverage(&self, position: &glm::Vec2) -> bool {
+        let distance = self.along_track_distance(position);
+        let angle = self
+            .crosstrack_distance(position)
+            .atan2(distance)
+            .to_degrees()
+            .abs();
+        distance > 0.
+            && ((distance <= LOCALIZER_RANGE && angle <= LOCALIZER_COVERAGE)
+                || (distance <= LOCALIZER_WIDE_RANGE && angle <= LOCALIZER_WIDE_COVERAGE))
     }
 }
 
 #[derive(Clone, Debug)]
 pub struct Runway {
+    /// designator, e.g. "29"
+    pub name: String,
     /// offset from airport
     pub offset: glm::Vec2,
     /// bearing
@@ -390,8 +1025,27 @@ pub struct Runway {
     pub length: u32,
     /// width in meters
     pub width: u32,
-    /// in feet
+    /// highest altitude the localizer can be intercepted at, in feet
     pub ils_max_altitude: u32,
+    /// altitude departures climb to, in feet
+    pub initial_altitude: u32,
+    pub missed_approach: MissedApproach,
+}
+
+/// Published procedure for aircraft going around.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MissedApproach {
+    /// feet
+    pub altitude: u32,
+    pub route: MissedApproachRoute,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum MissedApproachRoute {
+    /// straight ahead, or turning onto another heading
+    Heading(u32),
+    /// proceed direct to the fix
+    Fix(String),
 }
 
 impl Runway {
@@ -406,22 +1060,34 @@ impl Runway {
         )
     }
 
+    /// Start of the runway, where departures line up and arrivals touch down.
+    pub fn threshold(&self, origin: glm::Vec2) -> glm::Vec2 {
+        // rotated runway line points
+        self.as_line(origin)[0]
+    }
+
+    /// Where departures wait before lining up, off to the side of the threshold.
+    pub fn holding_point(&self, origin: glm::Vec2) -> glm::Vec2 {
+        let side = heading_to_point(self.heading as i32 + 90);
+        self.threshold(origin) + side * (self.width as f32 * 2.)
+    }
+
     pub fn ils(&self, origin: glm::Vec2) -> ILS {
-        let origin = glm::vec2(
-            // rotated runway line points
-            self.as_line(origin)[0].x,
-            self.as_line(origin)[0].y,
-        );
         // note, state not automatically updated
         ILS {
-            origin,
+            origin: self.threshold(origin),
             runway: self.clone(),
         }
     }
 
-    // TODO
+    /// Whether the aircraft has reached the threshold along the runway
+    /// course, and is over the runway within its width of the centreline.
     pub fn has_landed(&self, origin: glm::Vec2, aircraft: &Aircraft) -> bool {
-        is_point_in_circle(aircraft.position, origin, 500.0)
+        let ils = self.ils(origin);
+        let along = ils.along_track_distance(&aircraft.position);
+        along <= 0.
+            && along >= -(self.length as f32)
+            && ils.crosstrack_distance(&aircraft.position).abs() <= self.width as f32
     }
 
     // FIXME: move me
@@ -452,6 +1118,8 @@ impl Runway {
 pub struct Airport {
     pub position: glm::Vec2,
     pub icao_code: String,
+    /// current ATIS information letter
+    pub atis: char,
     pub takeoff_runways: Vec<Runway>,
     pub landing_runways: Vec<Runway>,
 }
@@ -460,4 +1128,388 @@ impl Airport {
     pub fn origin(&self, runway: &Runway) -> glm::Vec2 {
         self.position + runway.offset
     }
+
+    pub fn takeoff_runway(&self, name: &str) -> Option<&Runway> {
+        self.takeoff_runways.iter().find(|r| r.name == name)
+    }
+
+    pub fn landing_runway(&self, name: &str) -> Option<&Runway> {
+        self.landing_runways.iter().find(|r| r.name == name)
+    }
+}
+
+/// An A320, CYP2202, flying at 200kt.
+#[cfg(test)]
+pub fn test_aircraft(position: glm::Vec2, heading: f32, altitude: f32) -> Aircraft {
+    Aircraft::new(
+        Callsign::from_string("CYP2202".into()).unwrap(),
+        crate::performance::test_performance("A320"),
+        position,
+        heading,
+        altitude,
+        200.,
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::performance::test_performance;
+
+    fn fly(aircraft: &mut Aircraft, duration: f32) {
+        let dt = 1. / 60.;
+        for _ in 0..(duration / dt).round() as u32 {
+            aircraft.update(dt);
+        }
+    }
+
+    fn assert_near(expected: f32, actual: f32) {
+        assert!((expected - actual).abs() < 1., "{} != {}", expected, actual);
+    }
+
+    #[test]
+    fn test_aircraft_speed_envelope() {
+        let mut aircraft = test_aircraft(glm::zero(), 0., 5000.);
+        aircraft.performance = test_performance("AT72");
+        aircraft.change_speed(250);
+        assert_eq!(230., aircraft.speed.intended);
+        aircraft.change_speed(100);
+        assert_eq!(110., aircraft.speed.intended);
+
+        // decelerates at 1kt/s
+        fly(&mut aircraft, 30.);
+        assert_near(170., aircraft.speed.current);
+        fly(&mut aircraft, 61.);
+        assert_eq!(110., aircraft.speed.current);
+
+        // accelerates at 1.5kt/s
+        aircraft.change_speed(200);
+        fly(&mut aircraft, 20.);
+        assert_near(140., aircraft.speed.current);
+    }
+
+    #[test]
+    fn test_aircraft_climb_rate_by_band() {
+        let mut a320 = test_aircraft(glm::zero(), 0., 5000.);
+        let mut at72 = test_aircraft(glm::zero(), 0., 5000.);
+        at72.performance = test_performance("AT72");
+        a320.change_altitude(15000);
+        at72.change_altitude(15000);
+
+        // 2500ft/min and 1500ft/min below 10000ft
+        fly(&mut a320, 60.);
+        fly(&mut at72, 60.);
+        assert_near(7500., a320.altitude.current);
+        assert_near(6500., at72.altitude.current);
+
+        // 2000ft/min above 10000ft
+        fly(&mut a320, 60.);
+        assert_near(10000., a320.altitude.current);
+        fly(&mut a320, 60.);
+        assert_near(12000., a320.altitude.current);
+
+        // descends at 2500ft/min above 10000ft, then 2000ft/min
+        a320.change_altitude(5000);
+        fly(&mut a320, 60.);
+        assert_near(9600., a320.altitude.current);
+    }
+
+    /// Vertical speeds are measured over a step, to within a few feet per
+    /// minute.
+    fn assert_vertical_speed(expected: f32, aircraft: &Aircraft) {
+        let actual = aircraft.vertical_speed;
+        assert!((expected - actual).abs() < 10., "{} != {}", expected, actual);
+    }
+
+    #[test]
+    fn test_aircraft_vertical_instructions() {
+        let mut aircraft = test_aircraft(glm::zero(), 0., 5000.);
+        aircraft.change_altitude(9000);
+        aircraft.command(AtcRequest(AtcCommand::VerticalRate(1500)));
+        fly(&mut aircraft, 60.);
+        assert_near(6500., aircraft.altitude.current);
+        assert_vertical_speed(1500., &aircraft);
+
+        // half as fast again as the usual 2500ft/min
+        aircraft.command(AtcRequest(AtcCommand::Expedite));
+        fly(&mut aircraft, 30.);
+        assert_near(8375., aircraft.altitude.current);
+        assert_vertical_speed(3750., &aircraft);
+
+        // level, and back to the usual rates after
+        fly(&mut aircraft, 30.);
+        assert_eq!(9000., aircraft.altitude.current);
+        assert_eq!(0., aircraft.vertical_speed);
+        assert_eq!(None, aircraft.vertical);
+
+        // no faster than expediting
+        aircraft.change_altitude(5000);
+        aircraft.command(AtcRequest(AtcCommand::VerticalRate(5000)));
+        fly(&mut aircraft, 30.);
+        assert_near(7500., aircraft.altitude.current);
+        assert_vertical_speed(-3000., &aircraft);
+
+        // a new clearance is flown at the usual rates
+        aircraft.command(AtcRequest(AtcCommand::ChangeAltitude(4000)));
+        assert_eq!(None, aircraft.vertical);
+        fly(&mut aircraft, 30.);
+        assert_vertical_speed(-2000., &aircraft);
+    }
+
+    #[test]
+    fn test_aircraft_vertical_instructions_when_level() {
+        let mut aircraft = test_aircraft(glm::zero(), 0., 5000.);
+        for cmd in [AtcCommand::VerticalRate(1500), AtcCommand::Expedite] {
+            assert_eq!(
+                AtcReply::Unable(cmd.clone(), "level at 5000 feet".into()),
+                aircraft.command(AtcRequest(cmd))
+            );
+            assert_eq!(None, aircraft.vertical);
+        }
+    }
+
+    #[test]
+    fn test_aircraft_handoff_on_departure() {
+        let handoff = AtcCommand::Handoff {
+            unit: "CTR".into(),
+            frequency: None,
+        };
+        let mut departure = test_aircraft(glm::zero(), 0., 0.);
+        for status in [
+            AircraftStatus::Taxi,
+            AircraftStatus::LinedUp,
+            AircraftStatus::Takeoff,
+        ] {
+            departure.status = status.clone();
+            assert_eq!(
+                AtcReply::Unable(handoff.clone(), "not clear of the runway".into()),
+                departure.command(AtcRequest(handoff.clone()))
+            );
+            assert_eq!(status, departure.status);
+        }
+
+        // once climbing out as ordinary traffic
+        departure.status = AircraftStatus::Flight;
+        assert_eq!(
+            AtcReply::Wilco(handoff.clone()),
+            departure.command(AtcRequest(handoff))
+        );
+        assert_eq!(AircraftStatus::HandedOff, departure.status);
+    }
+
+    #[test]
+    fn test_aircraft_turn_roll_in_and_out() {
+        let mut aircraft = test_aircraft(glm::zero(), 0., 5000.);
+        aircraft.change_heading(90., None);
+
+        // rolls in at 5 degrees per second
+        fly(&mut aircraft, 1.);
+        assert_near(5., aircraft.heading.bank);
+        fly(&mut aircraft, 9.);
+        assert_eq!(MAX_BANK_ANGLE, aircraft.heading.bank);
+
+        // turn rate from the bank angle and airspeed
+        let heading = aircraft.heading.current;
+        fly(&mut aircraft, 1.);
+        let turn_rate = GRAVITY * MAX_BANK_ANGLE.to_radians().tan() / aircraft.true_airspeed();
+        assert!((turn_rate.to_degrees() - (aircraft.heading.current - heading)).abs() < 0.01);
+
+        // rolls out onto the heading without overshooting
+        let mut max_heading: f32 = 0.;
+        for _ in 0..60 {
+            fly(&mut aircraft, 1.);
+            max_heading = max_heading.max(aircraft.heading.current);
+        }
+        assert_eq!(90., aircraft.heading.current);
+        assert_eq!(0., aircraft.heading.bank);
+        assert!(max_heading < 90.5);
+    }
+
+    #[test]
+    fn test_aircraft_turn_radius() {
+        // standard rate below ~200kt, limited by the maximum bank above
+        let mut slow = test_aircraft(glm::zero(), 0., 5000.);
+        slow.speed = AircraftParameter::new(160.);
+        let mut fast = test_aircraft(glm::zero(), 0., 5000.);
+        fast.speed = AircraftParameter::new(250.);
+        assert!(slow.max_bank() < MAX_BANK_ANGLE);
+        assert_eq!(MAX_BANK_ANGLE, fast.max_bank());
+        assert!(fast.turn_radius() > 2. * slow.turn_radius());
+
+        // follows an arc, a right turn to the south ends about a turn
+        // diameter to the east
+        for aircraft in [&mut slow, &mut fast] {
+            aircraft.change_heading(180., Some(TurnDirection::Right));
+            fly(aircraft, 150.);
+            assert_eq!(180., aircraft.heading.current);
+
+            let diameter = 2. * aircraft.turn_radius();
+            assert!((aircraft.position.x - diameter).abs() < diameter * 0.15);
+        }
+    }
+
+    #[test]
+    fn test_aircraft_turn_direction() {
+        let mut aircraft = test_aircraft(glm::zero(), 0., 5000.);
+        aircraft.change_heading(90., Some(TurnDirection::Left));
+        fly(&mut aircraft, 10.);
+        assert!(aircraft.heading.bank < 0.);
+        assert!(aircraft.heading.current > 270.);
+
+        fly(&mut aircraft, 180.);
+        assert_eq!(90., aircraft.heading.current);
+    }
+
+    fn test_ils() -> ILS {
+        let runway = Runway {
+            name: "27".into(),
+            offset: glm::zero(),
+            heading: 270,
+            length: 2000,
+            width: 45,
+            ils_max_altitude: 2000,
+            initial_altitude: 3000,
+            missed_approach: MissedApproach {
+                altitude: 3000,
+                route: MissedApproachRoute::Heading(270),
+            },
+        };
+        runway.ils(glm::zero())
+    }
+
+    #[test]
+    fn test_aircraft_wind_drift() {
+        let mut aircraft = test_aircraft(glm::zero(), 0., 5000.);
+        aircraft.altitude = AircraftParameter::new(0.);
+        assert_eq!(0., aircraft.track());
+        assert_near(200., aircraft.ground_speed());
+
+        // 20kt from the west drifts a northbound aircraft east
+        aircraft.wind = glm::vec2(20. * units::KT_TO_MS as f32, 0.);
+        assert_eq!(0., aircraft.heading.current);
+        assert_near(5.7, aircraft.track());
+        assert_near(201., aircraft.ground_speed());
+        fly(&mut aircraft, 60.);
+        assert_near(20. * units::KT_TO_MS as f32 * 60., aircraft.position.x);
+
+        // headwind
+        aircraft.wind = glm::vec2(0., -20. * units::KT_TO_MS as f32);
+        assert_eq!(0., aircraft.track());
+        assert_near(180., aircraft.ground_speed());
+    }
+
+    #[test]
+    fn test_aircraft_wind_correction_angle() {
+        let mut aircraft = test_aircraft(glm::zero(), 0., 5000.);
+        assert_eq!(0., aircraft.wind_correction_angle(0.));
+
+        // wind from the west, heading left of a northbound course
+        aircraft.wind = glm::vec2(20. * units::KT_TO_MS as f32, 0.);
+        let angle = aircraft.wind_correction_angle(0.);
+        assert!(angle < -5. && angle > -6.);
+        assert_eq!(0., aircraft.wind_correction_angle(90.).round());
+        assert_near(-angle, aircraft.wind_correction_angle(180.));
+
+        // holding the corrected heading keeps the track on the course
+        aircraft.heading = HeadingParameter::new(degrees_normalize(angle));
+        assert_near(0., short_angle_distance(0., aircraft.track()));
+    }
+
+    #[test]
+    fn test_aircraft_fly_direct() {
+        let mut aircraft = test_aircraft(glm::zero(), 0., 5000.);
+        aircraft.command(AtcRequest(AtcCommand::DirectTo("PHA".into())));
+        assert_eq!(Some("PHA".into()), aircraft.direct);
+
+        // fix to the east, crabbing into a northerly wind
+        aircraft.wind = glm::vec2(0., -20. * units::KT_TO_MS as f32);
+        aircraft.fly_direct(glm::vec2(20_000., 0.));
+        let heading = aircraft.heading.intended();
+        assert!(heading < 90. && heading > 84., "{}", heading);
+
+        // a heading cancels the direct
+        aircraft.command(AtcRequest(AtcCommand::ChangeHeading(180)));
+        assert_eq!(None, aircraft.direct);
+        assert_eq!(180., aircraft.heading.intended());
+    }
+
+    #[test]
+    fn test_aircraft_fly_direct_passed() {
+        let mut aircraft = test_aircraft(glm::zero(), 0., 5000.);
+        aircraft.direct = Some("PHA".into());
+        aircraft.fly_direct(glm::vec2(500., 500.));
+        assert_eq!(None, aircraft.direct);
+        assert_eq!(0., aircraft.heading.intended());
+
+        // behind, too close to turn back to
+        aircraft.direct = Some("PHA".into());
+        aircraft.fly_direct(glm::vec2(0., -1500.));
+        assert_eq!(None, aircraft.direct);
+
+        // behind, with room to turn
+        aircraft.direct = Some("PHA".into());
+        aircraft.fly_direct(glm::vec2(0., -20_000.));
+        assert_eq!(Some("PHA".into()), aircraft.direct);
+        assert_eq!(180., aircraft.heading.intended());
+    }
+
+    #[test]
+    fn test_ils_geometry() {
+        let ils = test_ils();
+        // threshold is at the east end, approaches come from the east
+        assert!(glm::distance(&glm::vec2(1000., 0.), &ils.origin) < 0.01);
+        assert_eq!(270., ils.course());
+        assert_eq!(500., ils.along_track_distance(&glm::vec2(1500., 0.)).round());
+        assert_eq!(-500., ils.along_track_distance(&glm::vec2(500., 0.)).round());
+
+        // right of the centreline is north, looking west towards the runway
+        assert_eq!(200., ils.crosstrack_distance(&glm::vec2(3000., 200.)).round());
+        assert_eq!(-200., ils.crosstrack_distance(&glm::vec2(3000., -200.)).round());
+    }
+
+    #[test]
+    fn test_ils_glideslope_altitude() {
+        let ils = test_ils();
+        assert_eq!(THRESHOLD_CROSSING_HEIGHT, ils.glideslope_altitude(&ils.origin));
+        // past the threshold
+        assert_eq!(THRESHOLD_CROSSING_HEIGHT, ils.glideslope_altitude(&glm::zero()));
+        // ~318ft per nm
+        let one_nm = ils.origin + glm::vec2(units::NM_to_KM as f32 * 1000., 0.);
+        assert_eq!(368., ils.glideslope_altitude(&one_nm).round());
+        // independent of the distance off the centreline
+        assert_eq!(
+            ils.glideslope_altitude(&one_nm),
+            ils.glideslope_altitude(&(one_nm + glm::vec2(0., 500.)))
+        );
+    }
+
+    #[test]
+    fn test_runway_has_landed() {
+        let ils = test_ils();
+        let mut aircraft = test_aircraft(glm::zero(), 0., 5000.);
+        let at = |aircraft: &mut Aircraft, position: glm::Vec2| {
+            aircraft.position = position;
+            ils.runway.has_landed(glm::zero(), aircraft)
+        };
+        // short of the threshold, then past it
+        assert!(!at(&mut aircraft, ils.origin + glm::vec2(100., 0.)));
+        assert!(at(&mut aircraft, ils.origin - glm::vec2(1., 0.)));
+        assert!(at(&mut aircraft, glm::vec2(0., 20.)));
+        // beside the runway, or beyond its end
+        assert!(!at(&mut aircraft, glm::vec2(0., 200.)));
+        assert!(!at(&mut aircraft, glm::vec2(-1100., 0.)));
+    }
+
+    #[test]
+    fn test_ils_coverage() {
+        let ils = test_ils();
+        let nm = units::NM_to_KM as f32 * 1000.;
+        assert!(ils.is_in_coverage(&(ils.origin + glm::vec2(15. * nm, 0.))));
+        // 30 degrees off the centreline, only covered close in
+        assert!(ils.is_in_coverage(&(ils.origin + glm::vec2(5. * nm, 2.5 * nm))));
+        assert!(!ils.is_in_coverage(&(ils.origin + glm::vec2(15. * nm, 7.5 * nm))));
+        // behind the localizer
+        assert!(!ils.is_in_coverage(&(ils.origin - glm::vec2(1. * nm, 0.))));
+        assert!(!ils.is_in_coverage(&(ils.origin + glm::vec2(20. * nm, 0.))));
+    }
 }
@@ -0,0 +1,98 @@
+//! Volumes of airspace, with a lateral boundary, a floor and a ceiling.
+
+use serde::Deserialize;
+
+use crate::geom::{distance_segment_and_point, is_point_in_polygon, polygon_edges};
+
+/// What an airspace is, and so what flying into it means.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AirspaceClass {
+    /// the airspace under our control
+    Sector,
+    /// control zone around the airport
+    Ctr,
+    /// not to be entered
+    Restricted,
+    /// entered at the pilot's own risk
+    Danger,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Airspace {
+    pub name: String,
+    pub class: AirspaceClass,
+    /// vertices in meters east and north of the airport reference point, in
+    /// order around it
+    pub boundary: Vec<glm::Vec2>,
+    /// feet
+    pub floor: f32,
+    /// feet, infinite when unlimited
+    pub ceiling: f32,
+}
+
+impl Airspace {
+    /// Whether `position` at `altitude` in feet is inside it.
+    pub fn contains(&self, position: &glm::Vec2, altitude: f32) -> bool {
+        (self.floor..=self.ceiling).contains(&altitude) && self.is_over(position)
+    }
+
+    /// Whether `position` is within the lateral boundary, at any altitude.
+    pub fn is_over(&self, position: &glm::Vec2) -> bool {
+        is_point_in_polygon(*position, &self.boundary)
+    }
+
+    /// Meters from `position` to the lateral boundary, negative inside.
+    pub fn distance(&self, position: &glm::Vec2) -> f32 {
+        let distance = polygon_edges(&self.boundary)
+            .map(|edge| distance_segment_and_point(&edge, position))
+            .fold(f32::INFINITY, f32::min);
+        if self.is_over(position) {
+            -distance
+        } else {
+            distance
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn test_airspace() -> Airspace {
+        Airspace {
+            name: "LCR1".into(),
+            class: AirspaceClass::Restricted,
+            boundary: vec![
+                glm::vec2(-1000., -1000.),
+                glm::vec2(1000., -1000.),
+                glm::vec2(1000., 1000.),
+                glm::vec2(-1000., 1000.),
+            ],
+            floor: 2000.,
+            ceiling: 6000.,
+        }
+    }
+
+    #[test]
+    fn test_airspace_contains() {
+        let airspace = test_airspace();
+        assert!(airspace.contains(&glm::zero(), 2000.));
+        assert!(airspace.contains(&glm::vec2(900., -900.), 6000.));
+        // below, above and beside it
+        assert!(!airspace.contains(&glm::zero(), 1900.));
+        assert!(!airspace.contains(&glm::zero(), 6100.));
+        assert!(!airspace.contains(&glm::vec2(1100., 0.), 4000.));
+        assert!(airspace.is_over(&glm::zero()));
+    }
+
+    #[test]
+    fn test_airspace_distance() {
+        let airspace = test_airspace();
+        assert_eq!(-1000., airspace.distance(&glm::zero()));
+        assert_eq!(-100., airspace.distance(&glm::vec2(0., 900.)));
+        assert_eq!(500., airspace.distance(&glm::vec2(1500., 0.)));
+        // off a corner
+        assert_eq!(500., airspace.distance(&glm::vec2(1300., 1400.)));
+    }
+}
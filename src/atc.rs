@@ -1,39 +1,217 @@
-use crate::aircraft::Aircraft;
+use crate::aircraft::Callsign;
 use crate::cli::CliPrompt;
 use crate::command::AtcCommand;
+use crate::simulation::{CommandError, Simulation};
 use crate::tts;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// feet an altitude is misheard by
+const ALTITUDE_ERROR: u32 = 1000;
+/// degrees a heading is misheard by
+const HEADING_ERROR: i32 = 20;
+/// knots a speed is misheard by
+const SPEED_ERROR: u32 = 20;
 
 #[derive(Debug)]
 pub struct Atc {
     tts: Option<tts::TextToSpeech>,
+    /// fraction of readbacks, between 0 and 1, with an error in them
+    readback_errors: f32,
+    rng: StdRng,
 }
 
 impl Atc {
-    pub fn new(enable_tts: bool) -> Self {
+    pub fn new(enable_tts: bool, readback_errors: f32) -> Self {
         Self {
             tts: if enable_tts {
                 Some(tts::TextToSpeech::new())
             } else {
                 None
             },
+            readback_errors,
+            rng: StdRng::from_entropy(),
         }
     }
 
-    pub fn command(&mut self, cli: &mut CliPrompt, aircraft: &mut Aircraft, cmd: AtcCommand) {
+    /// Give the command to the aircraft with the callsign, which reads back and
+    /// flies what the pilot heard, not always what was said. A command the
+    /// simulation rejects isn't transmitted.
+    pub fn command(
+        &mut self,
+        cli: &mut CliPrompt,
+        sim: &mut Simulation,
+        callsign: &Callsign,
+        cmd: AtcCommand,
+    ) -> Result<AtcReply, CommandError> {
+        sim.check_command(callsign, &cmd)?;
+
+        // between controllers, the pilot calls once it's done
+        if cmd == AtcCommand::AcceptHandoff {
+            cli.output(format!("Accepted handoff of {}", callsign));
+            return sim.command(callsign, cmd);
+        }
+
         // request
-        cli.output(format!("==> {}, {}", aircraft.callsign, cmd.as_string()));
+        cli.output(format!("==> {}, {}", callsign, cmd.as_string()));
+        self.say(format!("{}, {}", callsign.spoken(), cmd.as_string()));
+
+        let reply = sim.command(callsign, self.hear(cmd))?;
+
+        // readback
+        cli.output(format!("<== {}, {}", reply.as_string(), callsign));
+        self.say(format!("{}, {}", reply.as_string(), callsign.spoken()));
+        Ok(reply)
+    }
+
+    fn say(&mut self, s: String) {
         if let Some(tts) = &mut self.tts {
-            tts.say(format!(
-                "{}, {}",
-                aircraft.callsign.spoken(),
-                cmd.as_string()
-            ))
-            .expect("failed to send tts message");
+            tts.say(s).expect("failed to send tts message");
         }
+    }
 
-        aircraft.command(AtcRequest(cmd));
+    /// The command as the pilot heard it.
+    fn hear(&mut self, cmd: AtcCommand) -> AtcCommand {
+        if self.rng.gen::<f32>() < self.readback_errors {
+            misheard(&cmd, &mut self.rng).unwrap_or(cmd)
+        } else {
+            cmd
+        }
     }
 }
 
+/// The command with a wrong value in it, for commands that have a value to
+/// get wrong.
+fn misheard(cmd: &AtcCommand, rng: &mut impl Rng) -> Option<AtcCommand> {
+    use AtcCommand::*;
+    let higher: bool = rng.gen();
+    let heading = |heading: i32| {
+        let error = if higher {
+            HEADING_ERROR
+        } else {
+            -HEADING_ERROR
+        };
+        match (heading + error).rem_euclid(360) {
+            0 => 360,
+            heading => heading,
+        }
+    };
+    Some(match cmd {
+        ChangeHeading(hdg) => ChangeHeading(heading(*hdg)),
+        ChangeHeadingWithTurnDirection(hdg, direction) => {
+            ChangeHeadingWithTurnDirection(heading(*hdg), *direction)
+        }
+        ChangeAltitude(alt) if higher || *alt <= ALTITUDE_ERROR => {
+            ChangeAltitude(alt + ALTITUDE_ERROR)
+        }
+        ChangeAltitude(alt) => ChangeAltitude(alt - ALTITUDE_ERROR),
+        ChangeSpeed(speed) if higher || *speed <= SPEED_ERROR => ChangeSpeed(speed + SPEED_ERROR),
+        ChangeSpeed(speed) => ChangeSpeed(speed - SPEED_ERROR),
+        Conditional { condition, command } => Conditional {
+            condition: condition.clone(),
+            command: Box::new(misheard(command, rng)?),
+        },
+        _ => return None,
+    })
+}
+
 pub struct AtcRequest(pub AtcCommand);
-pub struct AtcReply(pub AtcCommand);
+
+/// What the pilot read back.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AtcReply {
+    /// and is flying
+    Wilco(AtcCommand),
+    /// and can't fly, for the reason given
+    Unable(AtcCommand, String),
+}
+
+impl AtcReply {
+    pub fn command(&self) -> &AtcCommand {
+        match self {
+            AtcReply::Wilco(cmd) | AtcReply::Unable(cmd, _) => cmd,
+        }
+    }
+
+    /// What the pilot says, e.g. "left heading 270".
+    pub fn as_string(&self) -> String {
+        match self {
+            AtcReply::Wilco(cmd) => cmd.readback(),
+            AtcReply::Unable(cmd, reason) => format!("unable {}, {}", cmd.as_string(), reason),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::aircraft::TurnDirection;
+    use crate::command::Condition;
+
+    fn atc(readback_errors: f32) -> Atc {
+        Atc {
+            tts: None,
+            readback_errors,
+            rng: StdRng::seed_from_u64(7),
+        }
+    }
+
+    #[test]
+    fn test_misheard() {
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..20 {
+            match misheard(&AtcCommand::ChangeAltitude(3000), &mut rng) {
+                Some(AtcCommand::ChangeAltitude(alt)) => assert!(alt == 2000 || alt == 4000),
+                other => panic!("{:?}", other),
+            }
+            match misheard(&AtcCommand::ChangeAltitude(1000), &mut rng) {
+                Some(AtcCommand::ChangeAltitude(alt)) => assert_eq!(2000, alt),
+                other => panic!("{:?}", other),
+            }
+            match misheard(
+                &AtcCommand::ChangeHeadingWithTurnDirection(350, TurnDirection::Left),
+                &mut rng,
+            ) {
+                Some(AtcCommand::ChangeHeadingWithTurnDirection(hdg, TurnDirection::Left)) => {
+                    assert!(hdg == 330 || hdg == 10)
+                }
+                other => panic!("{:?}", other),
+            }
+            match misheard(&AtcCommand::ChangeHeading(20), &mut rng) {
+                Some(AtcCommand::ChangeHeading(hdg)) => assert!(hdg == 360 || hdg == 40),
+                other => panic!("{:?}", other),
+            }
+        }
+        let conditional = AtcCommand::Conditional {
+            condition: Condition::Time(1.),
+            command: Box::new(AtcCommand::ChangeSpeed(200)),
+        };
+        assert!(matches!(
+            misheard(&conditional, &mut rng),
+            Some(AtcCommand::Conditional { condition: Condition::Time(_), command })
+                if *command != AtcCommand::ChangeSpeed(200)
+        ));
+        // nothing to get wrong
+        assert_eq!(None, misheard(&AtcCommand::GoAround, &mut rng));
+        assert_eq!(
+            None,
+            misheard(&AtcCommand::DirectTo("KOLOS".into()), &mut rng)
+        );
+    }
+
+    #[test]
+    fn test_readback_errors() {
+        let cmd = AtcCommand::ChangeAltitude(5000);
+        let mut never = atc(0.);
+        assert!((0..100).all(|_| never.hear(cmd.clone()) == cmd));
+
+        let mut always = atc(1.);
+        assert!((0..100).all(|_| always.hear(cmd.clone()) != cmd));
+        // unless there's nothing to mishear
+        assert_eq!(AtcCommand::LineUp, always.hear(AtcCommand::LineUp));
+
+        let mut some = atc(0.2);
+        let errors = (0..1000).filter(|_| some.hear(cmd.clone()) != cmd).count();
+        assert!((150..250).contains(&errors), "{}", errors);
+    }
+}
@@ -58,6 +58,23 @@ impl CliPrompt {
         write!(buf, "{}\n", s.to_string()).unwrap()
     }
 
+    /// Write a line straight away instead of waiting for the next prompt,
+    /// e.g. for alerts raised while the controller is typing.
+    pub fn alert<S: ToString>(&mut self, s: S) {
+        let (out, _) = &*self.output;
+        let mut buf = out.lock().unwrap();
+        write!(buf, "\n{}\n", s.to_string()).unwrap();
+        buf.flush().unwrap();
+    }
+
+    /// Echo the input with a marker under the offending position,
+    /// followed by the error message.
+    pub fn output_error<S: ToString>(&mut self, input: &str, position: usize, error: S) {
+        self.output(input);
+        self.output(format!("{}^", " ".repeat(position)));
+        self.output(format!("Error: {}", error.to_string()));
+    }
+
     /// unblock waiting for output, start receiving input again
     pub fn flush(&mut self) {
         let (_, cvar) = &*self.output;
@@ -1,56 +1,494 @@
 use crate::aircraft::{Callsign, TurnDirection};
+use crate::phraseology;
+use serde::{Deserialize, Serialize};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
+pub enum ParseErrorKind {
+    /// a command was given without its argument, e.g. "HDG"
+    MissingArgument {
+        command: String,
+        argument: &'static str,
+    },
+    InvalidNumber(String),
+    HeadingOutOfRange(i32),
+    InvalidTurnDirection(String),
+    InvalidCondition(String),
+    InvalidCallsign(String),
+    /// a phrase went on with something other than the word it needs
+    ExpectedWord {
+        expected: &'static str,
+        found: Option<String>,
+    },
+    UnknownToken(String),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseError {
+    pub kind: ParseErrorKind,
+    /// column of the offending token in the input, in characters
+    pub position: usize,
+}
+
+impl ParseError {
+    pub(crate) fn new(kind: ParseErrorKind, position: usize) -> Self {
+        Self { kind, position }
+    }
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        use ParseErrorKind::*;
+        match &self.kind {
+            MissingArgument { command, argument } => write!(f, "{} requires {}", command, argument),
+            InvalidNumber(s) => write!(f, "{} is not a valid number", s),
+            HeadingOutOfRange(hdg) => write!(f, "heading {} is not between 0 and 360", hdg),
+            InvalidTurnDirection(s) => write!(f, "{} is not a turn direction, L or R", s),
+            InvalidCondition(s) => write!(f, "{} is not a condition, ALT, FIX, RWY or TIME", s),
+            InvalidCallsign(s) => write!(f, "{} is not a valid callsign", s),
+            ExpectedWord {
+                expected,
+                found: Some(found),
+            } => write!(f, "expected {} but found {}", expected, found),
+            ExpectedWord {
+                expected,
+                found: None,
+            } => write!(f, "expected {}", expected),
+            UnknownToken(s) => write!(f, "unknown command {}", s),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Token<'a> {
+    pub text: &'a str,
+    pub position: usize,
+}
+
+/// Split on whitespace, keeping track of where each token starts.
+pub(crate) fn tokenize(s: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    for (i, c) in s.char_indices().chain(std::iter::once((s.len(), ' '))) {
+        match (c.is_whitespace(), start) {
+            (true, Some(position)) => {
+                tokens.push(Token {
+                    text: &s[position..i],
+                    position,
+                });
+                start = None;
+            }
+            (false, None) => start = Some(i),
+            _ => {}
+        }
+    }
+    tokens
+}
+
+fn argument<'a, T: std::str::FromStr>(
+    iter: &mut impl Iterator<Item = &'a Token<'a>>,
+    command: &Token,
+    argument: &'static str,
+) -> Result<(T, usize), ParseError> {
+    let token = iter.next().ok_or_else(|| {
+        ParseError::new(
+            ParseErrorKind::MissingArgument {
+                command: command.text.to_string(),
+                argument,
+            },
+            command.position,
+        )
+    })?;
+    token
+        .text
+        .parse::<T>()
+        .map(|value| (value, token.position))
+        .map_err(|_| {
+            ParseError::new(
+                ParseErrorKind::InvalidNumber(token.text.to_string()),
+                token.position,
+            )
+        })
+}
+
+fn heading<'a>(
+    iter: &mut impl Iterator<Item = &'a Token<'a>>,
+    command: &Token,
+) -> Result<i32, ParseError> {
+    let (hdg, position) = argument::<i32>(iter, command, "a heading")?;
+    if (0..=360).contains(&hdg) {
+        Ok(hdg)
+    } else {
+        Err(ParseError::new(
+            ParseErrorKind::HeadingOutOfRange(hdg),
+            position,
+        ))
+    }
+}
+
+/// A name argument, e.g. a fix.
+fn name<'a>(
+    iter: &mut impl Iterator<Item = &'a Token<'a>>,
+    command: &Token,
+    argument: &'static str,
+) -> Result<String, ParseError> {
+    iter.next().map(|token| token.text.to_string()).ok_or_else(|| {
+        ParseError::new(
+            ParseErrorKind::MissingArgument {
+                command: command.text.to_string(),
+                argument,
+            },
+            command.position,
+        )
+    })
+}
+
+fn turn_direction<'a>(
+    iter: &mut impl Iterator<Item = &'a Token<'a>>,
+    command: &Token,
+) -> Result<TurnDirection, ParseError> {
+    let token = iter.next().ok_or_else(|| {
+        ParseError::new(
+            ParseErrorKind::MissingArgument {
+                command: command.text.to_string(),
+                argument: "a turn direction",
+            },
+            command.position,
+        )
+    })?;
+    match token.text {
+        "L" | "LEFT" => Ok(TurnDirection::Left),
+        "R" | "RIGHT" => Ok(TurnDirection::Right),
+        other => Err(ParseError::new(
+            ParseErrorKind::InvalidTurnDirection(other.to_string()),
+            token.position,
+        )),
+    }
+}
+
+/// An optional positive number of minutes, left for the next command when
+/// it isn't a number.
+fn minutes<'a, I: Iterator<Item = &'a Token<'a>>>(
+    iter: &mut std::iter::Peekable<I>,
+) -> Result<Option<f32>, ParseError> {
+    let token = match iter.peek() {
+        Some(token) if token.text.starts_with(|c: char| c.is_ascii_digit() || c == '-') => token,
+        _ => return Ok(None),
+    };
+    match token.text.parse::<f32>() {
+        Ok(minutes) if minutes > 0. => {
+            iter.next();
+            Ok(Some(minutes))
+        }
+        _ => Err(ParseError::new(
+            ParseErrorKind::InvalidNumber(token.text.to_string()),
+            token.position,
+        )),
+    }
+}
+
+/// A frequency in MHz, kept as written.
+fn frequency<'a>(
+    iter: &mut impl Iterator<Item = &'a Token<'a>>,
+    command: &Token,
+) -> Result<String, ParseError> {
+    let token = iter.next().ok_or_else(|| {
+        ParseError::new(
+            ParseErrorKind::MissingArgument {
+                command: command.text.to_string(),
+                argument: "a frequency",
+            },
+            command.position,
+        )
+    })?;
+    match token.text.parse::<f32>() {
+        Ok(frequency) if frequency > 0. => Ok(token.text.to_string()),
+        _ => Err(ParseError::new(
+            ParseErrorKind::InvalidNumber(token.text.to_string()),
+            token.position,
+        )),
+    }
+}
+
+/// A positive number, e.g. a distance.
+fn positive<'a>(
+    iter: &mut impl Iterator<Item = &'a Token<'a>>,
+    command: &Token,
+    argument_name: &'static str,
+) -> Result<f32, ParseError> {
+    match argument::<f32>(iter, command, argument_name)? {
+        (value, _) if value > 0. => Ok(value),
+        (value, position) => Err(ParseError::new(
+            ParseErrorKind::InvalidNumber(value.to_string()),
+            position,
+        )),
+    }
+}
+
+fn condition<'a>(
+    iter: &mut impl Iterator<Item = &'a Token<'a>>,
+    command: &Token,
+) -> Result<Condition, ParseError> {
+    let token = iter.next().ok_or_else(|| {
+        ParseError::new(
+            ParseErrorKind::MissingArgument {
+                command: command.text.to_string(),
+                argument: "a condition",
+            },
+            command.position,
+        )
+    })?;
+    Ok(match token.text {
+        "ALT" => Condition::Altitude(argument(iter, token, "an altitude")?.0),
+        "FIX" => Condition::FixDistance(
+            name(iter, token, "a fix")?,
+            positive(iter, token, "a distance")?,
+        ),
+        "RWY" => Condition::ThresholdDistance(
+            name(iter, token, "a runway")?,
+            positive(iter, token, "a distance")?,
+        ),
+        "TIME" => Condition::Time(positive(iter, token, "minutes")?),
+        other => {
+            return Err(ParseError::new(
+                ParseErrorKind::InvalidCondition(other.to_string()),
+                token.position,
+            ))
+        }
+    })
+}
+
+/// When a deferred instruction is to be carried out.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Condition {
+    /// passing the altitude in feet, climbing or descending
+    Altitude(u32),
+    /// within nautical miles of the named fix
+    FixDistance(String, f32),
+    /// within nautical miles of the threshold of the runway
+    ThresholdDistance(String, f32),
+    /// minutes after being given
+    Time(f32),
+}
+
+impl Condition {
+    pub fn as_string(&self) -> String {
+        match self {
+            Condition::Altitude(altitude) => format!("after passing {} feet", altitude),
+            Condition::FixDistance(fix, distance) => format!("at {} miles from {}", distance, fix),
+            Condition::ThresholdDistance(runway, distance) => {
+                format!("at {} miles from runway {}", distance, runway)
+            }
+            Condition::Time(minutes) => format!("in {} minutes", minutes),
+        }
+    }
+
+    /// Short form for the data block.
+    pub fn tag(&self) -> String {
+        match self {
+            Condition::Altitude(altitude) => format!("A{}", altitude / 100),
+            Condition::FixDistance(fix, distance) => format!("{}/{}", fix, distance),
+            Condition::ThresholdDistance(runway, distance) => format!("R{}/{}", runway, distance),
+            Condition::Time(minutes) => format!("{}MIN", minutes),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum AtcCommand {
     ChangeHeading(i32),
     ChangeHeadingWithTurnDirection(i32, TurnDirection),
     ChangeAltitude(u32),
+    /// climb or descend at feet per minute
+    VerticalRate(u32),
+    /// climb or descend as fast as possible
+    Expedite,
+    /// be level at the cleared altitude by the named fix
+    ReachBy(String),
     ChangeSpeed(u32),
-    ClearedToLand(bool),
+    /// cleared for the ILS approach to the runway
+    ClearedForApproach(String),
+    /// cleared to land on the runway, or the clearance cancelled
+    ClearedToLand(Option<String>),
+    /// break off the approach and fly the missed approach
+    GoAround,
+    LineUp,
+    ClearedForTakeoff,
+    /// fly straight to the named fix
+    DirectTo(String),
+    /// hold at a fix on an inbound course, legs in minutes when not the
+    /// standard length
+    Hold {
+        fix: String,
+        course: i32,
+        direction: TurnDirection,
+        leg_time: Option<f32>,
+    },
+    /// change to the unit's frequency, given when not the one it's known by
+    Handoff {
+        unit: String,
+        frequency: Option<String>,
+    },
+    /// take control of an aircraft offered by the adjacent unit
+    AcceptHandoff,
+    /// carry out the command once the condition is met
+    Conditional {
+        condition: Condition,
+        command: Box<AtcCommand>,
+    },
 }
 
 impl AtcCommand {
-    fn from_parts(parts: &Vec<&str>) -> Vec<AtcCommand> {
+    const KEYWORDS: &'static [&'static str] = &[
+        "ILS", "LND", "GA", "HDG", "TURNL", "TURNR", "ALT", "RATE", "EXP", "BY", "SPD", "LUW",
+        "CTO", "DCT", "HOLD", "WHEN", "HO", "CONTACT", "ACC",
+    ];
+
+    fn is_keyword(s: &str) -> bool {
+        Self::KEYWORDS.contains(&s)
+    }
+
+    fn from_parts(parts: &[Token]) -> Result<Vec<AtcCommand>, ParseError> {
         let mut commands = Vec::new();
-        let mut iter = parts.iter();
+        let mut iter = parts.iter().peekable();
         while let Some(cmd_str) = iter.next() {
-            let cmd = match *cmd_str {
-                "LND" => Some(AtcCommand::ClearedToLand(true)),
-                "HDG" => {
-                    // TODO: error handling
-                    let hdg = iter.next().unwrap();
-                    Some(AtcCommand::ChangeHeading(hdg.parse::<i32>().unwrap()))
-                }
-                "TURNL" => {
-                    let hdg = iter.next().unwrap();
-                    Some(AtcCommand::ChangeHeadingWithTurnDirection(
-                        hdg.parse::<i32>().unwrap(),
-                        TurnDirection::Left,
-                    ))
-                }
-                "TURNR" => {
-                    let hdg = iter.next().unwrap();
-                    Some(AtcCommand::ChangeHeadingWithTurnDirection(
-                        hdg.parse::<i32>().unwrap(),
-                        TurnDirection::Right,
-                    ))
-                }
-                "ALT" => {
-                    let alt = iter.next().unwrap();
-                    Some(AtcCommand::ChangeAltitude(alt.parse::<u32>().unwrap()))
+            commands.push(Self::from_token(cmd_str, &mut iter)?);
+        }
+        Ok(commands)
+    }
+
+    /// The command named by `cmd_str`, taking its arguments from `iter`.
+    fn from_token<'a, I: Iterator<Item = &'a Token<'a>>>(
+        cmd_str: &Token,
+        iter: &mut std::iter::Peekable<I>,
+    ) -> Result<AtcCommand, ParseError> {
+        Ok(match cmd_str.text {
+            "ILS" => AtcCommand::ClearedForApproach(name(iter, cmd_str, "a runway")?),
+            "LND" => AtcCommand::ClearedToLand(Some(name(iter, cmd_str, "a runway")?)),
+            "GA" => AtcCommand::GoAround,
+            "LUW" => AtcCommand::LineUp,
+            "CTO" => AtcCommand::ClearedForTakeoff,
+            "HDG" => AtcCommand::ChangeHeading(heading(iter, cmd_str)?),
+            "TURNL" => AtcCommand::ChangeHeadingWithTurnDirection(
+                heading(iter, cmd_str)?,
+                TurnDirection::Left,
+            ),
+            "TURNR" => AtcCommand::ChangeHeadingWithTurnDirection(
+                heading(iter, cmd_str)?,
+                TurnDirection::Right,
+            ),
+            "ALT" => AtcCommand::ChangeAltitude(argument(iter, cmd_str, "an altitude")?.0),
+            "RATE" => AtcCommand::VerticalRate(argument(iter, cmd_str, "a rate")?.0),
+            "EXP" => AtcCommand::Expedite,
+            "BY" => AtcCommand::ReachBy(name(iter, cmd_str, "a fix")?),
+            "SPD" => AtcCommand::ChangeSpeed(argument(iter, cmd_str, "a speed")?.0),
+            "DCT" => AtcCommand::DirectTo(name(iter, cmd_str, "a fix")?),
+            "HOLD" => AtcCommand::Hold {
+                fix: name(iter, cmd_str, "a fix")?,
+                course: heading(iter, cmd_str)?,
+                direction: turn_direction(iter, cmd_str)?,
+                leg_time: minutes(iter)?,
+            },
+            "HO" => AtcCommand::Handoff {
+                unit: name(iter, cmd_str, "a unit")?,
+                frequency: None,
+            },
+            "CONTACT" => AtcCommand::Handoff {
+                unit: name(iter, cmd_str, "a unit")?,
+                frequency: Some(frequency(iter, cmd_str)?),
+            },
+            "ACC" => AtcCommand::AcceptHandoff,
+            "WHEN" => {
+                let condition = condition(iter, cmd_str)?;
+                let next = iter.next().ok_or_else(|| {
+                    ParseError::new(
+                        ParseErrorKind::MissingArgument {
+                            command: cmd_str.text.to_string(),
+                            argument: "a command",
+                        },
+                        cmd_str.position,
+                    )
+                })?;
+                AtcCommand::Conditional {
+                    condition,
+                    command: Box::new(Self::from_token(next, iter)?),
                 }
-                "SPD" => {
-                    let spd = iter.next().unwrap();
-                    Some(AtcCommand::ChangeSpeed(spd.parse::<u32>().unwrap()))
+            }
+            other => {
+                return Err(ParseError::new(
+                    ParseErrorKind::UnknownToken(other.to_string()),
+                    cmd_str.position,
+                ))
+            }
+        })
+    }
+
+    /// The fixes the command refers to, including in its condition.
+    pub fn fixes(&self) -> Vec<&str> {
+        match self {
+            AtcCommand::DirectTo(fix) | AtcCommand::Hold { fix, .. } | AtcCommand::ReachBy(fix) => {
+                vec![fix]
+            }
+            AtcCommand::Conditional { condition, command } => {
+                let mut fixes = command.fixes();
+                if let Condition::FixDistance(fix, _) = condition {
+                    fixes.push(fix);
                 }
-                _ => None,
-            };
+                fixes
+            }
+            _ => vec![],
+        }
+    }
 
-            if let Some(cmd) = cmd {
-                commands.push(cmd);
+    /// The runways the command refers to, including in its condition.
+    pub fn runways(&self) -> Vec<&str> {
+        match self {
+            AtcCommand::ClearedForApproach(runway) | AtcCommand::ClearedToLand(Some(runway)) => {
+                vec![runway]
+            }
+            AtcCommand::Conditional { condition, command } => {
+                let mut runways = command.runways();
+                if let Condition::ThresholdDistance(runway, _) = condition {
+                    runways.push(runway);
+                }
+                runways
             }
+            _ => vec![],
+        }
+    }
+
+    /// The unit handed off to and the frequency given, including when
+    /// conditional.
+    pub fn handoff(&self) -> Option<(&str, Option<&str>)> {
+        match self {
+            AtcCommand::Handoff { unit, frequency } => Some((unit, frequency.as_deref())),
+            AtcCommand::Conditional { command, .. } => command.handoff(),
+            _ => None,
+        }
+    }
+
+    /// The keyword the command is given with.
+    pub fn keyword(&self) -> &'static str {
+        use AtcCommand::*;
+        match self {
+            ChangeHeading(_) => "HDG",
+            ChangeHeadingWithTurnDirection(_, TurnDirection::Left) => "TURNL",
+            ChangeHeadingWithTurnDirection(_, TurnDirection::Right) => "TURNR",
+            ChangeAltitude(_) => "ALT",
+            VerticalRate(_) => "RATE",
+            Expedite => "EXP",
+            ReachBy(_) => "BY",
+            ChangeSpeed(_) => "SPD",
+            ClearedForApproach(_) => "ILS",
+            ClearedToLand(_) => "LND",
+            GoAround => "GA",
+            LineUp => "LUW",
+            ClearedForTakeoff => "CTO",
+            DirectTo(_) => "DCT",
+            Hold { .. } => "HOLD",
+            Handoff { .. } => "HO",
+            AcceptHandoff => "ACC",
+            Conditional { .. } => "WHEN",
         }
-        commands
     }
 
     pub fn as_string(&self) -> String {
@@ -62,17 +500,97 @@ impl AtcCommand {
                 format!("turn {} to {}", direction, heading)
             }
             ChangeAltitude(alt) => format!("altitude to {} feet", alt),
+            VerticalRate(rate) => format!("rate {} feet per minute", rate),
+            Expedite => String::from("expedite"),
+            ReachBy(fix) => format!("reach by {}", fix),
             ChangeSpeed(speed) => format!("speed to {}", speed),
-            ClearedToLand(cleared) => String::from(if *cleared {
-                "cleared to land"
-            } else {
-                "clearance to land cancelled"
-            }),
+            ClearedForApproach(runway) => format!("cleared ILS approach runway {}", runway),
+            ClearedToLand(Some(runway)) => format!("cleared to land runway {}", runway),
+            ClearedToLand(None) => String::from("clearance to land cancelled"),
+            GoAround => String::from("go around"),
+            LineUp => String::from("line up and wait"),
+            ClearedForTakeoff => String::from("cleared for takeoff"),
+            DirectTo(fix) => format!("proceed direct {}", fix),
+            Hold {
+                fix,
+                course,
+                direction,
+                leg_time,
+            } => {
+                let legs = leg_time
+                    .map(|minutes| format!(", {} minute legs", minutes))
+                    .unwrap_or_default();
+                format!(
+                    "hold at {}, inbound course {}, {} turns{}",
+                    fix, course, direction, legs
+                )
+            }
+            Handoff {
+                unit,
+                frequency: Some(frequency),
+            } => format!("contact {} {}", unit, frequency),
+            Handoff {
+                unit,
+                frequency: None,
+            } => format!("contact {}", unit),
+            AcceptHandoff => String::from("handoff accepted"),
+            Conditional { condition, command } => {
+                format!("{}, {}", condition.as_string(), command.as_string())
+            }
+        }
+    }
+
+    /// How the pilot reads the command back, e.g. "left heading 270".
+    pub fn readback(&self) -> String {
+        use AtcCommand::*;
+        match self {
+            ChangeHeading(heading) => format!("heading {:03}", heading),
+            ChangeHeadingWithTurnDirection(heading, direction) => {
+                format!("{} heading {:03}", direction, heading)
+            }
+            ChangeAltitude(alt) => format!("maintain {} feet", alt),
+            VerticalRate(rate) => format!("{} feet per minute", rate),
+            Expedite => String::from("expediting"),
+            ReachBy(fix) => format!("level by {}", fix),
+            ChangeSpeed(speed) => format!("speed {} knots", speed),
+            ClearedForApproach(runway) => format!("cleared ILS runway {}", runway),
+            ClearedToLand(Some(runway)) => format!("cleared to land runway {}", runway),
+            ClearedToLand(None) => String::from("landing clearance cancelled"),
+            GoAround => String::from("going around"),
+            LineUp => String::from("lining up and waiting"),
+            ClearedForTakeoff => String::from("cleared for takeoff"),
+            DirectTo(fix) => format!("direct {}", fix),
+            Hold {
+                fix,
+                course,
+                direction,
+                leg_time,
+            } => {
+                let legs = leg_time
+                    .map(|minutes| format!(", {} minute legs", minutes))
+                    .unwrap_or_default();
+                format!(
+                    "holding at {}, inbound {:03}, {} turns{}",
+                    fix, course, direction, legs
+                )
+            }
+            Handoff {
+                unit,
+                frequency: Some(frequency),
+            } => format!("{} {}", unit, frequency),
+            Handoff {
+                unit,
+                frequency: None,
+            } => format!("contact {}", unit),
+            AcceptHandoff => String::from("handoff accepted"),
+            Conditional { condition, command } => {
+                format!("{}, {}", condition.as_string(), command.readback())
+            }
         }
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum CommCommand {
     ChangeAircraft(Callsign),
     // ChangeAircrafyByIndex(usize),
@@ -80,52 +598,637 @@ pub enum CommCommand {
 }
 
 impl CommCommand {
-    fn from_parts(parts: &Vec<&str>) -> Vec<CommCommand> {
+    fn from_parts(parts: &[Token]) -> Result<Vec<CommCommand>, ParseError> {
+        let callsign = |token: &Token| {
+            Callsign::from_string(token.text.to_string())
+                .map(CommCommand::ChangeAircraft)
+                .ok_or_else(|| {
+                    ParseError::new(
+                        ParseErrorKind::InvalidCallsign(token.text.to_string()),
+                        token.position,
+                    )
+                })
+        };
+
         let mut commands = Vec::new();
         let mut iter = parts.iter();
         while let Some(cmd_str) = iter.next() {
-            let cmd = match *cmd_str {
+            let cmd = match cmd_str.text {
                 "LIST" => {
                     // todo: add other subcommands
-                    Some(CommCommand::ListAircraft)
+                    CommCommand::ListAircraft
                 }
                 "SEL" => {
-                    let aircraft_code = iter.next().unwrap();
-                    Callsign::from_string(aircraft_code.to_string())
-                        .map(|callsign| CommCommand::ChangeAircraft(callsign))
+                    let aircraft_code = iter.next().ok_or_else(|| {
+                        ParseError::new(
+                            ParseErrorKind::MissingArgument {
+                                command: cmd_str.text.to_string(),
+                                argument: "a callsign",
+                            },
+                            cmd_str.position,
+                        )
+                    })?;
+                    callsign(aircraft_code)?
                 }
-                other => Callsign::from_string(other.to_string())
-                    .map(|callsign| CommCommand::ChangeAircraft(callsign)),
+                other => callsign(cmd_str).map_err(|mut e| {
+                    e.kind = ParseErrorKind::UnknownToken(other.to_string());
+                    e
+                })?,
             };
-
-            if let Some(cmd) = cmd {
-                commands.push(cmd);
-            }
+            commands.push(cmd);
         }
-        commands
+        Ok(commands)
     }
 }
 
-#[derive(Clone, Debug)]
+/// Column in `s` of a byte offset into its upper-cased form, which may be
+/// longer, e.g. "ß" becoming "SS".
+fn column(s: &str, position: usize) -> usize {
+    let mut end = 0;
+    s.chars()
+        .position(|c| {
+            end += c.to_uppercase().map(char::len_utf8).sum::<usize>();
+            end > position
+        })
+        .unwrap_or_else(|| s.chars().count())
+}
+
+#[derive(Clone, Debug, PartialEq)]
 pub enum CliCommand {
+    /// for the selected aircraft
     Atc(AtcCommand),
+    /// for the named aircraft, leaving the selection as it is
+    Addressed(Callsign, AtcCommand),
     Comm(CommCommand),
     // Options(OptionsCommand),
 }
 
 impl CliCommand {
-    pub fn from_string(s: String) -> Vec<CliCommand> {
-        let cmd_str = s.trim().to_uppercase();
-        let command_parts: Vec<&str> = cmd_str.split(' ').collect();
-        // atc commands have precedence
-        let atc_cmd = AtcCommand::from_parts(&command_parts);
-        if atc_cmd.is_empty() {
-            CommCommand::from_parts(&command_parts)
-                .iter()
-                .map(|c| CliCommand::Comm(c.clone()))
-                .collect()
-        } else {
-            atc_cmd.iter().map(|c| CliCommand::Atc(c.clone())).collect()
+    /// Parse a line of transmissions separated by ";".
+    pub fn from_string(s: String) -> Result<Vec<CliCommand>, ParseError> {
+        let cmd_str = s.to_uppercase();
+        let mut commands = Vec::new();
+        let mut offset = 0;
+        for transmission in cmd_str.split(';') {
+            let parsed = Self::from_transmission(transmission).map_err(|mut e| {
+                e.position = column(&s, e.position + offset);
+                e
+            })?;
+            commands.extend(parsed);
+            offset += transmission.len() + 1;
         }
+        Ok(commands)
+    }
+
+    fn from_transmission(s: &str) -> Result<Vec<CliCommand>, ParseError> {
+        if phraseology::is_phrase(s) {
+            return phraseology::parse(s);
+        }
+        let command_parts = tokenize(s);
+        match command_parts.as_slice() {
+            // a callsign followed by what to tell it
+            [first, second, ..]
+                if !AtcCommand::is_keyword(first.text) && AtcCommand::is_keyword(second.text) =>
+            {
+                let callsign = Callsign::from_string(first.text.to_string()).ok_or_else(|| {
+                    ParseError::new(
+                        ParseErrorKind::InvalidCallsign(first.text.to_string()),
+                        first.position,
+                    )
+                })?;
+                Ok(AtcCommand::from_parts(&command_parts[1..])?
+                    .into_iter()
+                    .map(|cmd| CliCommand::Addressed(callsign.clone(), cmd))
+                    .collect())
+            }
+            // atc commands have precedence
+            [first, ..] if AtcCommand::is_keyword(first.text) => {
+                Ok(AtcCommand::from_parts(&command_parts)?
+                    .into_iter()
+                    .map(CliCommand::Atc)
+                    .collect())
+            }
+            _ => Ok(CommCommand::from_parts(&command_parts)?
+                .into_iter()
+                .map(CliCommand::Comm)
+                .collect()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse(s: &str) -> Result<Vec<CliCommand>, ParseError> {
+        CliCommand::from_string(s.to_string())
+    }
+
+    fn error(s: &str) -> (ParseErrorKind, usize) {
+        let e = parse(s).unwrap_err();
+        (e.kind, e.position)
+    }
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            vec![
+                Token {
+                    text: "HDG",
+                    position: 1
+                },
+                Token {
+                    text: "180",
+                    position: 6
+                },
+            ],
+            tokenize(" HDG  180 ")
+        );
+        assert!(tokenize("   ").is_empty());
+    }
+
+    #[test]
+    fn test_parse_atc_commands() {
+        assert_eq!(
+            vec![
+                CliCommand::Atc(AtcCommand::ChangeHeadingWithTurnDirection(
+                    270,
+                    TurnDirection::Left
+                )),
+                CliCommand::Atc(AtcCommand::ChangeAltitude(3000)),
+                CliCommand::Atc(AtcCommand::ChangeSpeed(180)),
+                CliCommand::Atc(AtcCommand::ClearedToLand(Some("29".into()))),
+            ],
+            parse("turnl 270 ALT 3000 SPD 180 LND 29").unwrap()
+        );
+        assert_eq!(
+            vec![
+                CliCommand::Atc(AtcCommand::ClearedForApproach("11L".into())),
+                CliCommand::Atc(AtcCommand::ChangeSpeed(160)),
+            ],
+            parse("ils 11l SPD 160").unwrap()
+        );
+        assert_eq!(
+            vec![
+                CliCommand::Atc(AtcCommand::GoAround),
+                CliCommand::Atc(AtcCommand::ChangeAltitude(3000)),
+            ],
+            parse("GA ALT 3000").unwrap()
+        );
+        assert_eq!(
+            vec![
+                CliCommand::Atc(AtcCommand::ChangeAltitude(5000)),
+                CliCommand::Atc(AtcCommand::VerticalRate(1500)),
+            ],
+            parse("ALT 5000 RATE 1500").unwrap()
+        );
+        assert_eq!(
+            vec![
+                CliCommand::Atc(AtcCommand::ChangeAltitude(3000)),
+                CliCommand::Atc(AtcCommand::ReachBy("KOLOS".into())),
+                CliCommand::Atc(AtcCommand::Expedite),
+            ],
+            parse("alt 3000 by kolos exp").unwrap()
+        );
+        assert_eq!(
+            vec![CliCommand::Atc(AtcCommand::ChangeHeading(360))],
+            parse("HDG 360").unwrap()
+        );
+        assert_eq!(
+            vec![
+                CliCommand::Atc(AtcCommand::LineUp),
+                CliCommand::Atc(AtcCommand::ClearedForTakeoff),
+            ],
+            parse("LUW CTO").unwrap()
+        );
+        assert_eq!(
+            vec![
+                CliCommand::Atc(AtcCommand::DirectTo("PHA".into())),
+                CliCommand::Atc(AtcCommand::ChangeAltitude(4000)),
+            ],
+            parse("dct pha ALT 4000").unwrap()
+        );
+        assert_eq!(
+            vec![
+                CliCommand::Atc(AtcCommand::Hold {
+                    fix: "PHA".into(),
+                    course: 285,
+                    direction: TurnDirection::Right,
+                    leg_time: None,
+                }),
+                CliCommand::Atc(AtcCommand::ChangeAltitude(5000)),
+            ],
+            parse("HOLD PHA 285 R ALT 5000").unwrap()
+        );
+        assert_eq!(
+            vec![CliCommand::Atc(AtcCommand::Hold {
+                fix: "KOLOS".into(),
+                course: 90,
+                direction: TurnDirection::Left,
+                leg_time: Some(1.5),
+            })],
+            parse("hold kolos 090 left 1.5").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_conditional() {
+        let conditional = |condition, command| {
+            CliCommand::Atc(AtcCommand::Conditional {
+                condition,
+                command: Box::new(command),
+            })
+        };
+        assert_eq!(
+            vec![
+                conditional(
+                    Condition::Altitude(10000),
+                    AtcCommand::ChangeHeadingWithTurnDirection(270, TurnDirection::Left)
+                ),
+                CliCommand::Atc(AtcCommand::ChangeSpeed(250)),
+            ],
+            parse("WHEN ALT 10000 TURNL 270 SPD 250").unwrap()
+        );
+        assert_eq!(
+            vec![conditional(
+                Condition::FixDistance("KOLOS".into(), 10.),
+                AtcCommand::ChangeSpeed(180)
+            )],
+            parse("when fix kolos 10 spd 180").unwrap()
+        );
+        assert_eq!(
+            vec![conditional(
+                Condition::ThresholdDistance("29".into(), 4.5),
+                AtcCommand::ClearedToLand(Some("29".into()))
+            )],
+            parse("WHEN RWY 29 4.5 LND 29").unwrap()
+        );
+        assert_eq!(
+            vec![conditional(
+                Condition::Time(2.),
+                AtcCommand::DirectTo("PHA".into())
+            )],
+            parse("WHEN TIME 2 DCT PHA").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_conditional_errors() {
+        assert_eq!(
+            (
+                ParseErrorKind::MissingArgument {
+                    command: "WHEN".into(),
+                    argument: "a condition"
+                },
+                0
+            ),
+            error("WHEN")
+        );
+        assert_eq!(
+            (
+                ParseErrorKind::MissingArgument {
+                    command: "WHEN".into(),
+                    argument: "a command"
+                },
+                0
+            ),
+            error("WHEN ALT 10000")
+        );
+        assert_eq!(
+            (ParseErrorKind::InvalidCondition("DME".into()), 5),
+            error("WHEN DME 10 SPD 180")
+        );
+        assert_eq!(
+            (ParseErrorKind::InvalidNumber("-1".into()), 15),
+            error("WHEN FIX KOLOS -1 SPD 180")
+        );
+        assert_eq!(
+            (ParseErrorKind::UnknownToken("FDX261".into()), 14),
+            error("WHEN ALT 3000 FDX261")
+        );
+    }
+
+    #[test]
+    fn test_command_references() {
+        let command = parse("WHEN FIX KOLOS 5 DCT PHA").unwrap().remove(0);
+        let command = match command {
+            CliCommand::Atc(command) => command,
+            _ => unreachable!(),
+        };
+        assert_eq!(vec!["PHA", "KOLOS"], command.fixes());
+        assert!(command.runways().is_empty());
+        assert_eq!(
+            vec!["29"],
+            AtcCommand::ClearedForApproach("29".into()).runways()
+        );
+        assert_eq!("WHEN", command.keyword());
+        assert_eq!(
+            "at 5 miles from KOLOS, proceed direct PHA",
+            command.as_string()
+        );
+    }
+
+    #[test]
+    fn test_readback() {
+        assert_eq!(
+            "left heading 090",
+            AtcCommand::ChangeHeadingWithTurnDirection(90, TurnDirection::Left).readback()
+        );
+        assert_eq!(
+            "maintain 3000 feet",
+            AtcCommand::ChangeAltitude(3000).readback()
+        );
+        assert_eq!(
+            "after passing 5000 feet, speed 210 knots",
+            AtcCommand::Conditional {
+                condition: Condition::Altitude(5000),
+                command: Box::new(AtcCommand::ChangeSpeed(210)),
+            }
+            .readback()
+        );
+        assert_eq!(
+            "holding at PHA, inbound 005, right turns",
+            AtcCommand::Hold {
+                fix: "PHA".into(),
+                course: 5,
+                direction: TurnDirection::Right,
+                leg_time: None,
+            }
+            .readback()
+        );
+    }
+
+    #[test]
+    fn test_parse_handoff() {
+        let handoff = |unit: &str, frequency: Option<&str>| AtcCommand::Handoff {
+            unit: unit.into(),
+            frequency: frequency.map(String::from),
+        };
+        assert_eq!(
+            vec![CliCommand::Atc(handoff("TWR", None))],
+            parse("ho twr").unwrap()
+        );
+        assert_eq!(
+            vec![CliCommand::Atc(handoff("DEP", Some("120.2")))],
+            parse("CONTACT DEP 120.2").unwrap()
+        );
+        let callsign = Callsign::from_string("CYP2202".into()).unwrap();
+        assert_eq!(
+            vec![CliCommand::Addressed(callsign, AtcCommand::AcceptHandoff)],
+            parse("CYP2202 ACC").unwrap()
+        );
+        let conditional = match parse("WHEN FIX KOLOS 5 CONTACT APP 124.375").unwrap().remove(0) {
+            CliCommand::Atc(command) => command,
+            _ => unreachable!(),
+        };
+        assert_eq!(Some(("APP", Some("124.375"))), conditional.handoff());
+        assert_eq!("HO", handoff("TWR", None).keyword());
+        assert_eq!("contact DEP 120.2", handoff("DEP", Some("120.2")).as_string());
+        assert_eq!("DEP 120.2", handoff("DEP", Some("120.2")).readback());
+
+        assert_eq!(
+            (
+                ParseErrorKind::MissingArgument {
+                    command: "HO".into(),
+                    argument: "a unit"
+                },
+                0
+            ),
+            error("HO")
+        );
+        assert_eq!(
+            (
+                ParseErrorKind::MissingArgument {
+                    command: "CONTACT".into(),
+                    argument: "a frequency"
+                },
+                12
+            ),
+            error("WHEN TIME 1 CONTACT TWR")
+        );
+        assert_eq!(
+            (ParseErrorKind::InvalidNumber("TOWER".into()), 24),
+            error("WHEN TIME 1 CONTACT TWR TOWER")
+        );
+    }
+
+    #[test]
+    fn test_parse_hold_errors() {
+        assert_eq!(
+            (
+                ParseErrorKind::MissingArgument {
+                    command: "HOLD".into(),
+                    argument: "a turn direction"
+                },
+                0
+            ),
+            error("HOLD PHA 285")
+        );
+        assert_eq!(
+            (ParseErrorKind::InvalidTurnDirection("X".into()), 13),
+            error("HOLD PHA 285 X")
+        );
+        assert_eq!(
+            (ParseErrorKind::InvalidNumber("0".into()), 15),
+            error("HOLD PHA 285 R 0")
+        );
+        assert_eq!(
+            (ParseErrorKind::HeadingOutOfRange(400), 9),
+            error("HOLD PHA 400 R")
+        );
+    }
+
+    #[test]
+    fn test_parse_comm_commands() {
+        assert_eq!(
+            vec![CliCommand::Comm(CommCommand::ListAircraft)],
+            parse("LIST").unwrap()
+        );
+        assert_eq!(
+            vec![CliCommand::Comm(CommCommand::ChangeAircraft(
+                Callsign::from_string("CYP2202".into()).unwrap()
+            ))],
+            parse("sel cyp2202").unwrap()
+        );
+        assert_eq!(
+            vec![CliCommand::Comm(CommCommand::ChangeAircraft(
+                Callsign::from_string("FDX261".into()).unwrap()
+            ))],
+            parse("FDX261").unwrap()
+        );
+        assert!(parse("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parse_addressed() {
+        let cyp = Callsign::from_string("CYP2202".into()).unwrap();
+        let fdx = Callsign::from_string("FDX261".into()).unwrap();
+        assert_eq!(
+            vec![
+                CliCommand::Addressed(cyp.clone(), AtcCommand::ChangeHeading(180)),
+                CliCommand::Addressed(cyp.clone(), AtcCommand::ChangeAltitude(4000)),
+            ],
+            parse("CYP2202 HDG 180 ALT 4000").unwrap()
+        );
+        assert_eq!(
+            vec![
+                CliCommand::Addressed(cyp, AtcCommand::ChangeSpeed(180)),
+                CliCommand::Addressed(fdx.clone(), AtcCommand::ClearedForTakeoff),
+                CliCommand::Atc(AtcCommand::GoAround),
+                CliCommand::Comm(CommCommand::ChangeAircraft(fdx.clone())),
+                CliCommand::Addressed(fdx, AtcCommand::LineUp),
+            ],
+            parse("cyp2202 spd 180; fdx261 cto;GA ; fdx261; FDX261 line up and wait;").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_addressed_errors() {
+        assert_eq!(
+            (ParseErrorKind::InvalidCallsign("FOO".into()), 0),
+            error("FOO HDG 180")
+        );
+        // positions are within the whole line
+        assert_eq!(
+            (ParseErrorKind::HeadingOutOfRange(400), 28),
+            error("CYP2202 HDG 180; FDX261 HDG 400")
+        );
+        assert_eq!(
+            (ParseErrorKind::UnknownToken("FOO".into()), 9),
+            error("LIST; GA;FOO")
+        );
+    }
+
+    #[test]
+    fn test_parse_error_column() {
+        // characters rather than bytes, of the input as typed
+        assert_eq!(
+            (ParseErrorKind::HeadingOutOfRange(400), 5),
+            error("HDG\u{a0}\u{a0}400")
+        );
+        assert_eq!(
+            (ParseErrorKind::UnknownToken("STRASSE".into()), 5),
+            error("list;straße")
+        );
+    }
+
+    #[test]
+    fn test_parse_missing_argument() {
+        assert_eq!(
+            (
+                ParseErrorKind::MissingArgument {
+                    command: "HDG".into(),
+                    argument: "a heading"
+                },
+                0
+            ),
+            error("HDG")
+        );
+        assert_eq!(
+            (
+                ParseErrorKind::MissingArgument {
+                    command: "SPD".into(),
+                    argument: "a speed"
+                },
+                9
+            ),
+            error("ALT 3000 SPD")
+        );
+        assert_eq!(
+            (
+                ParseErrorKind::MissingArgument {
+                    command: "SEL".into(),
+                    argument: "a callsign"
+                },
+                0
+            ),
+            error("SEL")
+        );
+        assert_eq!(
+            (
+                ParseErrorKind::MissingArgument {
+                    command: "DCT".into(),
+                    argument: "a fix"
+                },
+                8
+            ),
+            error("HDG 180 DCT")
+        );
+        assert_eq!(
+            (
+                ParseErrorKind::MissingArgument {
+                    command: "LND".into(),
+                    argument: "a runway"
+                },
+                0
+            ),
+            error("LND")
+        );
+        assert_eq!(
+            (
+                ParseErrorKind::MissingArgument {
+                    command: "RATE".into(),
+                    argument: "a rate"
+                },
+                9
+            ),
+            error("ALT 3000 RATE")
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_number() {
+        assert_eq!(
+            (ParseErrorKind::InvalidNumber("ABC".into()), 4),
+            error("ALT abc")
+        );
+        assert_eq!(
+            (ParseErrorKind::InvalidNumber("-100".into()), 4),
+            error("SPD -100")
+        );
+        // a command where a number is expected
+        assert_eq!(
+            (ParseErrorKind::InvalidNumber("ALT".into()), 6),
+            error("TURNR ALT 3000")
+        );
+    }
+
+    #[test]
+    fn test_parse_heading_out_of_range() {
+        assert_eq!(
+            (ParseErrorKind::HeadingOutOfRange(400), 4),
+            error("HDG 400")
+        );
+        assert_eq!(
+            (ParseErrorKind::HeadingOutOfRange(-10), 6),
+            error("TURNL -10")
+        );
+    }
+
+    #[test]
+    fn test_parse_unknown_token() {
+        assert_eq!(
+            (ParseErrorKind::UnknownToken("FOO".into()), 8),
+            error("HDG 180 FOO")
+        );
+        assert_eq!((ParseErrorKind::UnknownToken("FOO".into()), 0), error("FOO"));
+        // callsign numbers start with a digit
+        assert_eq!(
+            (ParseErrorKind::UnknownToken("HELLO".into()), 0),
+            error("HELLO")
+        );
+        assert_eq!(
+            (ParseErrorKind::InvalidCallsign("BAD".into()), 4),
+            error("SEL BAD")
+        );
+    }
+
+    #[test]
+    fn test_parse_error_message() {
+        assert_eq!("HDG requires a heading", parse("HDG").unwrap_err().to_string());
+        assert_eq!(
+            "heading 400 is not between 0 and 360",
+            parse("HDG 400").unwrap_err().to_string()
+        );
     }
 }
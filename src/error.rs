@@ -0,0 +1,17 @@
+//! Conversions shared by the error types of the data files.
+
+/// `From` each source error into the variant of an error enum wrapping it,
+/// e.g. `impl_from!(ScenarioError { Io(std::io::Error), Parse(toml::de::Error) });`
+macro_rules! impl_from {
+    ($error:ident { $($variant:ident($source:ty)),+ $(,)? }) => {
+        $(
+            impl From<$source> for $error {
+                fn from(e: $source) -> Self {
+                    Self::$variant(e)
+                }
+            }
+        )+
+    };
+}
+
+pub(crate) use impl_from;
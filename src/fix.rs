@@ -0,0 +1,81 @@
+//! Named points aircraft can be sent direct to.
+
+use std::collections::HashMap;
+
+use crate::geo::LatLon;
+
+#[derive(Clone, Debug)]
+pub struct Fix {
+    pub name: String,
+    /// meters east and north of the airport reference point
+    pub position: glm::Vec2,
+}
+
+impl Fix {
+    /// A fix at a real world position, projected into the game world around
+    /// `origin`.
+    pub fn new(name: &str, latlon: LatLon, origin: &LatLon) -> Self {
+        let position = latlon.to_game_world(origin);
+        Self {
+            name: name.to_uppercase(),
+            position: glm::vec2(position.x, position.y),
+        }
+    }
+}
+
+/// Every fix of a scenario, by name.
+#[derive(Clone, Debug, Default)]
+pub struct FixDatabase {
+    fixes: HashMap<String, Fix>,
+}
+
+impl FixDatabase {
+    /// Add a fix, returning false if one with the same name exists.
+    pub fn insert(&mut self, fix: Fix) -> bool {
+        if self.fixes.contains_key(&fix.name) {
+            false
+        } else {
+            self.fixes.insert(fix.name.clone(), fix);
+            true
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Fix> {
+        self.fixes.get(&name.to_uppercase())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Fix> {
+        self.fixes.values()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn lcph() -> LatLon {
+        LatLon::new(34.718078, 32.485072)
+    }
+
+    #[test]
+    fn test_fix_projection() {
+        let origin = lcph();
+        let fix = Fix::new("pha", origin.destination(90., 10_000.), &origin);
+        assert_eq!("PHA", fix.name);
+        assert_eq!(10_000., fix.position.x.round());
+        assert_eq!(0., fix.position.y.round());
+    }
+
+    #[test]
+    fn test_fix_database() {
+        let origin = lcph();
+        let mut fixes = FixDatabase::default();
+        assert!(fixes.insert(Fix::new("PHA", origin, &origin)));
+        assert!(fixes.insert(Fix::new("KOLOS", origin.destination(0., 5000.), &origin)));
+        assert!(!fixes.insert(Fix::new("pha", origin, &origin)));
+
+        assert_eq!(2, fixes.iter().count());
+        assert_eq!(5000., fixes.get("kolos").unwrap().position.y.round());
+        assert!(fixes.get("BAMOS").is_none());
+    }
+}
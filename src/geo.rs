@@ -1,5 +1,6 @@
 use geographiclib_rs::{DirectGeodesic, Geodesic, InverseGeodesic};
 use std::fmt::Display;
+use std::str::FromStr;
 
 use crate::geom::{point_distance, point_to_heading, Point};
 
@@ -11,6 +12,18 @@ pub enum Cardinal {
     West,
 }
 
+impl Cardinal {
+    fn from_char(c: char) -> Option<Self> {
+        match c.to_ascii_uppercase() {
+            'N' => Some(Cardinal::North),
+            'S' => Some(Cardinal::South),
+            'E' => Some(Cardinal::East),
+            'W' => Some(Cardinal::West),
+            _ => None,
+        }
+    }
+}
+
 impl Display for Cardinal {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let s = match self {
@@ -93,6 +106,67 @@ impl DMS {
     }
 }
 
+#[derive(Clone, Debug, PartialEq)]
+pub enum ParseDMSError {
+    /// not degrees, minutes and seconds
+    Format,
+    /// degrees above 180, or minutes or seconds of 60 and above
+    OutOfRange,
+}
+
+impl Display for ParseDMSError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Format => write!(f, "expected degrees, minutes and seconds"),
+            Self::OutOfRange => write!(f, "degrees, minutes or seconds out of range"),
+        }
+    }
+}
+
+/// Parse degrees, minutes and seconds separated by spaces or by the
+/// degree, minute and second symbols, with the cardinal before or after,
+/// e.g. `34 43 05.08 N` or `34°43'5.08"N`.
+impl FromStr for DMS {
+    type Err = ParseDMSError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (cardinal, s) = match (s.chars().next(), s.chars().last()) {
+            (_, Some(c)) if Cardinal::from_char(c).is_some() => {
+                (Cardinal::from_char(c), &s[..s.len() - c.len_utf8()])
+            }
+            (Some(c), _) if Cardinal::from_char(c).is_some() => {
+                (Cardinal::from_char(c), &s[c.len_utf8()..])
+            }
+            _ => (None, s),
+        };
+
+        let parts = s
+            .split(|c: char| c.is_whitespace() || "°'\"".contains(c))
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>();
+        let (degrees, minutes, seconds) = match parts[..] {
+            [d, m, s] => (
+                d.parse::<u16>().map_err(|_| ParseDMSError::Format)?,
+                m.parse::<u8>().map_err(|_| ParseDMSError::Format)?,
+                s.parse::<f64>().map_err(|_| ParseDMSError::Format)?,
+            ),
+            _ => return Err(ParseDMSError::Format),
+        };
+
+        if degrees > 180 || minutes >= 60 || !(0.0..60.0).contains(&seconds) {
+            Err(ParseDMSError::OutOfRange)
+        } else {
+            Ok(Self {
+                degrees,
+                minutes,
+                seconds,
+                cardinal,
+            })
+        }
+    }
+}
+
 impl Display for DMS {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         if let Some(cardinal) = self.cardinal {
@@ -191,6 +265,33 @@ mod test {
         lon: 33.624722,
     };
 
+    #[test]
+    fn test_dms_from_str() {
+        let expected = DMS::new(34, 43, 5.08, Cardinal::North);
+        assert_eq!(Ok(expected), "34 43 05.08 N".parse());
+        assert_eq!(Ok(expected), "34°43'5.08\"N".parse());
+        assert_eq!(Ok(expected), "N34 43 5.08".parse());
+        assert_eq!(Ok(expected), expected.to_string().parse());
+
+        let west: DMS = "032 29 06.26W".parse().unwrap();
+        assert_eq!(Some(Cardinal::West), west.cardinal);
+        assert_eq!(-32.485, round_decimal(west.to_degrees(), 3));
+
+        let no_cardinal: DMS = "34 43 05".parse().unwrap();
+        assert_eq!(None, no_cardinal.cardinal);
+    }
+
+    #[test]
+    fn test_dms_from_str_errors() {
+        assert_eq!(Err(ParseDMSError::Format), "".parse::<DMS>());
+        assert_eq!(Err(ParseDMSError::Format), "34 43 N".parse::<DMS>());
+        assert_eq!(Err(ParseDMSError::Format), "34 43 05.08 X".parse::<DMS>());
+        assert_eq!(Err(ParseDMSError::Format), "34.5 43 05 N".parse::<DMS>());
+        assert_eq!(Err(ParseDMSError::OutOfRange), "34 60 05 N".parse::<DMS>());
+        assert_eq!(Err(ParseDMSError::OutOfRange), "34 43 60 N".parse::<DMS>());
+        assert_eq!(Err(ParseDMSError::OutOfRange), "181 00 00 E".parse::<DMS>());
+    }
+
     #[test]
     fn test_latlon_from_game_world() {
         let offset = LatLon::from_game_world(LCPH, glm::vec2(0.0, 100.0));
@@ -38,11 +38,6 @@ pub fn point_distance(p1: &glm::Vec2, p2: &glm::Vec2) -> f32 {
     glm::distance(&p1, &p2)
 }
 
-// Angle between 2 points, in radians
-pub fn point_angle(p1: &glm::Vec2, p2: &glm::Vec2) -> f32 {
-    glm::angle(&p1, &p2)
-}
-
 pub fn is_point_in_circle(point: glm::Vec2, circle_pos: glm::Vec2, circle_radius: f32) -> bool {
     (point.x - circle_pos.x).powi(2) + (point.y - circle_pos.y).powi(2) < circle_radius.powi(2)
 }
@@ -51,17 +46,6 @@ pub fn sign(p1: glm::Vec2, p2: glm::Vec2, p3: glm::Vec2) -> f32 {
     (p1.x - p3.x) * (p2.y - p3.y) - (p2.x - p3.x) * (p1.y - p3.y)
 }
 
-pub fn is_point_in_triangle(point: glm::Vec2, triangle: &[glm::Vec2]) -> bool {
-    let d1 = sign(point, triangle[0], triangle[1]);
-    let d2 = sign(point, triangle[1], triangle[2]);
-    let d3 = sign(point, triangle[2], triangle[0]);
-
-    let has_neg = (d1 < 0.0) || (d2 < 0.0) || (d3 < 0.0);
-    let has_pos = (d1 > 0.0) || (d2 > 0.0) || (d3 > 0.0);
-
-    !(has_neg && has_pos)
-}
-
 /// Rotate a point by an angle (in radians) around an origin (clockwise)
 pub fn rotate_point(origin: glm::Vec2, point: glm::Vec2, angle: f32) -> glm::Vec2 {
     let cos = angle.cos(); 
@@ -110,6 +94,66 @@ pub fn distance_line_and_point(line: &[glm::Vec2], p: &glm::Vec2) -> f32 {
     (a * p.x + b * p.y + c).abs() / (a * a + b * b).sqrt()
 }
 
+/// Distance from a point to the nearest point of a line segment
+pub fn distance_segment_and_point(segment: &[glm::Vec2], p: &glm::Vec2) -> f32 {
+    let v = segment[0];
+    let w = segment[1];
+    let length_squared = glm::distance2(&v, &w);
+    if length_squared == 0. {
+        return glm::distance(&v, p);
+    }
+
+    // projection of the point onto the segment, as a fraction along it
+    let t = clamp((p - v).dot(&(w - v)) / length_squared, 0., 1.);
+    glm::distance(&(v + (w - v) * t), p)
+}
+
+/// Whether two line segments cross, or touch
+pub fn segments_intersect(s1: &[glm::Vec2], s2: &[glm::Vec2]) -> bool {
+    let d1 = sign(s1[0], s1[1], s2[0]);
+    let d2 = sign(s1[0], s1[1], s2[1]);
+    let d3 = sign(s2[0], s2[1], s1[0]);
+    let d4 = sign(s2[0], s2[1], s1[1]);
+
+    let straddles = |a: f32, b: f32| (a > 0. && b < 0.) || (a < 0. && b > 0.);
+    if straddles(d1, d2) && straddles(d3, d4) {
+        return true;
+    }
+
+    // an end on the other segment
+    let is_within = |p: glm::Vec2, s: &[glm::Vec2]| {
+        p.x >= s[0].x.min(s[1].x)
+            && p.x <= s[0].x.max(s[1].x)
+            && p.y >= s[0].y.min(s[1].y)
+            && p.y <= s[0].y.max(s[1].y)
+    };
+    (d1 == 0. && is_within(s2[0], s1))
+        || (d2 == 0. && is_within(s2[1], s1))
+        || (d3 == 0. && is_within(s1[0], s2))
+        || (d4 == 0. && is_within(s1[1], s2))
+}
+
+/// The edges of a polygon, the last back to the first vertex
+pub fn polygon_edges(polygon: &[glm::Vec2]) -> impl Iterator<Item = [glm::Vec2; 2]> + '_ {
+    polygon
+        .iter()
+        .zip(polygon.iter().cycle().skip(1))
+        .map(|(a, b)| [*a, *b])
+}
+
+/// Whether a point is inside a polygon, by the number of its edges crossed
+/// going east from it
+pub fn is_point_in_polygon(point: glm::Vec2, polygon: &[glm::Vec2]) -> bool {
+    let crossings = polygon_edges(polygon)
+        .filter(|[a, b]| {
+            (a.y > point.y) != (b.y > point.y)
+                && point.x < a.x + (point.y - a.y) / (b.y - a.y) * (b.x - a.x)
+        })
+        .count();
+
+    crossings % 2 == 1
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -130,21 +174,6 @@ mod test {
         assert!(!is_point_in_circle(glm::vec2(2., 2.), glm::zero(), 1.));
     }
 
-    #[test]
-    fn test_is_point_in_triangle() {
-        assert!(is_point_in_triangle(glm::zero(), &[
-            glm::vec2(-1., -1.),
-            glm::vec2(1., 1.),
-            glm::vec2(-1., 1.)
-        ]));
-
-        assert!(!is_point_in_triangle(glm::vec2(2., 2.), &[
-            glm::vec2(-1., -1.),
-            glm::vec2(1., 1.),
-            glm::vec2(-1., 1.)
-        ]));
-    }
-
     #[test]
     fn test_heading_to_point() {
         assert_eq!((0.0, 1.0), (heading_to_point(0).x, heading_to_point(0).y));
@@ -201,4 +230,46 @@ mod test {
             glm::vec2(1., 0.)
         ], &glm::vec2(0., 1.)));
     }
+
+    #[test]
+    fn test_distance_segment_and_point() {
+        let segment = [glm::zero(), glm::vec2(0., 2.)];
+        assert_eq!(1., distance_segment_and_point(&segment, &glm::vec2(1., 1.)));
+        // beyond either end
+        assert_eq!(5., distance_segment_and_point(&segment, &glm::vec2(3., 6.)));
+        assert_eq!(1., distance_segment_and_point(&segment, &glm::vec2(0., -1.)));
+        assert_eq!(1., distance_segment_and_point(&[glm::zero(), glm::zero()], &glm::vec2(1., 0.)));
+    }
+
+    #[test]
+    fn test_segments_intersect() {
+        let segment = [glm::vec2(-1., -1.), glm::vec2(1., 1.)];
+        assert!(segments_intersect(&segment, &[glm::vec2(-1., 1.), glm::vec2(1., -1.)]));
+        assert!(!segments_intersect(&segment, &[glm::vec2(3., 0.), glm::vec2(0., 3.)]));
+        // parallel
+        assert!(!segments_intersect(&segment, &[glm::vec2(0., -1.), glm::vec2(2., 1.)]));
+        // touching at an end
+        assert!(segments_intersect(&segment, &[glm::vec2(1., 1.), glm::vec2(2., 0.)]));
+        // in line, but apart
+        assert!(!segments_intersect(&segment, &[glm::vec2(2., 2.), glm::vec2(3., 3.)]));
+    }
+
+    #[test]
+    fn test_is_point_in_polygon() {
+        // L shaped
+        let polygon = [
+            glm::zero(),
+            glm::vec2(2., 0.),
+            glm::vec2(2., 1.),
+            glm::vec2(1., 1.),
+            glm::vec2(1., 2.),
+            glm::vec2(0., 2.),
+        ];
+        assert!(is_point_in_polygon(glm::vec2(0.5, 0.5), &polygon));
+        assert!(is_point_in_polygon(glm::vec2(1.5, 0.5), &polygon));
+        assert!(is_point_in_polygon(glm::vec2(0.5, 1.5), &polygon));
+        assert!(!is_point_in_polygon(glm::vec2(1.5, 1.5), &polygon));
+        assert!(!is_point_in_polygon(glm::vec2(-0.5, 0.5), &polygon));
+        assert!(!is_point_in_polygon(glm::vec2(0.5, 0.5), &[]));
+    }
 }
@@ -0,0 +1,237 @@
+//! Holding patterns, a racetrack of two half turns and two timed legs with
+//! the fix at the end of the inbound leg.
+
+use serde::{Deserialize, Serialize};
+
+use crate::aircraft::TurnDirection;
+use crate::math::{degrees_normalize, short_angle_distance};
+
+/// degrees off the inbound course, away from and towards the holding side,
+/// arriving at the fix within which the hold is joined by turning straight
+/// onto the outbound leg
+const DIRECT_ENTRY_SECTOR: std::ops::RangeInclusive<f32> = -70.0..=110.0;
+/// degrees off the outbound course towards the holding side, for a
+/// teardrop entry
+const TEARDROP_ANGLE: f32 = 30.;
+/// line segments per half turn when drawn
+const TURN_SEGMENTS: u32 = 12;
+
+/// How an aircraft joins the hold, decided by its heading at the fix.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum HoldEntry {
+    /// turn onto the outbound leg
+    Direct,
+    /// fly away from the fix into the holding side, then turn back onto the
+    /// inbound leg
+    Teardrop,
+    /// fly the outbound course outside the holding side, then turn back
+    /// across onto the inbound leg
+    Parallel,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum HoldState {
+    /// flying to the fix for the first time
+    Entering,
+    /// flying the entry leg away from the fix, seconds flown
+    Entry(HoldEntry, f32),
+    /// turning the given way back after a parallel entry and flying
+    /// direct to the fix
+    Returning(TurnDirection),
+    /// turning onto and flying the outbound leg, seconds flown since the
+    /// turn
+    Outbound(f32),
+    /// turning the given way back onto the inbound leg and flying it to
+    /// the fix
+    Inbound(TurnDirection),
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Hold {
+    pub fix: String,
+    /// course of the inbound leg, towards the fix
+    pub inbound_course: f32,
+    /// way of the turns at either end
+    pub direction: TurnDirection,
+    /// seconds
+    pub leg_time: f32,
+    pub state: HoldState,
+}
+
+impl Hold {
+    pub fn new(fix: String, inbound_course: f32, direction: TurnDirection, leg_time: f32) -> Self {
+        Self {
+            fix,
+            inbound_course: degrees_normalize(inbound_course),
+            direction,
+            leg_time,
+            state: HoldState::Entering,
+        }
+    }
+
+    pub fn outbound_course(&self) -> f32 {
+        degrees_normalize(self.inbound_course + 180.)
+    }
+
+    /// 1 for right hand holds, -1 for left
+    fn side(&self) -> f32 {
+        match self.direction {
+            TurnDirection::Right => 1.,
+            TurnDirection::Left => -1.,
+        }
+    }
+
+    /// Entry for an aircraft reaching the fix on `heading`, mirrored for
+    /// left hand holds.
+    pub fn entry(&self, heading: f32) -> HoldEntry {
+        let angle = short_angle_distance(self.inbound_course, heading) * self.side();
+        if DIRECT_ENTRY_SECTOR.contains(&angle) {
+            HoldEntry::Direct
+        } else if angle > *DIRECT_ENTRY_SECTOR.end() {
+            HoldEntry::Teardrop
+        } else {
+            HoldEntry::Parallel
+        }
+    }
+
+    /// Heading flown away from the fix on an entry leg.
+    pub fn entry_heading(&self, entry: HoldEntry) -> f32 {
+        match entry {
+            HoldEntry::Teardrop => {
+                degrees_normalize(self.outbound_course() - TEARDROP_ANGLE * self.side())
+            }
+            _ => self.outbound_course(),
+        }
+    }
+
+    /// Way of the turn back onto the inbound leg after an entry leg.
+    pub fn entry_turn(&self, entry: HoldEntry) -> TurnDirection {
+        match (entry, self.direction) {
+            (HoldEntry::Parallel, TurnDirection::Right) => TurnDirection::Left,
+            (HoldEntry::Parallel, TurnDirection::Left) => TurnDirection::Right,
+            _ => self.direction,
+        }
+    }
+
+    fn inbound_unit(&self) -> glm::Vec2 {
+        let course = self.inbound_course.to_radians();
+        glm::vec2(course.sin(), course.cos())
+    }
+
+    fn right_unit(&self) -> glm::Vec2 {
+        let course = self.inbound_course.to_radians();
+        glm::vec2(course.cos(), -course.sin())
+    }
+
+    /// Meters along the inbound course past the fix, negative before it.
+    pub fn along_track_distance(&self, fix: glm::Vec2, position: glm::Vec2) -> f32 {
+        glm::dot(&(position - fix), &self.inbound_unit())
+    }
+
+    /// Meters off the inbound course, positive to the right of it.
+    pub fn crosstrack_distance(&self, fix: glm::Vec2, position: glm::Vec2) -> f32 {
+        glm::dot(&(position - fix), &self.right_unit())
+    }
+
+    /// Outline of the racetrack around `fix`, for turns of `turn_radius`
+    /// and legs of `leg_length` meters.
+    pub fn racetrack(&self, fix: glm::Vec2, turn_radius: f32, leg_length: f32) -> Vec<glm::Vec2> {
+        let side = self.side();
+        let offset = self.right_unit() * side * turn_radius;
+        let leg_start = fix - self.inbound_unit() * leg_length;
+
+        // half turn about `center`, starting at a bearing from it
+        let turn = |center: glm::Vec2, start: f32| {
+            (0..=TURN_SEGMENTS).map(move |i| {
+                let bearing = (start + side * 180. * i as f32 / TURN_SEGMENTS as f32).to_radians();
+                center + glm::vec2(bearing.sin(), bearing.cos()) * turn_radius
+            })
+        };
+        let course = self.inbound_course;
+        turn(fix + offset, course - 90. * side)
+            .chain(turn(leg_start + offset, course + 90. * side))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn assert_near(expected: f32, actual: f32) {
+        assert!((expected - actual).abs() < 1., "{} != {}", expected, actual);
+    }
+
+    #[test]
+    fn test_hold_entry() {
+        // right hand, inbound north, holding side to the east
+        let hold = Hold::new("PHA".into(), 360., TurnDirection::Right, 60.);
+        assert_eq!(180., hold.outbound_course());
+        assert_eq!(HoldEntry::Direct, hold.entry(0.));
+        assert_eq!(HoldEntry::Direct, hold.entry(290.));
+        assert_eq!(HoldEntry::Direct, hold.entry(110.));
+        assert_eq!(HoldEntry::Teardrop, hold.entry(150.));
+        assert_eq!(HoldEntry::Parallel, hold.entry(240.));
+
+        // mirrored for left hand holds
+        let hold = Hold::new("PHA".into(), 360., TurnDirection::Left, 60.);
+        assert_eq!(HoldEntry::Direct, hold.entry(70.));
+        assert_eq!(HoldEntry::Teardrop, hold.entry(210.));
+        assert_eq!(HoldEntry::Parallel, hold.entry(120.));
+    }
+
+    #[test]
+    fn test_hold_entry_legs() {
+        let right = Hold::new("PHA".into(), 90., TurnDirection::Right, 60.);
+        assert_eq!(240., right.entry_heading(HoldEntry::Teardrop));
+        assert_eq!(270., right.entry_heading(HoldEntry::Parallel));
+        assert_eq!(TurnDirection::Right, right.entry_turn(HoldEntry::Teardrop));
+        assert_eq!(TurnDirection::Left, right.entry_turn(HoldEntry::Parallel));
+
+        let left = Hold::new("PHA".into(), 90., TurnDirection::Left, 60.);
+        assert_eq!(300., left.entry_heading(HoldEntry::Teardrop));
+        assert_eq!(TurnDirection::Right, left.entry_turn(HoldEntry::Parallel));
+    }
+
+    #[test]
+    fn test_hold_track_distances() {
+        let hold = Hold::new("PHA".into(), 90., TurnDirection::Right, 60.);
+        let fix = glm::vec2(1000., 1000.);
+        assert_near(
+            -500.,
+            hold.along_track_distance(fix, glm::vec2(500., 1000.)),
+        );
+        assert_near(200., hold.along_track_distance(fix, glm::vec2(1200., 0.)));
+        // right of an eastbound course is south
+        assert_near(300., hold.crosstrack_distance(fix, glm::vec2(0., 700.)));
+        assert_near(-300., hold.crosstrack_distance(fix, glm::vec2(0., 1300.)));
+    }
+
+    #[test]
+    fn test_hold_racetrack() {
+        let hold = Hold::new("PHA".into(), 360., TurnDirection::Right, 60.);
+        let racetrack = hold.racetrack(glm::zero(), 1000., 5000.);
+        assert_eq!(2 * (TURN_SEGMENTS as usize + 1), racetrack.len());
+
+        // turning east over the fix onto the outbound leg, two radii across
+        assert_near(0., glm::length(&racetrack[0]));
+        let abeam = racetrack[TURN_SEGMENTS as usize / 2];
+        assert_near(1000., abeam.x);
+        assert_near(1000., abeam.y);
+        let outbound_start = racetrack[TURN_SEGMENTS as usize];
+        assert_near(2000., outbound_start.x);
+        assert_near(0., outbound_start.y);
+
+        // back round to the start of the inbound leg
+        let inbound_start = racetrack.last().unwrap();
+        assert_near(0., inbound_start.x);
+        assert_near(-5000., inbound_start.y);
+        assert!(racetrack.iter().all(|p| p.x >= -0.01 && p.x <= 2000.01));
+
+        let left = Hold::new("PHA".into(), 360., TurnDirection::Left, 60.);
+        assert!(left
+            .racetrack(glm::zero(), 1000., 5000.)
+            .iter()
+            .all(|p| p.x <= 0.01));
+    }
+}
@@ -1,26 +1,46 @@
 extern crate nalgebra_glm as glm;
 
 mod aircraft;
+mod airspace;
 mod atc;
 mod camera;
 mod cli;
 mod command;
+mod error;
+mod fix;
 mod geo;
 mod geom;
+mod hold;
 mod math;
+mod msaw;
 mod msfs_integration;
+mod pending;
+mod performance;
+mod phraseology;
+mod pilot;
+mod recording;
+mod scenario;
+mod score;
+mod sector;
+mod simulation;
+mod stca;
+mod terrain;
+mod traffic;
 mod tts;
 mod units;
-
-use std::sync::Arc;
-use std::sync::RwLock;
+mod wind;
 
 use crate::aircraft::*;
+use crate::airspace::AirspaceClass;
 use crate::atc::*;
 use crate::cli::*;
 use crate::command::*;
 use crate::geo::*;
 use crate::geom::*;
+use crate::performance::PerformanceDatabase;
+use crate::recording::{Recorder, Replay, ReplayEvent, Session, SessionError};
+use crate::scenario::Scenario;
+use crate::simulation::Simulation;
 use camera::Camera;
 use ggez::input;
 use ggez::{
@@ -28,95 +48,117 @@ use ggez::{
     graphics::{self, Color},
     timer, Context, ContextBuilder, GameResult,
 };
-use lazy_static::lazy_static;
 use msfs_integration::start_msfs_monitor;
+use std::fs::File;
+use std::io::BufWriter;
 
 const TTS_ENABLED: bool = false;
 
 const AIRCRAFT_RADIUS: f32 = 4.0;
 const AIRCRAFT_BOUNDING_RADIUS: f32 = AIRCRAFT_RADIUS * 5.0;
+const FIX_SIZE: f32 = 5.0;
+
+const DEFAULT_SCENARIO: &str = "scenarios/lcph.toml";
+const PERFORMANCE_DATA: &str = "data/performance.toml";
+
+const USAGE: &str = "usage: atc [SCENARIO] [--record SESSION] [--readback-errors FRACTION] \
+                     [--replay SESSION [--headless]] [--score FILE]";
+
+/// seconds skipped by a single seek during replay
+const REPLAY_SEEK_STEP: f32 = 30.;
+
+#[derive(Debug, Default)]
+struct Args {
+    scenario: Option<String>,
+    /// session file to record to
+    record: Option<String>,
+    /// session file to play back
+    replay: Option<String>,
+    /// replay without a window, reporting to stdout
+    headless: bool,
+    /// fraction of pilot readbacks with an error in them
+    readback_errors: f32,
+    /// file to write the score to at the end of the session
+    score: Option<String>,
+}
 
-lazy_static! {
-    // 34° 43' 5.08" N 32° 29' 6.26" E
-    static ref PAPHOS_LATLON: LatLon = LatLon::from_dms(
-        DMS::new(34, 43, 5.08, Cardinal::North),
-        DMS::new(32, 29, 6.26, Cardinal::East)
-    );
+impl Args {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut parsed = Self::default();
+        while let Some(arg) = args.next() {
+            let mut value = |flag: &str, argument: &str| {
+                args.next()
+                    .ok_or_else(|| format!("{} needs {}", flag, argument))
+            };
+            match arg.as_str() {
+                "--record" => parsed.record = Some(value(&arg, "a session file")?),
+                "--replay" => parsed.replay = Some(value(&arg, "a session file")?),
+                "--score" => parsed.score = Some(value(&arg, "a file")?),
+                "--readback-errors" => {
+                    parsed.readback_errors = value(&arg, "a fraction")?
+                        .parse()
+                        .ok()
+                        .filter(|fraction| (0. ..=1.).contains(fraction))
+                        .ok_or_else(|| format!("{} needs a fraction between 0 and 1", arg))?
+                }
+                "--headless" => parsed.headless = true,
+                flag if flag.starts_with("--") => return Err(format!("unknown option {}", flag)),
+                _ => parsed.scenario = Some(arg),
+            }
+        }
+
+        if parsed.replay.is_some() && (parsed.record.is_some() || parsed.scenario.is_some()) {
+            Err(String::from("a replay can't be recorded or given a scenario"))
+        } else if parsed.headless && parsed.replay.is_none() {
+            Err(String::from("--headless only applies to replays"))
+        } else {
+            Ok(parsed)
+        }
+    }
+}
+
+fn format_time(seconds: f32) -> String {
+    format!("{:02}:{:02}", seconds as u32 / 60, seconds as u32 % 60)
+}
+
+/// Write the session's score so far to `path`, as JSON.
+fn export_score(path: &str, sim: &Simulation) {
+    let report = serde_json::to_string_pretty(&sim.score.report(sim.time()))
+        .expect("failed to serialize the score");
+    if let Err(e) = std::fs::write(path, report) {
+        eprintln!("{}: {}", path, e);
+    }
 }
 
 #[derive(Debug)]
 struct Game {
     atc: Atc,
     cli: CliPrompt,
-    airport: Airport,
+    sim: Simulation,
     selected_aircraft: Option<usize>,
-    aircraft: Arc<RwLock<Vec<Aircraft>>>,
     camera: Camera,
+    recorder: Option<Recorder<BufWriter<File>>>,
+    replay: Option<Replay>,
+    /// file to write the score to on quitting
+    score_path: Option<String>,
 }
 
 impl Game {
-    pub fn new(ctx: &mut Context) -> Self {
-        let runway_29 = Runway {
-            offset: glm::zero(),
-            heading: 285,
-            length: 2700,
-            width: 45,
-            ils_max_altitude: 2000,
-        };
-        let aircraft = Arc::new(RwLock::new(vec![
-            Aircraft {
-                position: glm::vec2(0.0, 0.0),
-                callsign: Callsign {
-                    name: "Cyprus Airways".into(),
-                    code: "CYP".into(),
-                    number: "2202".into(),
-                },
-                heading: HeadingParameter::new(90.0),
-                altitude: AircraftParameter::new(6000.0),
-                speed: AircraftParameter::new(240.0),
-                status: AircraftStatus::Flight,
-                cleared_to_land: false,
-            },
-            Aircraft {
-                position: glm::vec2(14000.0, -2000.0),
-                callsign: Callsign {
-                    name: "Fedex".into(),
-                    code: "FDX".into(),
-                    number: "261".into(),
-                },
-
-                heading: HeadingParameter::new(245.0),
-                altitude: AircraftParameter::new(1000.0),
-                speed: AircraftParameter::new(180.0),
-                status: AircraftStatus::Flight,
-                cleared_to_land: false,
-            },
-            Aircraft {
-                position: glm::vec2(-2000.0, -5000.0),
-                callsign: Callsign {
-                    name: "Transavia".into(),
-                    code: "TRA".into(),
-                    number: "1112".into(),
-                },
-                heading: HeadingParameter::new(180.0),
-                altitude: AircraftParameter::new(4000.0),
-                speed: AircraftParameter::new(220.0),
-                status: AircraftStatus::Flight,
-                cleared_to_land: false,
-            },
-        ]));
-
-        let _msfs_thread = start_msfs_monitor(*PAPHOS_LATLON, aircraft.clone());
+    pub fn new(
+        ctx: &mut Context,
+        sim: Simulation,
+        origin: LatLon,
+        recorder: Option<Recorder<BufWriter<File>>>,
+        replay: Option<Replay>,
+        readback_errors: f32,
+        score_path: Option<String>,
+    ) -> Self {
+        let _msfs_thread = start_msfs_monitor(origin, sim.aircraft.clone());
 
         Self {
-            atc: Atc::new(TTS_ENABLED),
+            atc: Atc::new(TTS_ENABLED, readback_errors),
             cli: CliPrompt::new(String::from("ATC>")),
-            airport: Airport {
-                position: glm::vec2(0.0, 0.0),
-                icao_code: "LCPH".into(),
-                takeoff_runways: vec![runway_29.clone()],
-                landing_runways: vec![runway_29.clone()],
-            },
+            sim,
             selected_aircraft: None,
             camera: Camera::new(
                 graphics::screen_coordinates(ctx).w,
@@ -125,7 +167,82 @@ impl Game {
                 80_000.,
                 80_000.,
             ),
-            aircraft,
+            recorder,
+            replay,
+            score_path,
+        }
+    }
+
+    fn stop_recording(&mut self, e: SessionError) {
+        self.cli.alert(format!("Recording stopped: {}", e));
+        self.recorder = None;
+    }
+
+    /// Give the command to the aircraft at `idx`, recording both what was said
+    /// and what the pilot read back, which is what a replay has to fly.
+    fn command_aircraft(&mut self, idx: usize, atc_cmd: AtcCommand) {
+        // the full callsign, with its name for the readback
+        let callsign = self.sim.aircraft.read().unwrap()[idx].callsign.clone();
+        let issued = atc_cmd.clone();
+        match self.atc.command(&mut self.cli, &mut self.sim, &callsign, atc_cmd) {
+            Ok(reply) => {
+                let recorded = self
+                    .recorder
+                    .as_mut()
+                    .map(|recorder| recorder.command(&self.sim, &callsign, &issued, &reply));
+                if let Some(Err(e)) = recorded {
+                    self.stop_recording(e);
+                }
+            }
+            Err(e) => self.cli.output(format!("Error: {}", e)),
+        }
+    }
+
+    fn handle_command(&mut self, cmd: CliCommand) {
+        match cmd {
+            CliCommand::Atc(_) | CliCommand::Addressed(..) if self.replay.is_some() => {
+                self.cli.output("Error: Commands can't be given during a replay");
+            }
+            CliCommand::Atc(atc_cmd) => match self.selected_aircraft {
+                Some(sel) => self.command_aircraft(sel, atc_cmd),
+                None => self.cli.output("Error: No aircraft selected"),
+            },
+            CliCommand::Addressed(callsign, atc_cmd) => {
+                let idx = {
+                    let aircraft = self.sim.aircraft.read().unwrap();
+                    aircraft_by_callsign(callsign.clone(), &aircraft).map(|(idx, _)| idx)
+                };
+                match idx {
+                    Some(idx) => self.command_aircraft(idx, atc_cmd),
+                    None => self.cli.output(format!(
+                        "Error: Aircraft with callsign {} doesn't exist",
+                        callsign
+                    )),
+                }
+            }
+            CliCommand::Comm(CommCommand::ListAircraft) => {
+                let aircraft = self.sim.aircraft.read().unwrap();
+                for (idx, aircraft) in aircraft.iter().enumerate() {
+                    self.cli.output(format!("{}: {}", idx, aircraft.callsign.coded()));
+                }
+            }
+            CliCommand::Comm(CommCommand::ChangeAircraft(callsign)) => {
+                self.cli.output(format!("Changing aircraft to {}", callsign));
+
+                let aircraft = self.sim.aircraft.read().unwrap();
+                match aircraft_by_callsign(callsign.clone(), &aircraft) {
+                    Some((idx, aircraft)) => {
+                        self.cli.output(format!("Now speaking to {}", aircraft.callsign));
+                        self.selected_aircraft = Some(idx);
+                    }
+                    None => {
+                        self.cli.output(format!(
+                            "Error: Aircraft with callsign {} doesn't exist",
+                            callsign
+                        ));
+                    }
+                }
+            }
         }
     }
 }
@@ -148,90 +265,64 @@ impl EventHandler<ggez::GameError> for Game {
         let dt = timer::delta(ctx).as_secs_f32();
 
         if let Some(msg) = self.cli.try_input() {
-            for cmd in CliCommand::from_string(msg) {
-                match cmd {
-                    CliCommand::Atc(atc_cmd) => {
-                        let mut aircraft = self.aircraft.write().unwrap();
-                        self.selected_aircraft.map(|sel| {
-                            self.atc.command(&mut self.cli, &mut aircraft[sel], atc_cmd);
-                        });
-                    }
-                    CliCommand::Comm(CommCommand::ListAircraft) => {
-                        let aircraft = self.aircraft.read().unwrap();
-                        for (idx, aircraft) in aircraft.iter().enumerate() {
-                            self.cli
-                                .output(format!("{}: {}", idx, aircraft.callsign.coded()));
-                        }
-                    }
-                    CliCommand::Comm(CommCommand::ChangeAircraft(callsign)) => {
-                        self.cli
-                            .output(format!("Changing aircraft to {}", callsign));
-
-                        let aircraft = self.aircraft.read().unwrap();
-                        match aircraft_by_callsign(callsign.clone(), &aircraft) {
-                            Some((idx, aircraft)) => {
-                                self.cli
-                                    .output(format!("Now speaking to {}", aircraft.callsign));
-                                self.selected_aircraft = Some(idx);
-                            }
-                            None => {
-                                self.cli.output(format!(
-                                    "Error: Aircraft with callsign {} doesn't exist",
-                                    callsign
-                                ));
-                            }
-                        }
+            match CliCommand::from_string(msg.clone()) {
+                Ok(cmds) => {
+                    for cmd in cmds {
+                        self.handle_command(cmd);
                     }
                 }
+                Err(e) => self.cli.output_error(&msg, e.position, e),
             }
         }
         self.cli.flush();
 
-        let mut aircraft = self.aircraft.write().unwrap();
-        for mut aircraft in &mut aircraft.iter_mut() {
-            if !aircraft.is_grounded() {
-                let speed_change = aircraft.speed.current(dt) * units::KT_TO_MS as f32 * dt;
-
-                let heading = aircraft.heading.current(dt);
-                let heading = heading_to_point(heading as i32);
-                aircraft.position.x += speed_change * heading.x;
-                aircraft.position.y += speed_change * heading.y;
-
-                let _alt = aircraft.altitude.current(dt);
-            }
-
-            if aircraft.cleared_to_land() {
-                // super inefficient
-                for runway in &self.airport.landing_runways {
-                    let origin = self.airport.origin(runway);
-                    let ils = runway.ils(origin);
-
-                    if runway.has_landed(origin, aircraft) {
-                        aircraft.status = AircraftStatus::Landed;
-                    } else if aircraft.is_localizer_captured(&ils) {
-                        aircraft.status = AircraftStatus::Landing;
-                    }
-
-                    // TEMPORARY, fix loc capture logic
-                    if aircraft.status == AircraftStatus::Landing {
-                        aircraft.change_heading(ils.intercept_heading(aircraft), None);
+        let old_selection = {
+            let aircraft = self.sim.aircraft.read().unwrap();
+            self.selected_aircraft
+                .and_then(|idx| aircraft.get(idx))
+                .map(|a| a.callsign.clone())
+        };
 
-                        let expected_alt = ils.altitude(&aircraft.position);
-                        aircraft.change_altitude(expected_alt);
+        match &mut self.replay {
+            Some(replay) => {
+                for event in replay.update(&mut self.sim, dt) {
+                    match event {
+                        ReplayEvent::Command(recorded) => self.cli.alert(format!(
+                            "{} ==> {}",
+                            format_time(recorded.time),
+                            recorded
+                        )),
+                        ReplayEvent::Rejected(recorded, e) => self.cli.alert(format!(
+                            "{} {} rejected: {}",
+                            format_time(recorded.time),
+                            recorded,
+                            e
+                        )),
+                        ReplayEvent::Diverged(snapshot) => self.cli.alert(format!(
+                            "Replay diverged from the recording at {}",
+                            format_time(snapshot.time)
+                        )),
                     }
                 }
             }
+            None => {
+                self.sim.update(dt);
+                let recorded = self.recorder.as_mut().map(|r| r.update(&self.sim));
+                if let Some(Err(e)) = recorded {
+                    self.stop_recording(e);
+                }
+            }
         }
 
-        let old_selection = self.selected_aircraft.and_then(|idx| aircraft.get(idx));
-        let mut aircraft = aircraft.clone(); // need to clone for lifetimes
-
-        // remove landed aircraft
-        aircraft.retain(|a| !a.is_grounded());
+        for event in self.sim.drain_events() {
+            self.cli.alert(format!("{} {}", format_time(self.sim.time()), event));
+        }
 
         // set to previously selected item, if exists
+        let aircraft = self.sim.aircraft.read().unwrap();
         self.selected_aircraft = old_selection
-            .and_then(|old_selection| aircraft.iter().position(|a| a == old_selection));
+            .and_then(|callsign| aircraft_by_callsign(callsign, &aircraft))
+            .map(|(idx, _)| idx);
 
         Ok(())
     }
@@ -251,11 +342,35 @@ impl EventHandler<ggez::GameError> for Game {
             KeyCode::RBracket => {
                 self.selected_aircraft = Some(
                     (self.selected_aircraft.unwrap_or(0) + 1)
-                        .min(self.aircraft.read().unwrap().len() - 1),
+                        .min(self.sim.aircraft.read().unwrap().len() - 1),
                 );
             }
             _ => {}
         }
+
+        // replay controls
+        if let Some(replay) = &mut self.replay {
+            let seek = match keycode {
+                KeyCode::Space => {
+                    replay.is_paused = !replay.is_paused;
+                    None
+                }
+                KeyCode::Up => {
+                    replay.change_speed(2.);
+                    None
+                }
+                KeyCode::Down => {
+                    replay.change_speed(0.5);
+                    None
+                }
+                KeyCode::Left => Some(self.sim.time() - REPLAY_SEEK_STEP),
+                KeyCode::Right => Some(self.sim.time() + REPLAY_SEEK_STEP),
+                _ => None,
+            };
+            if let Some(Err(e)) = seek.map(|time| replay.seek(&mut self.sim, time)) {
+                self.cli.alert(format!("Error: {}", e));
+            }
+        }
     }
 
     fn mouse_button_down_event(&mut self, _ctx: &mut Context, button: MouseButton, x: f32, y: f32) {
@@ -263,7 +378,7 @@ impl EventHandler<ggez::GameError> for Game {
         if button == MouseButton::Left {
             let click_pos = Point { x, y };
 
-            for (i, aircraft) in self.aircraft.read().unwrap().iter().enumerate() {
+            for (i, aircraft) in self.sim.aircraft.read().unwrap().iter().enumerate() {
                 if is_point_in_circle(MintPoint::from(click_pos).into(), aircraft.position, AIRCRAFT_BOUNDING_RADIUS) {
                     self.selected_aircraft = Some(i);
                     break;
@@ -277,10 +392,17 @@ impl EventHandler<ggez::GameError> for Game {
         self.camera.zoom(zoom_amount);
     }
 
+    fn quit_event(&mut self, _ctx: &mut Context) -> bool {
+        if let Some(path) = &self.score_path {
+            export_score(path, &self.sim);
+        }
+        false
+    }
+
     fn draw(&mut self, ctx: &mut Context) -> GameResult<()> {
         graphics::clear(ctx, Color::BLACK);
 
-        let aircraft = self.aircraft.read().unwrap();
+        let aircraft = self.sim.aircraft.read().unwrap();
 
         // scale line uses screen coords
         let scale_length = 1000. * self.camera.pixels_per_unit().x;
@@ -327,21 +449,106 @@ impl EventHandler<ggez::GameError> for Game {
         )?;
 
         // airport
-        let icao_text = graphics::Text::new(self.airport.icao_code.clone());
+        let icao_text = graphics::Text::new(self.sim.airport.icao_code.clone());
         graphics::queue_text(ctx, &icao_text, Point { x: 0.0, y: 0.0 }, Some(Color::BLUE));
         graphics::draw_queued_text(
             ctx,
             graphics::DrawParam::new()
-                .dest(self.camera.world_to_screen_coords(self.airport.position)),
+                .dest(self.camera.world_to_screen_coords(self.sim.airport.position)),
             None,
             graphics::FilterMode::Linear,
         )?;
 
-        for runway in &self.airport.landing_runways {
-            let origin = self.airport.origin(runway);
+        // sector boundary, when there is one, and the airspaces around it
+        for airspace in self.sim.sector.airspace.iter().chain(&self.sim.airspaces) {
+            let color = match airspace.class {
+                AirspaceClass::Sector => Color::BLUE,
+                AirspaceClass::Ctr => Color::from_rgb(0, 96, 160),
+                AirspaceClass::Restricted => Color::from_rgb(160, 0, 0),
+                AirspaceClass::Danger => Color::from_rgb(160, 112, 0),
+            };
+            let points: Vec<Point> = airspace
+                .boundary
+                .iter()
+                .map(|vertex| self.camera.world_to_screen_coords(*vertex))
+                .collect();
+            let boundary = graphics::Mesh::new_polygon(
+                ctx,
+                graphics::DrawMode::stroke(1.0),
+                &points,
+                color,
+            )?;
+            graphics::draw(ctx, &boundary, (Point { x: 0.0, y: 0.0 },))?;
+
+            if airspace.class != AirspaceClass::Sector {
+                let name_text = graphics::Text::new(airspace.name.clone());
+                graphics::queue_text(ctx, &name_text, Point { x: 0.0, y: 0.0 }, Some(color));
+                graphics::draw_queued_text(
+                    ctx,
+                    graphics::DrawParam::new().dest(points[0]),
+                    None,
+                    graphics::FilterMode::Linear,
+                )?;
+            }
+        }
+
+        for fix in self.sim.fixes.iter() {
+            let pos = self.camera.world_to_screen_coords(fix.position);
+            let triangle = [
+                Point {
+                    x: pos.x,
+                    y: pos.y - FIX_SIZE,
+                },
+                Point {
+                    x: pos.x + FIX_SIZE,
+                    y: pos.y + FIX_SIZE,
+                },
+                Point {
+                    x: pos.x - FIX_SIZE,
+                    y: pos.y + FIX_SIZE,
+                },
+            ];
+            let mesh = graphics::Mesh::new_polygon(
+                ctx,
+                graphics::DrawMode::stroke(1.0),
+                &triangle,
+                Color::CYAN,
+            )?;
+            graphics::draw(ctx, &mesh, (Point { x: 0.0, y: 0.0 },))?;
+
+            let name_text = graphics::Text::new(fix.name.clone());
+            graphics::queue_text(
+                ctx,
+                &name_text,
+                Point {
+                    x: FIX_SIZE * 2.0,
+                    y: -FIX_SIZE,
+                },
+                Some(Color::CYAN),
+            );
+            graphics::draw_queued_text(
+                ctx,
+                graphics::DrawParam::new().dest(pos),
+                None,
+                graphics::FilterMode::Linear,
+            )?;
+        }
+
+        for runway in &self.sim.airport.landing_runways {
+            let origin = self.sim.airport.origin(runway);
             let mesh = runway.as_mesh(ctx, origin, Color::RED, &self.camera)?;
             graphics::draw(ctx, &mesh, (Point { x: 0.0, y: 0.0 },))?;
 
+            let name_text = graphics::Text::new(runway.name.clone());
+            graphics::queue_text(ctx, &name_text, Point { x: 0.0, y: 0.0 }, Some(Color::RED));
+            graphics::draw_queued_text(
+                ctx,
+                graphics::DrawParam::new()
+                    .dest(self.camera.world_to_screen_coords(runway.as_line(origin)[0])),
+                None,
+                graphics::FilterMode::Linear,
+            )?;
+
             let ils = runway
                 .ils(origin)
                 .as_triangle()
@@ -363,8 +570,65 @@ impl EventHandler<ggez::GameError> for Game {
             graphics::draw(ctx, &mesh, (Point { x: 0.0, y: 0.0 },))?;
         }
 
+        // holds in use, as flown at each aircraft's speed
+        for aircraft in aircraft.iter() {
+            let racetrack = aircraft.hold.as_ref().and_then(|hold| {
+                self.sim.fixes.get(&hold.fix).map(|fix| {
+                    hold.racetrack(
+                        fix.position,
+                        aircraft.turn_radius(),
+                        hold.leg_time * aircraft.true_airspeed(),
+                    )
+                })
+            });
+            if let Some(racetrack) = racetrack {
+                let points = racetrack
+                    .into_iter()
+                    .map(|p| self.camera.world_to_screen_coords(p))
+                    .collect::<Vec<Point>>();
+                let mesh = graphics::Mesh::new_polygon(
+                    ctx,
+                    graphics::DrawMode::stroke(1.0),
+                    &points,
+                    Color::CYAN,
+                )?;
+                graphics::draw(ctx, &mesh, (Point { x: 0.0, y: 0.0 },))?;
+            }
+        }
+
+        for conflict in &self.sim.conflicts {
+            let positions = [&conflict.callsigns.0, &conflict.callsigns.1]
+                .iter()
+                .filter_map(|&callsign| aircraft.iter().find(|a| a.callsign == *callsign))
+                .map(|a| self.camera.world_to_screen_coords(a.position))
+                .collect::<Vec<Point>>();
+            if positions.len() == 2 {
+                let color = if conflict.is_loss { Color::RED } else { Color::YELLOW };
+                let mesh = graphics::Mesh::new_line(ctx, &positions, 1., color)?;
+                graphics::draw(ctx, &mesh, (Point { x: 0.0, y: 0.0 },))?;
+            }
+        }
+
         for aircraft in aircraft.iter() {
             let pos = self.camera.world_to_screen_coords(aircraft.position);
+            let conflicts = self
+                .sim
+                .conflicts
+                .iter()
+                .filter(|c| c.involves(&aircraft.callsign))
+                .map(|c| c.is_loss);
+            let terrain_warning = self
+                .sim
+                .terrain_warnings
+                .iter()
+                .find(|w| w.callsign == aircraft.callsign);
+            let color = match conflicts.chain(terrain_warning.map(|w| w.is_low())).max() {
+                Some(true) => Color::RED,
+                Some(false) => Color::YELLOW,
+                // another unit's traffic
+                None if !aircraft.is_under_control() => Color::WHITE,
+                None => Color::GREEN,
+            };
             let aircraft_rect = graphics::Mesh::new_rectangle(
                 ctx,
                 graphics::DrawMode::fill(),
@@ -374,7 +638,7 @@ impl EventHandler<ggez::GameError> for Game {
                     AIRCRAFT_RADIUS * 2.0,
                     AIRCRAFT_RADIUS * 2.0,
                 ),
-                Color::GREEN,
+                color,
             )?;
 
             graphics::draw(ctx, &aircraft_rect, (Point { x: 0.0, y: 0.0 },))?;
@@ -385,7 +649,7 @@ impl EventHandler<ggez::GameError> for Game {
                 pos,
                 AIRCRAFT_BOUNDING_RADIUS,
                 1.0,
-                Color::GREEN,
+                color,
             )?;
 
             graphics::draw(ctx, &bounding_circle, (Point { x: 0.0, y: 0.0 },))?;
@@ -395,7 +659,20 @@ impl EventHandler<ggez::GameError> for Game {
                 ctx,
                 &callsign_text,
                 Point { x: -20.0, y: 30.0 },
-                Some(Color::GREEN),
+                Some(color),
+            );
+            // speed and track over the ground, which the wind makes differ
+            // from the airspeed and heading
+            let ground_text = graphics::Text::new(format!(
+                "G{} T{}",
+                aircraft.ground_speed().round(),
+                aircraft.track().round()
+            ));
+            graphics::queue_text(
+                ctx,
+                &ground_text,
+                Point { x: 50.0, y: 30.0 },
+                Some(color),
             );
             let heading_text =
                 graphics::Text::new(format!("H{}", aircraft.heading.current.round()));
@@ -403,7 +680,7 @@ impl EventHandler<ggez::GameError> for Game {
                 ctx,
                 &heading_text,
                 Point { x: -20.0, y: 45.0 },
-                Some(Color::GREEN),
+                Some(color),
             );
             let altitude_text = {
                 // alt to FL
@@ -414,17 +691,78 @@ impl EventHandler<ggez::GameError> for Game {
                 ctx,
                 &altitude_text,
                 Point { x: 20.0, y: 45.0 },
-                Some(Color::GREEN),
+                Some(color),
             );
+            // climbing or descending, in hundreds of feet per minute
+            let vertical_speed = (aircraft.vertical_speed / 100.0).round();
+            if vertical_speed != 0.0 {
+                let vertical_speed_text = graphics::Text::new(format!("{:+}", vertical_speed));
+                graphics::queue_text(
+                    ctx,
+                    &vertical_speed_text,
+                    Point { x: 60.0, y: 45.0 },
+                    Some(color),
+                );
+            }
+
+            // runway cleared for, to land on once cleared to
+            let approach = aircraft
+                .runway
+                .as_ref()
+                .filter(|_| aircraft.approach.is_some() && aircraft.is_under_control());
+            if let Some(runway) = approach {
+                let clearance = if aircraft.cleared_to_land() {
+                    "LND"
+                } else {
+                    "ILS"
+                };
+                let text = graphics::Text::new(format!("{} {}", clearance, runway));
+                graphics::queue_text(ctx, &text, Point { x: -20.0, y: 55.0 }, Some(color));
+            }
+
+            // approach progress, otherwise the fix held at or flown direct to
+            let navigation = match aircraft.approach {
+                Some(ApproachState::LocalizerCaptured | ApproachState::GlideslopeArmed) => {
+                    Some(String::from("LOC"))
+                }
+                Some(ApproachState::GlideslopeCaptured) => Some(String::from("GS")),
+                _ => aircraft
+                    .hold
+                    .as_ref()
+                    .map(|hold| format!("HOLD {}", hold.fix))
+                    .or_else(|| aircraft.direct.clone()),
+            };
+            if let Some(navigation) = navigation {
+                let text = graphics::Text::new(navigation);
+                graphics::queue_text(ctx, &text, Point { x: 20.0, y: 55.0 }, Some(color));
+            }
 
-            if aircraft.cleared_to_land() {
-                let text = graphics::Text::new("LND");
-                graphics::queue_text(ctx, &text, Point { x: -20.0, y: 55.0 }, Some(Color::GREEN));
+            // departure, missed approach and handoff states
+            let status = match aircraft.status {
+                AircraftStatus::Taxi => Some("HLD"),
+                AircraftStatus::LinedUp => Some("LUW"),
+                AircraftStatus::Takeoff => Some("DEP"),
+                AircraftStatus::GoAround => Some("GA"),
+                AircraftStatus::Inbound => Some("INB"),
+                AircraftStatus::HandedOff => Some("HO"),
+                _ => None,
+            };
+            if let Some(status) = status {
+                let text = graphics::Text::new(status);
+                graphics::queue_text(ctx, &text, Point { x: -20.0, y: 55.0 }, Some(color));
+            }
+
+            // low over, or heading into, the terrain
+            if terrain_warning.is_some() {
+                let text = graphics::Text::new("MSAW");
+                graphics::queue_text(ctx, &text, Point { x: -20.0, y: 15.0 }, Some(color));
             }
 
-            if aircraft.status == AircraftStatus::Landing {
-                let text = graphics::Text::new("LOC");
-                graphics::queue_text(ctx, &text, Point { x: 20.0, y: 55.0 }, Some(Color::GREEN));
+            // instructions still waiting for their conditions, a line each
+            for (i, pending) in aircraft.pending.iter().enumerate() {
+                let text = graphics::Text::new(pending.tag());
+                let y = 65.0 + i as f32 * 10.0;
+                graphics::queue_text(ctx, &text, Point { x: -20.0, y }, Some(color));
             }
 
             graphics::draw_queued_text(
@@ -435,12 +773,34 @@ impl EventHandler<ggez::GameError> for Game {
             )?;
         }
 
+        let surface_wind = self
+            .sim
+            .wind
+            .layer(0.)
+            .map(|w| format!("{:03}/{}", w.direction, w.speed))
+            .unwrap_or_else(|| String::from("CALM"));
+        let replay_status = self
+            .replay
+            .as_ref()
+            .map(|replay| {
+                format!(
+                    "\nREPLAY: {}/{} x{}{}",
+                    format_time(self.sim.time()),
+                    format_time(replay.duration()),
+                    replay.speed(),
+                    if replay.is_paused { " PAUSED" } else { "" }
+                )
+            })
+            .unwrap_or_default();
         let selected_aircraft_text = graphics::Text::new(format!(
-            "SELECTED: {}",
+            "SELECTED: {}\nTIME: {}\nWIND: {}{}",
             self.selected_aircraft
                 .and_then(|idx| aircraft.get(idx))
                 .map(|a| a.callsign.coded())
-                .unwrap_or(String::from("None"))
+                .unwrap_or(String::from("None")),
+            format_time(self.sim.time()),
+            surface_wind,
+            replay_status,
         ));
         graphics::queue_text(
             ctx,
@@ -448,6 +808,28 @@ impl EventHandler<ggez::GameError> for Game {
             Point { x: 0.0, y: 0.0 },
             Some(Color::WHITE),
         );
+
+        let score_text = graphics::Text::new(
+            self.sim
+                .score
+                .breakdown()
+                .iter()
+                .fold(
+                    format!("SCORE: {}", self.sim.score.total()),
+                    |text, (item, count, points)| {
+                        format!("{}\n{} {}: {}", text, count, item.name().to_uppercase(), points)
+                    },
+                ),
+        );
+        graphics::queue_text(
+            ctx,
+            &score_text,
+            Point {
+                x: graphics::screen_coordinates(ctx).w - 320.0,
+                y: 0.0,
+            },
+            Some(Color::WHITE),
+        );
         graphics::draw_queued_text(
             ctx,
             graphics::DrawParam::new(),
@@ -459,13 +841,116 @@ impl EventHandler<ggez::GameError> for Game {
     }
 }
 
+/// Play a session back without a window, returning whether it matched the
+/// recording throughout.
+fn replay_headless(mut replay: Replay, mut sim: Simulation, score_path: Option<&str>) -> bool {
+    let mut has_diverged = false;
+    while !replay.is_finished(&sim) {
+        for event in replay.step(&mut sim) {
+            match event {
+                ReplayEvent::Command(recorded) => println!(
+                    "{} ==> {}",
+                    format_time(recorded.time),
+                    recorded
+                ),
+                ReplayEvent::Rejected(recorded, e) => println!(
+                    "{} {} rejected: {}",
+                    format_time(recorded.time),
+                    recorded,
+                    e
+                ),
+                ReplayEvent::Diverged(snapshot) => {
+                    println!(
+                        "{} diverged from the recording",
+                        format_time(snapshot.time)
+                    );
+                    has_diverged = true;
+                }
+            }
+        }
+        for event in sim.drain_events() {
+            println!("{} {}", format_time(sim.time()), event);
+        }
+    }
+    println!(
+        "{} end of session, score {}",
+        format_time(sim.time()),
+        sim.score.total()
+    );
+    if let Some(path) = score_path {
+        export_score(path, &sim);
+    }
+    !has_diverged
+}
+
 fn main() {
+    let args = Args::parse(std::env::args().skip(1)).unwrap_or_else(|e| {
+        eprintln!("{}\n{}", e, USAGE);
+        std::process::exit(2);
+    });
+    let (origin, sim, recorder, replay) = match &args.replay {
+        // flown with the performance data recorded in the session
+        Some(session_path) => {
+            let (replay, sim) = Session::load(session_path)
+                .and_then(Replay::new)
+                .unwrap_or_else(|e| {
+                    eprintln!("{}: {}", session_path, e);
+                    std::process::exit(1);
+                });
+            if args.headless {
+                let is_matching = replay_headless(replay, sim, args.score.as_deref());
+                std::process::exit(if is_matching { 0 } else { 1 });
+            }
+            (replay.scenario().origin(), sim, None, Some(replay))
+        }
+        None => {
+            let performance = PerformanceDatabase::load(PERFORMANCE_DATA).unwrap_or_else(|e| {
+                eprintln!("{}: {}", PERFORMANCE_DATA, e);
+                std::process::exit(1);
+            });
+            let scenario_path = args.scenario.as_deref().unwrap_or(DEFAULT_SCENARIO);
+            let (scenario, sim) = Scenario::load(scenario_path)
+                .and_then(|scenario| {
+                    scenario
+                        .simulation(&performance)
+                        .map(|sim| (scenario, sim))
+                })
+                .unwrap_or_else(|e| {
+                    eprintln!("{}: {}", scenario_path, e);
+                    std::process::exit(1);
+                });
+            let recorder = args.record.as_ref().map(|session_path| {
+                let sources = std::fs::read_to_string(scenario_path).and_then(|scenario| {
+                    Ok((scenario, std::fs::read_to_string(PERFORMANCE_DATA)?))
+                });
+                sources
+                    .map_err(SessionError::from)
+                    .and_then(|(scenario, performance)| {
+                        Recorder::create(session_path, &scenario, &performance, &sim)
+                    })
+                    .unwrap_or_else(|e| {
+                        eprintln!("{}: {}", session_path, e);
+                        std::process::exit(1);
+                    })
+            });
+            (scenario.origin(), sim, recorder, None)
+        }
+    };
+
     let (mut ctx, event_loop) = ContextBuilder::new("atc", "Antonis Kalou")
         .window_setup(ggez::conf::WindowSetup::default().title("ATC Simulator 2022"))
         .window_mode(ggez::conf::WindowMode::default().dimensions(1600.0, 1200.0))
         .build()
         .expect("Could not create ggez context");
 
-    let game = Game::new(&mut ctx);
+    let game = Game::new(
+        &mut ctx,
+        sim,
+        origin,
+        recorder,
+        replay,
+        args.readback_errors,
+        args.score,
+    );
     event::run(ctx, event_loop, game);
 }
@@ -0,0 +1,148 @@
+//! Minimum safe altitude warning, predicts aircraft flying into terrain from
+//! their current track, speed and vertical speed.
+
+use crate::aircraft::{Aircraft, AircraftStatus, ApproachState, Callsign};
+use crate::terrain::Terrain;
+
+/// feet an aircraft should stay above the terrain
+pub const TERRAIN_CLEARANCE: f32 = 500.;
+/// How far ahead terrain conflicts are predicted, in seconds.
+pub const LOOKAHEAD: f32 = 60.;
+/// seconds between the points of the projected path checked
+const STEP: f32 = 5.;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TerrainWarning {
+    pub callsign: Callsign,
+    /// seconds until the aircraft is within the terrain clearance
+    pub time: f32,
+    /// feet, of the terrain there
+    pub elevation: f32,
+}
+
+impl TerrainWarning {
+    /// Whether the aircraft is already within the terrain clearance, instead
+    /// of predicted to be.
+    pub fn is_low(&self) -> bool {
+        self.time == 0.
+    }
+}
+
+impl std::fmt::Display for TerrainWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if self.is_low() {
+            write!(
+                f,
+                "{} low altitude, terrain {}FT",
+                self.callsign, self.elevation as u32
+            )
+        } else {
+            write!(
+                f,
+                "{} terrain in {}s, {}FT",
+                self.callsign, self.time, self.elevation as u32
+            )
+        }
+    }
+}
+
+/// On the runway, taking off from it or on the glideslope to it, near the
+/// ground by intent.
+fn is_inhibited(aircraft: &Aircraft) -> bool {
+    aircraft.is_grounded()
+        || aircraft.status == AircraftStatus::Takeoff
+        || aircraft.approach == Some(ApproachState::GlideslopeCaptured)
+}
+
+/// Feet after `t` seconds, climbing or descending at the current vertical
+/// speed as far as the cleared altitude.
+fn predicted_altitude(aircraft: &Aircraft, t: f32) -> f32 {
+    let altitude = aircraft.altitude.current + aircraft.vertical_speed / 60. * t;
+    let cleared = aircraft.altitude.intended();
+    if aircraft.vertical_speed < 0. {
+        altitude.max(cleared)
+    } else {
+        altitude.min(cleared.max(aircraft.altitude.current))
+    }
+}
+
+/// Whether the aircraft has flown into the terrain, however near the
+/// ground it meant to be.
+pub fn has_crashed(aircraft: &Aircraft, terrain: &Terrain) -> bool {
+    let is_airborne = !aircraft.is_grounded() && !aircraft.is_rolling();
+    is_airborne && aircraft.altitude.current < terrain.elevation(&aircraft.position)
+}
+
+/// Airborne aircraft within, or predicted to come within, the terrain
+/// clearance in the lookahead time, each at the first point of its path
+/// that is.
+pub fn warnings(aircraft: &[Aircraft], terrain: &Terrain) -> Vec<TerrainWarning> {
+    let steps = (LOOKAHEAD / STEP) as u32;
+    aircraft
+        .iter()
+        .filter(|a| !is_inhibited(a))
+        .filter_map(|a| {
+            (0..=steps).map(|i| i as f32 * STEP).find_map(|t| {
+                let elevation = terrain.elevation(&(a.position + a.velocity() * t));
+                (predicted_altitude(a, t) < elevation + TERRAIN_CLEARANCE).then(|| TerrainWarning {
+                    callsign: a.callsign.clone(),
+                    time: t,
+                    elevation,
+                })
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::aircraft::test_aircraft;
+    use crate::atc::AtcRequest;
+    use crate::command::AtcCommand;
+    use crate::terrain::test_terrain;
+
+    #[test]
+    fn test_msaw_warnings() {
+        let terrain = test_terrain();
+        // towards the ridge
+        let warnings = warnings(&[test_aircraft(glm::zero(), 90., 2000.)], &terrain);
+        assert_eq!(1, warnings.len());
+        assert!(!warnings[0].is_low());
+        assert!(warnings[0].time > 0. && warnings[0].time <= LOOKAHEAD);
+        // away from it, or above it
+        assert!(super::warnings(&[test_aircraft(glm::zero(), 270., 2000.)], &terrain).is_empty());
+        assert!(super::warnings(&[test_aircraft(glm::zero(), 90., 5000.)], &terrain).is_empty());
+
+        // descending towards it, only when cleared low enough
+        let mut descending = test_aircraft(glm::zero(), 90., 4000.);
+        descending.command(AtcRequest(AtcCommand::ChangeAltitude(1000)));
+        descending.update(1.);
+        assert_eq!(1, super::warnings(&[descending.clone()], &terrain).len());
+        descending.command(AtcRequest(AtcCommand::ChangeAltitude(4000)));
+        assert!(super::warnings(&[descending], &terrain).is_empty());
+
+        let low = test_aircraft(glm::zero(), 270., 300.);
+        assert!(super::warnings(&[low], &terrain)[0].is_low());
+        // a missed approach is no safer from the terrain
+        let mut go_around = test_aircraft(glm::zero(), 90., 2000.);
+        go_around.status = AircraftStatus::GoAround;
+        assert_eq!(1, super::warnings(&[go_around], &terrain).len());
+        // on the glideslope
+        let mut landing = test_aircraft(glm::zero(), 270., 300.);
+        landing.approach = Some(ApproachState::GlideslopeCaptured);
+        assert!(super::warnings(&[landing], &terrain).is_empty());
+    }
+
+    #[test]
+    fn test_msaw_crash() {
+        let terrain = test_terrain();
+        let mut aircraft = test_aircraft(glm::zero(), 90., 2000.);
+        assert!(!has_crashed(&aircraft, &terrain));
+        aircraft.position = glm::vec2(8000., 0.);
+        assert!(has_crashed(&aircraft, &terrain));
+        // whatever it's cleared for
+        aircraft.approach = Some(ApproachState::GlideslopeCaptured);
+        assert!(has_crashed(&aircraft, &terrain));
+    }
+}
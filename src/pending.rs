@@ -0,0 +1,113 @@
+//! Instructions given ahead of time, carried out once their condition is met.
+
+use serde::{Deserialize, Serialize};
+
+use crate::command::{AtcCommand, Condition};
+use crate::units;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct PendingInstruction {
+    pub condition: Condition,
+    pub command: AtcCommand,
+    /// feet, when last updated, to tell when an altitude is passed
+    altitude: f32,
+    /// seconds since given
+    elapsed: f32,
+}
+
+impl PendingInstruction {
+    /// An instruction given to an aircraft at `altitude`.
+    pub fn new(condition: Condition, command: AtcCommand, altitude: f32) -> Self {
+        Self {
+            condition,
+            command,
+            altitude,
+            elapsed: 0.,
+        }
+    }
+
+    /// Whether the condition is met after another `dt` seconds, for an
+    /// aircraft at `altitude` and `position`. `target` is the position of
+    /// the fix or threshold the condition refers to, if it exists.
+    pub fn update(
+        &mut self,
+        dt: f32,
+        altitude: f32,
+        position: glm::Vec2,
+        target: Option<glm::Vec2>,
+    ) -> bool {
+        self.elapsed += dt;
+        let last = std::mem::replace(&mut self.altitude, altitude);
+        match (&self.condition, target) {
+            (Condition::Altitude(passing), _) => {
+                let passing = *passing as f32;
+                // reaching or crossing it since, not already level with it
+                last != passing && (last - passing) * (altitude - passing) <= 0.
+            }
+            (Condition::FixDistance(_, distance), Some(target))
+            | (Condition::ThresholdDistance(_, distance), Some(target)) => {
+                let meters = distance * units::NM_to_KM as f32 * 1000.;
+                glm::length(&(target - position)) <= meters
+            }
+            (Condition::Time(minutes), _) => self.elapsed >= minutes * 60.,
+            _ => false,
+        }
+    }
+
+    /// Short form for the data block, e.g. "A100 HDG".
+    pub fn tag(&self) -> String {
+        format!("{} {}", self.condition.tag(), self.command.keyword())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const NM: f32 = units::NM_to_KM as f32 * 1000.;
+
+    #[test]
+    fn test_pending_altitude() {
+        let command = AtcCommand::ChangeHeading(270);
+        let mut climbing = PendingInstruction::new(Condition::Altitude(10000), command, 5000.);
+        assert!(!climbing.update(1., 9990., glm::zero(), None));
+        assert!(climbing.update(1., 10010., glm::zero(), None));
+
+        let command = AtcCommand::ChangeSpeed(250);
+        let mut descending = PendingInstruction::new(Condition::Altitude(10000), command, 15000.);
+        assert!(!descending.update(1., 10010., glm::zero(), None));
+        assert!(descending.update(1., 10000., glm::zero(), None));
+        assert_eq!("A100 SPD", descending.tag());
+
+        // only once it's been left and reached again
+        let command = AtcCommand::ChangeHeading(270);
+        let mut level = PendingInstruction::new(Condition::Altitude(10000), command, 10000.);
+        assert!(!level.update(1., 10000., glm::zero(), None));
+        assert!(!level.update(1., 10100., glm::zero(), None));
+        assert!(level.update(1., 9990., glm::zero(), None));
+    }
+
+    #[test]
+    fn test_pending_distance() {
+        let condition = Condition::FixDistance("KOLOS".into(), 10.);
+        let mut pending = PendingInstruction::new(condition, AtcCommand::ChangeSpeed(180), 5000.);
+        let target = Some(glm::vec2(0., 10.5 * NM));
+        assert!(!pending.update(1., 5000., glm::zero(), target));
+        assert!(pending.update(1., 5000., glm::vec2(0., NM), target));
+        // never met for a fix that doesn't exist
+        assert!(!pending.update(1., 5000., glm::vec2(0., NM), None));
+        assert_eq!("KOLOS/10 SPD", pending.tag());
+    }
+
+    #[test]
+    fn test_pending_time() {
+        let mut pending = PendingInstruction::new(
+            Condition::Time(1.5),
+            AtcCommand::ChangeAltitude(3000),
+            5000.,
+        );
+        assert!(!pending.update(60., 5000., glm::zero(), None));
+        assert!(pending.update(30., 5000., glm::zero(), None));
+        assert_eq!("1.5MIN ALT", pending.tag());
+    }
+}
@@ -0,0 +1,220 @@
+//! Per aircraft type performance, loaded from a data file keyed by ICAO type
+//! designator.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::str::FromStr;
+
+use serde::Deserialize;
+
+use crate::error::impl_from;
+
+#[derive(Debug)]
+pub enum PerformanceError {
+    Io(std::io::Error),
+    Parse(toml::de::Error),
+    Invalid(String, &'static str),
+}
+
+impl std::fmt::Display for PerformanceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "failed to read performance data: {}", e),
+            Self::Parse(e) => write!(f, "failed to parse performance data: {}", e),
+            Self::Invalid(aircraft_type, reason) => write!(f, "{}: {}", aircraft_type, reason),
+        }
+    }
+}
+
+impl_from!(PerformanceError {
+    Io(std::io::Error),
+    Parse(toml::de::Error),
+});
+
+/// Climb and descent rates up to a ceiling.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct AltitudeBand {
+    /// feet
+    pub ceiling: u32,
+    /// feet per minute
+    pub climb_rate: u32,
+    /// feet per minute
+    pub descent_rate: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct Performance {
+    /// ICAO type designator, e.g. "A320"
+    #[serde(skip)]
+    pub aircraft_type: String,
+    pub name: String,
+    /// knots
+    pub min_speed: u32,
+    /// knots
+    pub max_speed: u32,
+    /// knots per second
+    pub acceleration: f32,
+    /// knots per second
+    pub deceleration: f32,
+    /// standard rate turn, in degrees per second
+    pub turn_rate: f32,
+    /// ordered by ceiling
+    pub bands: Vec<AltitudeBand>,
+}
+
+impl Performance {
+    fn band(&self, altitude: f32) -> &AltitudeBand {
+        self.bands
+            .iter()
+            .find(|band| altitude < band.ceiling as f32)
+            .or_else(|| self.bands.last())
+            .expect("validated to have at least one band")
+    }
+
+    /// Feet per minute at the altitude.
+    pub fn climb_rate(&self, altitude: f32) -> f32 {
+        self.band(altitude).climb_rate as f32
+    }
+
+    /// Feet per minute at the altitude.
+    pub fn descent_rate(&self, altitude: f32) -> f32 {
+        self.band(altitude).descent_rate as f32
+    }
+
+    /// Clamp a speed to the aircraft's envelope.
+    pub fn limit_speed(&self, speed: u32) -> u32 {
+        speed.clamp(self.min_speed, self.max_speed)
+    }
+
+    fn validate(&self) -> Result<(), PerformanceError> {
+        let invalid = |reason| {
+            Err(PerformanceError::Invalid(
+                self.aircraft_type.clone(),
+                reason,
+            ))
+        };
+
+        if self.min_speed > self.max_speed {
+            invalid("min_speed above max_speed")
+        } else if self.acceleration <= 0. || self.deceleration <= 0. || self.turn_rate <= 0. {
+            invalid("rates must be positive")
+        } else if self.bands.is_empty() {
+            invalid("no altitude bands")
+        } else if self.bands.windows(2).any(|w| w[0].ceiling >= w[1].ceiling) {
+            invalid("altitude bands out of order")
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Performance of every known aircraft type.
+#[derive(Clone, Debug)]
+pub struct PerformanceDatabase {
+    aircraft: HashMap<String, Performance>,
+}
+
+impl PerformanceDatabase {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, PerformanceError> {
+        std::fs::read_to_string(path)?.parse()
+    }
+
+    pub fn get(&self, aircraft_type: &str) -> Option<&Performance> {
+        self.aircraft.get(&aircraft_type.to_uppercase())
+    }
+}
+
+impl FromStr for PerformanceDatabase {
+    type Err = PerformanceError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut aircraft: HashMap<String, Performance> = toml::from_str(s)?;
+        for (aircraft_type, performance) in aircraft.iter_mut() {
+            performance.aircraft_type = aircraft_type.clone();
+            performance.validate()?;
+        }
+        Ok(Self { aircraft })
+    }
+}
+
+/// The bundled performance data file.
+#[cfg(test)]
+pub const TEST_DATA: &str = include_str!("../data/performance.toml");
+
+#[cfg(test)]
+pub fn test_database() -> PerformanceDatabase {
+    TEST_DATA.parse().unwrap()
+}
+
+/// Performance of a type in the bundled data.
+#[cfg(test)]
+pub fn test_performance(aircraft_type: &str) -> Performance {
+    test_database().get(aircraft_type).unwrap().clone()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_performance_database() {
+        let db = test_database();
+        let a320 = db.get("A320").unwrap();
+        assert_eq!("A320", a320.aircraft_type);
+        assert_eq!("Airbus A320", a320.name);
+        assert_eq!(130, a320.min_speed);
+        assert_eq!(250, a320.max_speed);
+        assert_eq!(3.0, a320.turn_rate);
+
+        assert_eq!(a320, db.get("a320").unwrap());
+        assert!(db.get("C172").is_none());
+    }
+
+    #[test]
+    fn test_performance_altitude_bands() {
+        let db = test_database();
+        let b738 = db.get("B738").unwrap();
+        assert_eq!(3000.0, b738.climb_rate(0.0));
+        assert_eq!(3000.0, b738.climb_rate(9999.0));
+        assert_eq!(2200.0, b738.climb_rate(10000.0));
+        assert_eq!(2500.0, b738.descent_rate(15000.0));
+        // above the highest band
+        assert_eq!(1200.0, b738.climb_rate(45000.0));
+    }
+
+    #[test]
+    fn test_performance_limit_speed() {
+        let db = test_database();
+        let at72 = db.get("AT72").unwrap();
+        assert_eq!(110, at72.limit_speed(90));
+        assert_eq!(180, at72.limit_speed(180));
+        assert_eq!(230, at72.limit_speed(250));
+    }
+
+    #[test]
+    fn test_performance_errors() {
+        assert!(matches!(
+            "[A320]".parse::<PerformanceDatabase>(),
+            Err(PerformanceError::Parse(_))
+        ));
+        assert!(matches!(
+            PerformanceDatabase::load("does/not/exist.toml"),
+            Err(PerformanceError::Io(_))
+        ));
+
+        let invalid = r#"
+            [A320]
+            name = "Airbus A320"
+            min_speed = 130
+            max_speed = 250
+            acceleration = 2.0
+            deceleration = 1.5
+            turn_rate = 3.0
+            bands = []
+        "#;
+        assert!(matches!(
+            invalid.parse::<PerformanceDatabase>(),
+            Err(PerformanceError::Invalid(t, "no altitude bands")) if t == "A320"
+        ));
+    }
+}
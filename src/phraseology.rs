@@ -0,0 +1,900 @@
+//! ICAO phraseology typed in full, an alternative to the token syntax, e.g.
+//! "CYP2202 turn left heading two seven zero, descend and maintain three
+//! thousand, cleared ILS runway 29".
+
+use crate::aircraft::{Callsign, TurnDirection};
+use crate::command::{
+    tokenize, AtcCommand, CliCommand, Condition, ParseError, ParseErrorKind, Token,
+};
+
+/// Words an instruction can start with.
+const STARTERS: &[&str] = &[
+    "TURN", "FLY", "CLIMB", "DESCEND", "MAINTAIN", "REACH", "SPEED", "REDUCE", "INCREASE",
+    "CLEARED", "CANCEL", "GO", "LINE", "PROCEED", "DIRECT", "EXPEDITE", "HOLD", "AFTER", "WHEN",
+    "AT", "IN", "CONTACT",
+];
+
+fn digit(word: &str) -> Option<f32> {
+    Some(match word {
+        "ZERO" => 0.,
+        "ONE" => 1.,
+        "TWO" => 2.,
+        "THREE" | "TREE" => 3.,
+        "FOUR" => 4.,
+        "FIVE" | "FIFE" => 5.,
+        "SIX" => 6.,
+        "SEVEN" => 7.,
+        "EIGHT" => 8.,
+        "NINE" | "NINER" => 9.,
+        _ => return None,
+    })
+}
+
+fn is_numeric(word: &str) -> bool {
+    word.starts_with(|c: char| c.is_ascii_digit()) && word.parse::<f32>().is_ok()
+}
+
+/// Whether the upper-cased line is phraseology rather than the token
+/// syntax, going by its first word after an optional callsign. HOLD and WHEN
+/// are also token keywords, so need the word after them to tell.
+pub fn is_phrase(s: &str) -> bool {
+    let text = s.replace(',', " ");
+    let tokens = tokenize(&text);
+    let mut words = tokens.iter().map(|token| token.text).peekable();
+    if matches!(words.peek(), Some(word) if Callsign::from_string(word.to_string()).is_some()) {
+        words.next();
+    }
+    match (words.next(), words.next()) {
+        (Some("HOLD"), next) => next == Some("AT"),
+        (Some("WHEN"), next) => next == Some("PASSING"),
+        (Some(word), _) => STARTERS.contains(&word),
+        (None, _) => false,
+    }
+}
+
+/// Parse an upper-cased transmission of phraseology. A leading callsign
+/// addresses the aircraft, commas and "and" between instructions are
+/// optional.
+pub fn parse(s: &str) -> Result<Vec<CliCommand>, ParseError> {
+    // commas are single bytes, so replacing them keeps the positions
+    let text = s.replace(',', " ");
+    let tokens = tokenize(&text);
+    let mut words = Words {
+        input: s,
+        tokens: &tokens,
+        next: 0,
+    };
+
+    let callsign = words
+        .peek()
+        .and_then(|word| Callsign::from_string(word.to_string()));
+    if callsign.is_some() {
+        words.next += 1;
+    }
+    let mut commands = Vec::new();
+    while words.peek().is_some() {
+        if words.accept("AND") {
+            continue;
+        }
+        commands.extend(words.instruction()?);
+    }
+    Ok(commands
+        .into_iter()
+        .map(|cmd| match &callsign {
+            Some(callsign) => CliCommand::Addressed(callsign.clone(), cmd),
+            None => CliCommand::Atc(cmd),
+        })
+        .collect())
+}
+
+struct Words<'a> {
+    /// the line with its commas, which end numbers
+    input: &'a str,
+    tokens: &'a [Token<'a>],
+    next: usize,
+}
+
+impl<'a> Words<'a> {
+    fn peek(&self) -> Option<&'a str> {
+        self.tokens.get(self.next).map(|token| token.text)
+    }
+
+    fn position(&self) -> usize {
+        self.tokens
+            .get(self.next)
+            .map_or(self.input.len(), |token| token.position)
+    }
+
+    fn after_comma(&self) -> bool {
+        self.input[..self.position()].trim_end().ends_with(',')
+    }
+
+    fn advance(&mut self) -> Option<Token<'a>> {
+        let token = self.tokens.get(self.next).copied();
+        self.next += 1;
+        token
+    }
+
+    /// Skip the word if it is next.
+    fn accept(&mut self, word: &str) -> bool {
+        let found = self.peek() == Some(word);
+        if found {
+            self.next += 1;
+        }
+        found
+    }
+
+    /// Skip one of the words if it is next.
+    fn accept_any(&mut self, words: &[&str]) -> bool {
+        words.iter().any(|word| self.accept(word))
+    }
+
+    fn expect(&mut self, word: &'static str) -> Result<(), ParseError> {
+        if self.accept(word) {
+            Ok(())
+        } else {
+            Err(self.expected(word))
+        }
+    }
+
+    fn expected(&self, expected: &'static str) -> ParseError {
+        ParseError::new(
+            ParseErrorKind::ExpectedWord {
+                expected,
+                found: self.peek().map(String::from),
+            },
+            self.position(),
+        )
+    }
+
+    fn missing(&self, command: &Token, argument: &'static str) -> ParseError {
+        ParseError::new(
+            ParseErrorKind::MissingArgument {
+                command: command.text.to_string(),
+                argument,
+            },
+            command.position,
+        )
+    }
+
+    fn is_number_next(&self) -> bool {
+        matches!(self.peek(), Some(word) if digit(word).is_some() || is_numeric(word))
+    }
+
+    /// A number in digits or spoken, e.g. "two seven zero", "three thousand
+    /// five hundred" or "one decimal five".
+    fn number(&mut self, command: &Token, argument: &'static str) -> Result<f32, ParseError> {
+        let first = match self.peek() {
+            Some(word) => word,
+            None => return Err(self.missing(command, argument)),
+        };
+        let position = self.position();
+        let (mut total, mut current) = (0., 0.);
+        // place value of the next digit after a decimal point
+        let mut fraction: Option<f32> = None;
+        let mut any = false;
+        while let Some(word) = self.peek() {
+            if any && self.after_comma() {
+                break;
+            }
+            match (digit(word), fraction) {
+                (Some(d), Some(place)) => {
+                    current += d * place;
+                    fraction = Some(place / 10.);
+                }
+                (Some(d), None) => current = current * 10. + d,
+                (None, None) if any && word == "HUNDRED" => {
+                    total += current * 100.;
+                    current = 0.;
+                }
+                (None, None) if any && word == "THOUSAND" => {
+                    total += current * 1000.;
+                    current = 0.;
+                }
+                (None, None) if any && (word == "DECIMAL" || word == "POINT") => {
+                    fraction = Some(0.1)
+                }
+                (None, None) if !any && is_numeric(word) => current = word.parse().unwrap(),
+                _ => break,
+            }
+            any = true;
+            self.next += 1;
+        }
+        if any {
+            Ok(total + current)
+        } else {
+            Err(ParseError::new(
+                ParseErrorKind::InvalidNumber(first.to_string()),
+                position,
+            ))
+        }
+    }
+
+    /// A number greater than zero, e.g. a distance.
+    fn positive(&mut self, command: &Token, argument: &'static str) -> Result<f32, ParseError> {
+        let position = self.position();
+        match self.number(command, argument)? {
+            value if value > 0. => Ok(value),
+            value => Err(ParseError::new(
+                ParseErrorKind::InvalidNumber(value.to_string()),
+                position,
+            )),
+        }
+    }
+
+    fn heading(&mut self, command: &Token) -> Result<i32, ParseError> {
+        let position = self.position();
+        let heading = self.number(command, "a heading")? as i32;
+        if (0..=360).contains(&heading) {
+            Ok(heading)
+        } else {
+            Err(ParseError::new(
+                ParseErrorKind::HeadingOutOfRange(heading),
+                position,
+            ))
+        }
+    }
+
+    /// An altitude in feet, either "flight level one zero zero" or
+    /// "[altitude] three thousand [feet]".
+    fn level(&mut self, command: &Token) -> Result<u32, ParseError> {
+        if self.accept("FLIGHT") {
+            self.expect("LEVEL")?;
+            return Ok(self.number(command, "a flight level")? as u32 * 100);
+        }
+        self.accept("ALTITUDE");
+        let altitude = self.number(command, "an altitude")? as u32;
+        self.accept("FEET");
+        Ok(altitude)
+    }
+
+    fn speed(&mut self, command: &Token) -> Result<u32, ParseError> {
+        let speed = self.number(command, "a speed")? as u32;
+        self.accept("KNOTS");
+        Ok(speed)
+    }
+
+    fn name(&mut self, command: &Token, argument: &'static str) -> Result<String, ParseError> {
+        match self.advance() {
+            Some(token) => Ok(token.text.to_string()),
+            None => Err(self.missing(command, argument)),
+        }
+    }
+
+    /// A runway designator after "runway", e.g. "two niner", "29" or "one
+    /// one left".
+    fn runway(&mut self, command: &Token) -> Result<String, ParseError> {
+        self.expect("RUNWAY")?;
+        let mut runway = String::new();
+        while let Some(d) = self.peek().and_then(digit) {
+            runway.push_str(&d.to_string());
+            self.next += 1;
+        }
+        if runway.is_empty() {
+            runway = self.name(command, "a runway")?;
+        }
+        match self.peek() {
+            Some("LEFT") => runway.push('L'),
+            Some("RIGHT") => runway.push('R'),
+            Some("CENTER") | Some("CENTRE") => runway.push('C'),
+            _ => return Ok(runway),
+        }
+        self.next += 1;
+        Ok(runway)
+    }
+
+    fn turn_direction(&mut self) -> Result<TurnDirection, ParseError> {
+        let direction = match self.peek() {
+            Some("LEFT") => TurnDirection::Left,
+            Some("RIGHT") => TurnDirection::Right,
+            _ => return Err(self.expected("LEFT or RIGHT")),
+        };
+        self.next += 1;
+        Ok(direction)
+    }
+
+    fn instruction(&mut self) -> Result<Vec<AtcCommand>, ParseError> {
+        let token = match self.advance() {
+            Some(token) => token,
+            None => return Ok(vec![]),
+        };
+        let command = match token.text {
+            "TURN" => {
+                let direction = self.turn_direction()?;
+                self.accept("HEADING");
+                AtcCommand::ChangeHeadingWithTurnDirection(self.heading(&token)?, direction)
+            }
+            "FLY" if self.accept("DIRECT") => {
+                self.accept("TO");
+                AtcCommand::DirectTo(self.name(&token, "a fix")?)
+            }
+            "FLY" => {
+                self.expect("HEADING")?;
+                AtcCommand::ChangeHeading(self.heading(&token)?)
+            }
+            "CLIMB" | "DESCEND" if self.accept("AT") => {
+                let rate = self.number(&token, "a rate")? as u32;
+                self.expect("FEET")?;
+                self.expect("PER")?;
+                self.expect("MINUTE")?;
+                AtcCommand::VerticalRate(rate)
+            }
+            "CLIMB" | "DESCEND" => {
+                if self.accept("AND") {
+                    self.expect("MAINTAIN")?;
+                }
+                self.accept("TO");
+                AtcCommand::ChangeAltitude(self.level(&token)?)
+            }
+            "MAINTAIN" if matches!(self.peek(), Some("FLIGHT") | Some("ALTITUDE")) => {
+                AtcCommand::ChangeAltitude(self.level(&token)?)
+            }
+            "MAINTAIN" => {
+                let value = self.number(&token, "an altitude or speed")? as u32;
+                if self.accept("KNOTS") {
+                    AtcCommand::ChangeSpeed(value)
+                } else {
+                    self.accept("FEET");
+                    AtcCommand::ChangeAltitude(value)
+                }
+            }
+            "REACH" => {
+                let altitude = self.level(&token)?;
+                self.expect("BY")?;
+                let fix = self.name(&token, "a fix")?;
+                return Ok(vec![
+                    AtcCommand::ChangeAltitude(altitude),
+                    AtcCommand::ReachBy(fix),
+                ]);
+            }
+            "SPEED" => AtcCommand::ChangeSpeed(self.speed(&token)?),
+            "REDUCE" | "INCREASE" => {
+                self.accept("SPEED");
+                self.accept("TO");
+                AtcCommand::ChangeSpeed(self.speed(&token)?)
+            }
+            "CLEARED" => {
+                self.accept("FOR");
+                if self.accept("ILS") {
+                    self.accept("APPROACH");
+                    AtcCommand::ClearedForApproach(self.runway(&token)?)
+                } else if self.accept("TO") {
+                    self.expect("LAND")?;
+                    AtcCommand::ClearedToLand(Some(self.runway(&token)?))
+                } else if self.accept("TAKEOFF") {
+                    AtcCommand::ClearedForTakeoff
+                } else {
+                    return Err(self.expected("ILS, TO LAND or TAKEOFF"));
+                }
+            }
+            "CANCEL" => {
+                self.expect("LANDING")?;
+                self.expect("CLEARANCE")?;
+                AtcCommand::ClearedToLand(None)
+            }
+            "GO" => {
+                self.expect("AROUND")?;
+                AtcCommand::GoAround
+            }
+            "LINE" => {
+                self.expect("UP")?;
+                self.expect("AND")?;
+                self.expect("WAIT")?;
+                AtcCommand::LineUp
+            }
+            "PROCEED" | "DIRECT" => {
+                if token.text == "PROCEED" {
+                    self.expect("DIRECT")?;
+                }
+                self.accept("TO");
+                AtcCommand::DirectTo(self.name(&token, "a fix")?)
+            }
+            "EXPEDITE" => {
+                self.accept_any(&["CLIMB", "DESCENT"]);
+                AtcCommand::Expedite
+            }
+            "HOLD" => self.hold(&token)?,
+            "CONTACT" => self.handoff(&token)?,
+            "AFTER" | "WHEN" | "AT" | "IN" => return self.conditional(&token),
+            other => {
+                return Err(ParseError::new(
+                    ParseErrorKind::UnknownToken(other.to_string()),
+                    token.position,
+                ))
+            }
+        };
+        Ok(vec![command])
+    }
+
+    /// "contact departure one two zero decimal two", the unit by the name it
+    /// is given in handoffs and the frequency as written, if given.
+    fn handoff(&mut self, command: &Token) -> Result<AtcCommand, ParseError> {
+        let unit = match self.name(command, "a unit")?.as_str() {
+            "GROUND" => String::from("GND"),
+            "TOWER" => String::from("TWR"),
+            "DEPARTURE" => String::from("DEP"),
+            "APPROACH" => String::from("APP"),
+            "CONTROL" | "CENTER" | "CENTRE" => String::from("CTR"),
+            other => other.to_string(),
+        };
+        let frequency = if self.is_number_next() {
+            // to the nearest kHz, without trailing zeros
+            let frequency = format!("{:.3}", self.positive(command, "a frequency")?);
+            Some(
+                frequency
+                    .trim_end_matches('0')
+                    .trim_end_matches('.')
+                    .to_string(),
+            )
+        } else {
+            None
+        };
+        Ok(AtcCommand::Handoff { unit, frequency })
+    }
+
+    /// "hold at KOLOS, inbound track 285, left hand pattern, one minute
+    /// legs", the turns are to the right unless given.
+    fn hold(&mut self, command: &Token) -> Result<AtcCommand, ParseError> {
+        self.accept("AT");
+        let fix = self.name(command, "a fix")?;
+        self.expect("INBOUND")?;
+        self.accept_any(&["TRACK", "COURSE"]);
+        let course = self.heading(command)?;
+        let direction = match self.peek() {
+            Some("LEFT") | Some("RIGHT") => {
+                let direction = self.turn_direction()?;
+                self.accept("HAND");
+                self.accept_any(&["PATTERN", "TURNS"]);
+                direction
+            }
+            _ => TurnDirection::Right,
+        };
+        let leg_time = if self.is_number_next() {
+            let minutes = self.positive(command, "minutes")?;
+            self.accept_any(&["MINUTE", "MINUTES"]);
+            self.accept_any(&["LEG", "LEGS"]);
+            Some(minutes)
+        } else {
+            None
+        };
+        Ok(AtcCommand::Hold {
+            fix,
+            course,
+            direction,
+            leg_time,
+        })
+    }
+
+    /// A condition, e.g. "after passing flight level one zero zero", "at
+    /// one zero miles from KOLOS" or "in two minutes", applied to the
+    /// instruction after it.
+    fn conditional(&mut self, command: &Token) -> Result<Vec<AtcCommand>, ParseError> {
+        let condition = match command.text {
+            "AFTER" | "WHEN" => {
+                self.expect("PASSING")?;
+                Condition::Altitude(self.level(command)?)
+            }
+            "AT" => {
+                let distance = self.positive(command, "a distance")?;
+                if !self.accept_any(&["MILES", "MILE"]) {
+                    return Err(self.expected("MILES"));
+                }
+                self.expect("FROM")?;
+                if self.peek() == Some("RUNWAY") {
+                    Condition::ThresholdDistance(self.runway(command)?, distance)
+                } else {
+                    Condition::FixDistance(self.name(command, "a fix")?, distance)
+                }
+            }
+            _ => {
+                let minutes = self.positive(command, "minutes")?;
+                if !self.accept_any(&["MINUTES", "MINUTE"]) {
+                    return Err(self.expected("MINUTES"));
+                }
+                Condition::Time(minutes)
+            }
+        };
+        if self.peek().is_none() {
+            return Err(self.missing(command, "an instruction"));
+        }
+        Ok(self
+            .instruction()?
+            .into_iter()
+            .map(|command| AtcCommand::Conditional {
+                condition: condition.clone(),
+                command: Box::new(command),
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse(s: &str) -> Result<Vec<CliCommand>, ParseError> {
+        CliCommand::from_string(s.to_string())
+    }
+
+    fn atc(s: &str) -> Vec<AtcCommand> {
+        parse(s)
+            .unwrap()
+            .into_iter()
+            .map(|command| match command {
+                CliCommand::Atc(command) => command,
+                other => panic!("{:?} is not an ATC command", other),
+            })
+            .collect()
+    }
+
+    fn error(s: &str) -> (ParseErrorKind, usize) {
+        let e = parse(s).unwrap_err();
+        (e.kind, e.position)
+    }
+
+    #[test]
+    fn test_phraseology_full_transmission() {
+        let callsign = Callsign::from_string("CYP2202".into()).unwrap();
+        assert_eq!(
+            vec![
+                CliCommand::Addressed(
+                    callsign.clone(),
+                    AtcCommand::ChangeHeadingWithTurnDirection(270, TurnDirection::Left)
+                ),
+                CliCommand::Addressed(callsign.clone(), AtcCommand::ChangeAltitude(3000)),
+                CliCommand::Addressed(callsign, AtcCommand::ClearedForApproach("29".into())),
+            ],
+            parse(
+                "CYP2202 turn left heading two seven zero, descend and maintain three \
+                 thousand, cleared ILS runway 29"
+            )
+            .unwrap()
+        );
+        // the same without punctuation, or joined with "and"
+        assert_eq!(
+            atc("turn left heading 270 and descend to 3000 feet cleared for ILS approach runway two niner"),
+            vec![
+                AtcCommand::ChangeHeadingWithTurnDirection(270, TurnDirection::Left),
+                AtcCommand::ChangeAltitude(3000),
+                AtcCommand::ClearedForApproach("29".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_phraseology_numbers() {
+        let altitude = |s: &str| atc(&format!("climb and maintain {}", s));
+        assert_eq!(
+            vec![AtcCommand::ChangeAltitude(3000)],
+            altitude("three thousand")
+        );
+        assert_eq!(
+            vec![AtcCommand::ChangeAltitude(3500)],
+            altitude("tree thousand fife hundred feet")
+        );
+        assert_eq!(
+            vec![AtcCommand::ChangeAltitude(10000)],
+            altitude("one zero thousand")
+        );
+        assert_eq!(
+            vec![AtcCommand::ChangeAltitude(11500)],
+            altitude("one one thousand five hundred")
+        );
+        assert_eq!(
+            vec![AtcCommand::ChangeAltitude(9000)],
+            altitude("flight level niner zero")
+        );
+        assert_eq!(
+            vec![AtcCommand::ChangeAltitude(24000)],
+            altitude("flight level 240")
+        );
+        assert_eq!(
+            vec![AtcCommand::ChangeAltitude(6000)],
+            altitude("altitude 6 thousand")
+        );
+        assert_eq!(
+            vec![AtcCommand::ChangeHeading(5)],
+            atc("fly heading zero zero five")
+        );
+        assert_eq!(
+            vec![AtcCommand::Hold {
+                fix: "KOLOS".into(),
+                course: 90,
+                direction: TurnDirection::Right,
+                leg_time: Some(1.5),
+            }],
+            atc("hold at kolos inbound track zero niner zero, one decimal five minute legs")
+        );
+    }
+
+    #[test]
+    fn test_phraseology_instructions() {
+        let phrases = [
+            (
+                "turn right heading 090",
+                AtcCommand::ChangeHeadingWithTurnDirection(90, TurnDirection::Right),
+            ),
+            (
+                "turn left three six zero",
+                AtcCommand::ChangeHeadingWithTurnDirection(360, TurnDirection::Left),
+            ),
+            ("fly heading one eight zero", AtcCommand::ChangeHeading(180)),
+            (
+                "descend flight level eight zero",
+                AtcCommand::ChangeAltitude(8000),
+            ),
+            (
+                "maintain four thousand feet",
+                AtcCommand::ChangeAltitude(4000),
+            ),
+            (
+                "maintain flight level one two zero",
+                AtcCommand::ChangeAltitude(12000),
+            ),
+            (
+                "maintain one eight zero knots",
+                AtcCommand::ChangeSpeed(180),
+            ),
+            ("speed two two zero knots", AtcCommand::ChangeSpeed(220)),
+            ("reduce speed to one six zero", AtcCommand::ChangeSpeed(160)),
+            ("increase 250 knots", AtcCommand::ChangeSpeed(250)),
+            (
+                "climb at one five hundred feet per minute",
+                AtcCommand::VerticalRate(1500),
+            ),
+            (
+                "descend at 800 feet per minute",
+                AtcCommand::VerticalRate(800),
+            ),
+            ("expedite descent", AtcCommand::Expedite),
+            (
+                "cleared to land runway one one left",
+                AtcCommand::ClearedToLand(Some("11L".into())),
+            ),
+            (
+                "cleared to land runway 29",
+                AtcCommand::ClearedToLand(Some("29".into())),
+            ),
+            ("cancel landing clearance", AtcCommand::ClearedToLand(None)),
+            ("go around", AtcCommand::GoAround),
+            ("line up and wait", AtcCommand::LineUp),
+            ("cleared for takeoff", AtcCommand::ClearedForTakeoff),
+            ("proceed direct kolos", AtcCommand::DirectTo("KOLOS".into())),
+            ("fly direct to pha", AtcCommand::DirectTo("PHA".into())),
+            ("direct tomba", AtcCommand::DirectTo("TOMBA".into())),
+            (
+                "hold at pha inbound course two eight five, left hand pattern",
+                AtcCommand::Hold {
+                    fix: "PHA".into(),
+                    course: 285,
+                    direction: TurnDirection::Left,
+                    leg_time: None,
+                },
+            ),
+            (
+                "contact departure one two zero decimal two",
+                AtcCommand::Handoff {
+                    unit: "DEP".into(),
+                    frequency: Some("120.2".into()),
+                },
+            ),
+            (
+                "contact TWR 118.075",
+                AtcCommand::Handoff {
+                    unit: "TWR".into(),
+                    frequency: Some("118.075".into()),
+                },
+            ),
+            (
+                "contact tower",
+                AtcCommand::Handoff {
+                    unit: "TWR".into(),
+                    frequency: None,
+                },
+            ),
+        ];
+        for (phrase, command) in phrases {
+            assert_eq!(vec![command], atc(phrase), "{}", phrase);
+        }
+        assert_eq!(
+            vec![
+                AtcCommand::ChangeAltitude(5000),
+                AtcCommand::ReachBy("KOLOS".into())
+            ],
+            atc("reach five thousand by kolos")
+        );
+    }
+
+    #[test]
+    fn test_phraseology_conditions() {
+        let conditional = |condition, command| AtcCommand::Conditional {
+            condition,
+            command: Box::new(command),
+        };
+        assert_eq!(
+            vec![
+                conditional(
+                    Condition::Altitude(10000),
+                    AtcCommand::ChangeHeadingWithTurnDirection(270, TurnDirection::Left)
+                ),
+                AtcCommand::ChangeSpeed(250),
+            ],
+            atc("after passing flight level one zero zero, turn left heading 270, speed 250")
+        );
+        assert_eq!(
+            vec![conditional(
+                Condition::Altitude(5000),
+                AtcCommand::ChangeSpeed(210)
+            )],
+            atc("when passing five thousand feet reduce speed to 210 knots")
+        );
+        assert_eq!(
+            vec![conditional(
+                Condition::FixDistance("KOLOS".into(), 10.),
+                AtcCommand::DirectTo("PHA".into())
+            )],
+            atc("at one zero miles from kolos, proceed direct pha")
+        );
+        assert_eq!(
+            vec![conditional(
+                Condition::ThresholdDistance("29".into(), 4.),
+                AtcCommand::ClearedToLand(Some("29".into()))
+            )],
+            atc("at four miles from runway 29, cleared to land runway 29")
+        );
+        assert_eq!(
+            vec![
+                conditional(Condition::Time(2.), AtcCommand::ChangeAltitude(3000)),
+                conditional(Condition::Time(2.), AtcCommand::ReachBy("KOLOS".into())),
+            ],
+            atc("in two minutes reach three thousand by kolos")
+        );
+    }
+
+    #[test]
+    fn test_phraseology_detection() {
+        assert!(is_phrase("TURN LEFT HEADING 270"));
+        assert!(is_phrase("CYP2202 GO AROUND"));
+        assert!(is_phrase("HOLD AT KOLOS INBOUND 090"));
+        assert!(is_phrase("WHEN PASSING 5000 SPEED 210"));
+        // token syntax
+        assert!(!is_phrase("HOLD KOLOS 090 R"));
+        assert!(!is_phrase("WHEN ALT 5000 SPD 210"));
+        assert!(!is_phrase("HDG 180"));
+        assert!(!is_phrase("CYP2202"));
+        assert!(!is_phrase(""));
+        assert_eq!(
+            vec![AtcCommand::Hold {
+                fix: "KOLOS".into(),
+                course: 90,
+                direction: TurnDirection::Right,
+                leg_time: None,
+            }],
+            atc("HOLD KOLOS 090 R")
+        );
+    }
+
+    #[test]
+    fn test_phraseology_rejected() {
+        assert_eq!(
+            (
+                ParseErrorKind::ExpectedWord {
+                    expected: "LEFT or RIGHT",
+                    found: Some("HEADING".into())
+                },
+                5
+            ),
+            error("turn heading 270")
+        );
+        assert_eq!(
+            (
+                ParseErrorKind::MissingArgument {
+                    command: "TURN".into(),
+                    argument: "a heading"
+                },
+                0
+            ),
+            error("turn left heading")
+        );
+        assert_eq!(
+            (ParseErrorKind::InvalidNumber("BANANA".into()), 18),
+            error("turn left heading banana")
+        );
+        assert_eq!(
+            (ParseErrorKind::HeadingOutOfRange(400), 12),
+            error("fly heading four zero zero")
+        );
+        assert_eq!(
+            (
+                ParseErrorKind::ExpectedWord {
+                    expected: "WAIT",
+                    found: None
+                },
+                11
+            ),
+            error("line up and")
+        );
+        assert_eq!(
+            (
+                ParseErrorKind::ExpectedWord {
+                    expected: "AROUND",
+                    found: Some("HOME".into())
+                },
+                3
+            ),
+            error("go home")
+        );
+        assert_eq!(
+            (
+                ParseErrorKind::ExpectedWord {
+                    expected: "ILS, TO LAND or TAKEOFF",
+                    found: Some("VISUAL".into())
+                },
+                8
+            ),
+            error("cleared visual approach")
+        );
+        assert_eq!(
+            (
+                ParseErrorKind::ExpectedWord {
+                    expected: "RUNWAY",
+                    found: None
+                },
+                15
+            ),
+            error("cleared to land")
+        );
+        assert_eq!(
+            (
+                ParseErrorKind::MissingArgument {
+                    command: "CLEARED".into(),
+                    argument: "a runway"
+                },
+                0
+            ),
+            error("cleared ILS runway")
+        );
+        assert_eq!(
+            (
+                ParseErrorKind::ExpectedWord {
+                    expected: "MAINTAIN",
+                    found: Some("HOLD".into())
+                },
+                12
+            ),
+            error("descend and hold")
+        );
+        assert_eq!(
+            (ParseErrorKind::InvalidNumber("0".into()), 3),
+            error("at zero miles from kolos go around")
+        );
+        assert_eq!(
+            (
+                ParseErrorKind::MissingArgument {
+                    command: "AFTER".into(),
+                    argument: "an instruction"
+                },
+                0
+            ),
+            error("after passing 5000")
+        );
+        assert_eq!(
+            (
+                ParseErrorKind::ExpectedWord {
+                    expected: "INBOUND",
+                    found: Some("285".into())
+                },
+                15
+            ),
+            error("hold at kolos, 285")
+        );
+        assert_eq!(
+            (ParseErrorKind::UnknownToken("PLEASE".into()), 31),
+            error("CYP2202 climb flight level 100 please")
+        );
+        assert_eq!(
+            "expected AROUND but found HOME",
+            parse("go home").unwrap_err().to_string()
+        );
+    }
+}
@@ -0,0 +1,294 @@
+//! Transmissions pilots make without being spoken to, as their aircraft
+//! changes state.
+
+use crate::aircraft::{Aircraft, AircraftStatus, ApproachState};
+
+/// seconds level at the cleared altitude before asking for another
+const REQUEST_DELAY: f32 = 120.;
+/// feet, arrivals at or below it wait for the approach rather than asking
+/// to descend
+const APPROACH_ALTITUDE: f32 = 5000.;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum PilotMessage {
+    /// first call on the frequency when airborne, altitudes in feet, with the
+    /// ATIS information letter
+    CheckIn {
+        altitude: u32,
+        cleared: u32,
+        atis: char,
+    },
+    /// first call on the frequency when holding short of the runway
+    ReadyForDeparture {
+        runway: String,
+        atis: char,
+    },
+    EstablishedLocalizer(String),
+    RequestHigher,
+    RequestLower,
+    /// with nothing left to fly, after a missed approach or reaching a fix
+    RequestVectors,
+}
+
+impl PilotMessage {
+    pub fn as_string(&self) -> String {
+        use PilotMessage::*;
+        match self {
+            CheckIn {
+                altitude,
+                cleared,
+                atis,
+            } if altitude == cleared => {
+                format!("with you at {} feet, information {}", altitude, atis)
+            }
+            CheckIn {
+                altitude,
+                cleared,
+                atis,
+            } => format!(
+                "passing {} feet for {}, information {}",
+                altitude, cleared, atis
+            ),
+            ReadyForDeparture { runway, atis } => format!(
+                "holding short runway {}, ready for departure, information {}",
+                runway, atis
+            ),
+            EstablishedLocalizer(runway) => format!("established localizer {}", runway),
+            RequestHigher => String::from("request higher"),
+            RequestLower => String::from("request lower"),
+            RequestVectors => String::from("request vectors"),
+        }
+    }
+}
+
+fn hundreds(feet: f32) -> u32 {
+    (feet / 100.).round() as u32 * 100
+}
+
+/// What a pilot remembers of their aircraft after its last step, to tell
+/// when it changed.
+#[derive(Clone, Debug, Default)]
+pub struct Pilot {
+    checked_in: bool,
+    /// took off from the airport, so asks to climb rather than descend
+    departure: bool,
+    status: Option<AircraftStatus>,
+    approach: Option<ApproachState>,
+    direct: Option<String>,
+    intended_heading: f32,
+    intended_altitude: f32,
+    /// seconds level at the cleared altitude
+    level_time: f32,
+    /// asked for another altitude since cleared to this one
+    has_requested: bool,
+}
+
+impl Pilot {
+    /// What the pilot says after the aircraft's step of `dt` seconds, if
+    /// anything, `atis` being the current information letter.
+    pub fn update(&mut self, aircraft: &Aircraft, dt: f32, atis: char) -> Option<PilotMessage> {
+        let message = self.message(aircraft, dt, atis);
+        self.status = Some(aircraft.status.clone());
+        self.approach = aircraft.approach;
+        self.direct = aircraft.direct.clone();
+        self.intended_heading = aircraft.heading.intended();
+        message
+    }
+
+    fn message(&mut self, aircraft: &Aircraft, dt: f32, atis: char) -> Option<PilotMessage> {
+        use AircraftStatus::*;
+        // on another unit's frequency, calling again if it comes back
+        if !aircraft.is_under_control() {
+            self.checked_in = false;
+            return None;
+        }
+
+        let altitude = aircraft.altitude.current;
+        let cleared = aircraft.altitude.intended();
+        if cleared != self.intended_altitude {
+            self.intended_altitude = cleared;
+            self.has_requested = false;
+        }
+        let is_level = (altitude - cleared).abs() < 1.;
+        let is_waiting = aircraft.status == Flight
+            && aircraft.approach.is_none()
+            && aircraft.hold.is_none()
+            && aircraft.pending.is_empty();
+        if is_level && is_waiting {
+            self.level_time += dt;
+        } else {
+            self.level_time = 0.;
+        }
+
+        if !self.checked_in {
+            self.checked_in = true;
+            return match (&aircraft.status, &aircraft.runway) {
+                (Taxi | LinedUp, Some(runway)) => {
+                    self.departure = true;
+                    Some(PilotMessage::ReadyForDeparture {
+                        runway: runway.clone(),
+                        atis,
+                    })
+                }
+                (Flight | GoAround, _) => Some(PilotMessage::CheckIn {
+                    altitude: hundreds(altitude),
+                    cleared: hundreds(cleared),
+                    atis,
+                }),
+                _ => None,
+            };
+        }
+
+        let is_established =
+            |approach| matches!(approach, Some(s) if s >= ApproachState::GlideslopeArmed);
+        if is_established(aircraft.approach) && !is_established(self.approach) {
+            return aircraft
+                .runway
+                .clone()
+                .map(PilotMessage::EstablishedLocalizer);
+        }
+
+        let has_flown_missed_approach = self.status == Some(GoAround) && aircraft.status == Flight;
+        // left on the heading it had when overhead, rather than given another
+        let has_reached_fix = self.direct.is_some()
+            && aircraft.direct.is_none()
+            && aircraft.hold.is_none()
+            && aircraft.approach.is_none()
+            && aircraft.heading.intended() == self.intended_heading;
+        if has_flown_missed_approach || has_reached_fix {
+            return Some(PilotMessage::RequestVectors);
+        }
+
+        if self.level_time >= REQUEST_DELAY && !self.has_requested {
+            self.has_requested = true;
+            if self.departure {
+                return Some(PilotMessage::RequestHigher);
+            } else if altitude > APPROACH_ALTITUDE {
+                return Some(PilotMessage::RequestLower);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::aircraft::test_aircraft;
+
+    /// Messages over `duration` seconds in steps of a second.
+    fn messages(pilot: &mut Pilot, aircraft: &Aircraft, duration: u32) -> Vec<PilotMessage> {
+        (0..duration)
+            .filter_map(|_| pilot.update(aircraft, 1., 'B'))
+            .collect()
+    }
+
+    #[test]
+    fn test_pilot_check_in() {
+        let mut pilot = Pilot::default();
+        let aircraft = test_aircraft(glm::zero(), 0., 6040.);
+        let message = pilot.update(&aircraft, 1., 'B').unwrap();
+        assert_eq!("with you at 6000 feet, information B", message.as_string());
+        // only once
+        assert_eq!(None, pilot.update(&aircraft, 1., 'B'));
+
+        // not until accepted
+        let mut inbound = test_aircraft(glm::zero(), 0., 6040.);
+        inbound.status = AircraftStatus::Inbound;
+        let mut pilot = Pilot::default();
+        assert!(messages(&mut pilot, &inbound, 10).is_empty());
+        inbound.status = AircraftStatus::Flight;
+        assert_eq!(1, messages(&mut pilot, &inbound, 10).len());
+
+        let mut departure = test_aircraft(glm::zero(), 0., 0.);
+        departure.status = AircraftStatus::Taxi;
+        departure.runway = Some("29".into());
+        assert_eq!(
+            vec![PilotMessage::ReadyForDeparture {
+                runway: "29".into(),
+                atis: 'B'
+            }],
+            messages(&mut Pilot::default(), &departure, 10)
+        );
+        assert_eq!(
+            "passing 3000 feet for 5000, information C",
+            PilotMessage::CheckIn {
+                altitude: 3000,
+                cleared: 5000,
+                atis: 'C'
+            }
+            .as_string()
+        );
+    }
+
+    #[test]
+    fn test_pilot_requests() {
+        let mut arrival = test_aircraft(glm::zero(), 0., 8000.);
+        let mut pilot = Pilot::default();
+        assert_eq!(1, messages(&mut pilot, &arrival, 100).len());
+        assert_eq!(
+            vec![PilotMessage::RequestLower],
+            messages(&mut pilot, &arrival, 100)
+        );
+        // asked once at each altitude
+        assert!(messages(&mut pilot, &arrival, 300).is_empty());
+
+        // content to wait for the approach
+        arrival.altitude = crate::aircraft::AircraftParameter::new(4000.);
+        assert!(messages(&mut pilot, &arrival, 300).is_empty());
+
+        let departure = test_aircraft(glm::zero(), 0., 4000.);
+        let mut pilot = Pilot {
+            departure: true,
+            checked_in: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            vec![PilotMessage::RequestHigher],
+            messages(&mut pilot, &departure, 300)
+        );
+    }
+
+    #[test]
+    fn test_pilot_transitions() {
+        let mut aircraft = test_aircraft(glm::zero(), 0., 2000.);
+        aircraft.runway = Some("29".into());
+        let mut pilot = Pilot::default();
+        pilot.update(&aircraft, 1., 'A');
+
+        aircraft.approach = Some(ApproachState::LocalizerCaptured);
+        assert_eq!(None, pilot.update(&aircraft, 1., 'A'));
+        aircraft.approach = Some(ApproachState::GlideslopeArmed);
+        assert_eq!(
+            Some(PilotMessage::EstablishedLocalizer("29".into())),
+            pilot.update(&aircraft, 1., 'A')
+        );
+        aircraft.approach = Some(ApproachState::GlideslopeCaptured);
+        assert_eq!(None, pilot.update(&aircraft, 1., 'A'));
+
+        aircraft.approach = None;
+        aircraft.status = AircraftStatus::GoAround;
+        assert_eq!(None, pilot.update(&aircraft, 1., 'A'));
+        aircraft.status = AircraftStatus::Flight;
+        assert_eq!(
+            Some(PilotMessage::RequestVectors),
+            pilot.update(&aircraft, 1., 'A')
+        );
+
+        // overhead the fix
+        aircraft.direct = Some("KOLOS".into());
+        pilot.update(&aircraft, 1., 'A');
+        aircraft.direct = None;
+        assert_eq!(
+            Some(PilotMessage::RequestVectors),
+            pilot.update(&aircraft, 1., 'A')
+        );
+        // or given a heading instead
+        aircraft.direct = Some("KOLOS".into());
+        pilot.update(&aircraft, 1., 'A');
+        aircraft.command(crate::atc::AtcRequest(
+            crate::command::AtcCommand::ChangeHeading(90),
+        ));
+        assert_eq!(None, pilot.update(&aircraft, 1., 'A'));
+    }
+}
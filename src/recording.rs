@@ -0,0 +1,665 @@
+//! Session recording and replay.
+//!
+//! A session file is a JSON record per line: the scenario and performance
+//! data the session started from, then every ATC command with the step it
+//! was given before,
+//! and snapshots of the traffic every few seconds. As the simulation runs in
+//! fixed steps, replaying the commands on the same steps reproduces the
+//! session, and the snapshots show where a replay diverges from the
+//! recording.
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::aircraft::*;
+use crate::atc::AtcReply;
+use crate::command::AtcCommand;
+use crate::error::impl_from;
+use crate::hold::Hold;
+use crate::pending::PendingInstruction;
+use crate::performance::{PerformanceDatabase, PerformanceError};
+use crate::scenario::{Scenario, ScenarioError};
+use crate::simulation::{CommandError, Simulation, TIMESTEP};
+
+/// seconds between snapshots
+const SNAPSHOT_INTERVAL: f32 = 5.;
+const SNAPSHOT_TICKS: u64 = (SNAPSHOT_INTERVAL / TIMESTEP + 0.5) as u64;
+
+/// slowest and fastest replay, as a multiple of real time
+const MIN_REPLAY_SPEED: f32 = 0.25;
+const MAX_REPLAY_SPEED: f32 = 16.;
+
+#[derive(Debug)]
+pub enum SessionError {
+    Io(std::io::Error),
+    Parse(serde_json::Error),
+    Scenario(ScenarioError),
+    Performance(PerformanceError),
+    /// the first record isn't the scenario
+    MissingScenario,
+}
+
+impl std::fmt::Display for SessionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "failed to access session: {}", e),
+            Self::Parse(e) => write!(f, "failed to parse session: {}", e),
+            Self::Scenario(e) => write!(f, "invalid session scenario: {}", e),
+            Self::Performance(e) => write!(f, "invalid session performance data: {}", e),
+            Self::MissingScenario => write!(f, "session doesn't start with a scenario"),
+        }
+    }
+}
+
+impl_from!(SessionError {
+    Io(std::io::Error),
+    Parse(serde_json::Error),
+    Scenario(ScenarioError),
+    Performance(PerformanceError),
+});
+
+/// Everything about an aircraft that affects how it flies from here on.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct AircraftState {
+    pub callsign: String,
+    pub aircraft_type: String,
+    pub position: [f32; 2],
+    pub heading: f32,
+    pub intended_heading: f32,
+    pub bank: f32,
+    pub altitude: f32,
+    pub intended_altitude: f32,
+    pub vertical_speed: f32,
+    pub vertical: Option<VerticalInstruction>,
+    pub speed: f32,
+    pub intended_speed: f32,
+    pub status: AircraftStatus,
+    pub runway: Option<String>,
+    pub approach: Option<ApproachState>,
+    pub direct: Option<String>,
+    pub hold: Option<Hold>,
+    pub pending: Vec<PendingInstruction>,
+    pub cleared_to_land: bool,
+    pub cleared_to_line_up: bool,
+    pub cleared_for_takeoff: bool,
+}
+
+impl From<&Aircraft> for AircraftState {
+    fn from(aircraft: &Aircraft) -> Self {
+        Self {
+            callsign: aircraft.callsign.coded(),
+            aircraft_type: aircraft.performance.aircraft_type.clone(),
+            position: [aircraft.position.x, aircraft.position.y],
+            heading: aircraft.heading.current,
+            intended_heading: aircraft.heading.intended(),
+            bank: aircraft.heading.bank,
+            altitude: aircraft.altitude.current,
+            intended_altitude: aircraft.altitude.intended(),
+            vertical_speed: aircraft.vertical_speed,
+            vertical: aircraft.vertical.clone(),
+            speed: aircraft.speed.current,
+            intended_speed: aircraft.speed.intended(),
+            status: aircraft.status.clone(),
+            runway: aircraft.runway.clone(),
+            approach: aircraft.approach,
+            direct: aircraft.direct.clone(),
+            hold: aircraft.hold.clone(),
+            pending: aircraft.pending.clone(),
+            cleared_to_land: aircraft.cleared_to_land(),
+            cleared_to_line_up: aircraft.cleared_to_line_up,
+            cleared_for_takeoff: aircraft.cleared_for_takeoff,
+        }
+    }
+}
+
+/// The traffic after a step.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub tick: u64,
+    /// seconds
+    pub time: f32,
+    pub aircraft: Vec<AircraftState>,
+}
+
+impl Snapshot {
+    pub fn new(sim: &Simulation) -> Self {
+        Self {
+            tick: sim.ticks(),
+            time: sim.time(),
+            aircraft: sim
+                .aircraft
+                .read()
+                .unwrap()
+                .iter()
+                .map(AircraftState::from)
+                .collect(),
+        }
+    }
+}
+
+/// A command given before the step `tick`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RecordedCommand {
+    pub tick: u64,
+    /// seconds
+    pub time: f32,
+    pub callsign: String,
+    /// as the controller gave it
+    pub command: AtcCommand,
+    /// as the pilot read it back and flew it, which a replay flies
+    pub readback: AtcCommand,
+}
+
+impl std::fmt::Display for RecordedCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}, {}", self.callsign, self.command.as_string())?;
+        if self.readback != self.command {
+            write!(f, " (read back {})", self.readback.readback())?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "record", rename_all = "lowercase")]
+enum Record {
+    Start {
+        /// scenario file contents
+        scenario: String,
+        /// performance data file contents
+        performance: String,
+    },
+    Command(RecordedCommand),
+    Snapshot(Snapshot),
+}
+
+/// Writes a session as it happens, flushing every record so the session
+/// survives a crash.
+#[derive(Debug)]
+pub struct Recorder<W: Write> {
+    out: W,
+    last_snapshot: u64,
+}
+
+impl Recorder<BufWriter<File>> {
+    pub fn create<P: AsRef<Path>>(
+        path: P,
+        scenario: &str,
+        performance: &str,
+        sim: &Simulation,
+    ) -> Result<Self, SessionError> {
+        Self::new(
+            BufWriter::new(File::create(path)?),
+            scenario,
+            performance,
+            sim,
+        )
+    }
+}
+
+impl<W: Write> Recorder<W> {
+    pub fn new(
+        out: W,
+        scenario: &str,
+        performance: &str,
+        sim: &Simulation,
+    ) -> Result<Self, SessionError> {
+        let mut recorder = Self {
+            out,
+            last_snapshot: sim.ticks(),
+        };
+        recorder.write(&Record::Start {
+            scenario: scenario.to_string(),
+            performance: performance.to_string(),
+        })?;
+        recorder.write(&Record::Snapshot(Snapshot::new(sim)))?;
+        Ok(recorder)
+    }
+
+    /// Record a command given to an aircraft, before the simulation steps
+    /// again.
+    pub fn command(
+        &mut self,
+        sim: &Simulation,
+        callsign: &Callsign,
+        command: &AtcCommand,
+        reply: &AtcReply,
+    ) -> Result<(), SessionError> {
+        self.write(&Record::Command(RecordedCommand {
+            tick: sim.ticks(),
+            time: sim.time(),
+            callsign: callsign.coded(),
+            command: command.clone(),
+            readback: reply.command().clone(),
+        }))
+    }
+
+    /// Take a snapshot if one is due, call after updating the simulation.
+    pub fn update(&mut self, sim: &Simulation) -> Result<(), SessionError> {
+        if sim.ticks() >= self.last_snapshot + SNAPSHOT_TICKS {
+            self.last_snapshot = sim.ticks();
+            self.write(&Record::Snapshot(Snapshot::new(sim)))?;
+        }
+        Ok(())
+    }
+
+    fn write(&mut self, record: &Record) -> Result<(), SessionError> {
+        serde_json::to_writer(&mut self.out, record)?;
+        writeln!(self.out)?;
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
+/// A recorded session, as read back from its file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Session {
+    /// scenario file contents
+    pub scenario: String,
+    /// performance data file contents, so a replay flies the same aircraft
+    pub performance: String,
+    /// ordered by tick
+    pub commands: Vec<RecordedCommand>,
+    /// ordered by tick
+    pub snapshots: Vec<Snapshot>,
+}
+
+impl Session {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, SessionError> {
+        std::fs::read_to_string(path)?.parse()
+    }
+
+    /// The last recorded step.
+    pub fn end(&self) -> u64 {
+        let last_command = self.commands.last().map(|c| c.tick);
+        let last_snapshot = self.snapshots.last().map(|s| s.tick);
+        last_command.max(last_snapshot).unwrap_or(0)
+    }
+}
+
+impl FromStr for Session {
+    type Err = SessionError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut records = s
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(serde_json::from_str::<Record>);
+
+        let (scenario, performance) = match records.next().transpose()? {
+            Some(Record::Start {
+                scenario,
+                performance,
+            }) => (scenario, performance),
+            _ => return Err(SessionError::MissingScenario),
+        };
+
+        let mut session = Self {
+            scenario,
+            performance,
+            commands: Vec::new(),
+            snapshots: Vec::new(),
+        };
+        for record in records {
+            match record? {
+                Record::Start { .. } => return Err(SessionError::MissingScenario),
+                Record::Command(command) => session.commands.push(command),
+                Record::Snapshot(snapshot) => session.snapshots.push(snapshot),
+            }
+        }
+        Ok(session)
+    }
+}
+
+/// Something that happened while replaying a session.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ReplayEvent {
+    Command(RecordedCommand),
+    /// a recorded command the simulation wouldn't give, for the reason given
+    Rejected(RecordedCommand, CommandError),
+    /// the simulation no longer matches the recording, first reported at the
+    /// snapshot taken on this step
+    Diverged(Snapshot),
+}
+
+/// Plays a session back by running its scenario with its performance data,
+/// and giving the recorded commands on the steps they were given.
+#[derive(Debug)]
+pub struct Replay {
+    session: Session,
+    scenario: Scenario,
+    performance: PerformanceDatabase,
+    next_command: usize,
+    next_snapshot: usize,
+    has_diverged: bool,
+    pub is_paused: bool,
+    /// multiple of real time
+    speed: f32,
+    /// real time not yet consumed by a step
+    accumulator: f32,
+}
+
+impl Replay {
+    /// The replay, and the simulation at the start of the session.
+    pub fn new(session: Session) -> Result<(Self, Simulation), SessionError> {
+        let scenario: Scenario = session.scenario.parse()?;
+        let performance: PerformanceDatabase = session.performance.parse()?;
+        let sim = scenario.simulation(&performance)?;
+        let mut replay = Self {
+            session,
+            scenario,
+            performance,
+            next_command: 0,
+            next_snapshot: 0,
+            has_diverged: false,
+            is_paused: false,
+            speed: 1.,
+            accumulator: 0.,
+        };
+        replay.check_snapshots(&sim);
+        Ok((replay, sim))
+    }
+
+    pub fn scenario(&self) -> &Scenario {
+        &self.scenario
+    }
+
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    pub fn change_speed(&mut self, factor: f32) {
+        self.speed = (self.speed * factor).clamp(MIN_REPLAY_SPEED, MAX_REPLAY_SPEED);
+    }
+
+    /// Length of the session in seconds.
+    pub fn duration(&self) -> f32 {
+        self.session.end() as f32 * TIMESTEP
+    }
+
+    pub fn is_finished(&self, sim: &Simulation) -> bool {
+        sim.ticks() >= self.session.end()
+    }
+
+    /// Consume real elapsed time at the replay speed, pausing at the end of
+    /// the session.
+    pub fn update(&mut self, sim: &mut Simulation, dt: f32) -> Vec<ReplayEvent> {
+        let mut events = Vec::new();
+        if self.is_paused {
+            return events;
+        }
+
+        self.accumulator += dt * self.speed;
+        while self.accumulator >= TIMESTEP && !self.is_finished(sim) {
+            events.extend(self.step(sim));
+            self.accumulator -= TIMESTEP;
+        }
+        if self.is_finished(sim) {
+            self.is_paused = true;
+            self.accumulator = 0.;
+        }
+        events
+    }
+
+    /// Give the commands due before the next step, then run it.
+    pub fn step(&mut self, sim: &mut Simulation) -> Vec<ReplayEvent> {
+        let mut events = Vec::new();
+        while let Some(recorded) = self.session.commands.get(self.next_command) {
+            if recorded.tick > sim.ticks() {
+                break;
+            }
+            let result = Callsign::from_string(recorded.callsign.clone())
+                .map(|callsign| sim.command(&callsign, recorded.readback.clone()));
+            events.push(match result {
+                Some(Err(e)) => ReplayEvent::Rejected(recorded.clone(), e),
+                _ => ReplayEvent::Command(recorded.clone()),
+            });
+            self.next_command += 1;
+        }
+
+        sim.tick(TIMESTEP);
+        events.extend(self.check_snapshots(sim));
+        events
+    }
+
+    /// Jump to `time` seconds into the session, starting over from the
+    /// scenario when going back. Events raised on the way are dropped.
+    pub fn seek(&mut self, sim: &mut Simulation, time: f32) -> Result<(), SessionError> {
+        let target = ((time.max(0.) / TIMESTEP).round() as u64).min(self.session.end());
+        if target < sim.ticks() {
+            self.restart(sim)?;
+        }
+        while sim.ticks() < target {
+            self.step(sim);
+        }
+        sim.drain_events();
+        self.accumulator = 0.;
+        Ok(())
+    }
+
+    fn restart(&mut self, sim: &mut Simulation) -> Result<(), SessionError> {
+        // keep sharing the traffic with anything holding on to it
+        let aircraft = sim.aircraft.clone();
+        *sim = self.scenario.simulation(&self.performance)?;
+        let initial = std::mem::take(&mut *sim.aircraft.write().unwrap());
+        *aircraft.write().unwrap() = initial;
+        sim.aircraft = aircraft;
+
+        self.next_command = 0;
+        self.next_snapshot = 0;
+        self.check_snapshots(sim);
+        Ok(())
+    }
+
+    fn check_snapshots(&mut self, sim: &Simulation) -> Option<ReplayEvent> {
+        let mut event = None;
+        while let Some(recorded) = self.session.snapshots.get(self.next_snapshot) {
+            if recorded.tick > sim.ticks() {
+                break;
+            }
+            if recorded.tick == sim.ticks() && !self.has_diverged && *recorded != Snapshot::new(sim)
+            {
+                self.has_diverged = true;
+                event = Some(ReplayEvent::Diverged(recorded.clone()));
+            }
+            self.next_snapshot += 1;
+        }
+        event
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::performance::{test_database, TEST_DATA};
+    use std::sync::Arc;
+
+    const LCPH: &str = include_str!("../scenarios/lcph.toml");
+
+    /// Record fifty seconds with a couple of commands, returning the file
+    /// contents.
+    fn record_session() -> String {
+        let mut sim: Simulation = LCPH
+            .parse::<Scenario>()
+            .unwrap()
+            .simulation(&test_database())
+            .unwrap();
+        let mut recorder = Recorder::new(Vec::new(), LCPH, TEST_DATA, &sim).unwrap();
+
+        // with the altitude misheard
+        let commands = [
+            (10., "CYP2202", AtcCommand::ChangeHeading(180), None),
+            (
+                12.,
+                "FDX261",
+                AtcCommand::ChangeAltitude(3000),
+                Some(AtcCommand::ChangeAltitude(4000)),
+            ),
+            (30., "CYP2202", AtcCommand::ChangeSpeed(180), None),
+        ];
+        for (time, callsign, command, heard) in commands {
+            while sim.time() < time {
+                sim.tick(TIMESTEP);
+                recorder.update(&sim).unwrap();
+            }
+            let callsign = Callsign::from_string(callsign.into()).unwrap();
+            let heard = heard.unwrap_or_else(|| command.clone());
+            let reply = sim.command(&callsign, heard).unwrap();
+            recorder.command(&sim, &callsign, &command, &reply).unwrap();
+        }
+        while sim.time() < 50. {
+            sim.tick(TIMESTEP);
+            recorder.update(&sim).unwrap();
+        }
+
+        String::from_utf8(recorder.out).unwrap()
+    }
+
+    #[test]
+    fn test_session_round_trip() {
+        let session: Session = record_session().parse().unwrap();
+        assert_eq!(LCPH, session.scenario);
+        assert_eq!(TEST_DATA, session.performance);
+        assert_eq!(3, session.commands.len());
+        assert_eq!("FDX261", session.commands[1].callsign);
+        assert_eq!(
+            AtcCommand::ChangeAltitude(3000),
+            session.commands[1].command
+        );
+        assert_eq!(
+            AtcCommand::ChangeAltitude(4000),
+            session.commands[1].readback
+        );
+        assert_eq!(
+            "FDX261, altitude to 3000 feet (read back maintain 4000 feet)",
+            session.commands[1].to_string()
+        );
+        assert_eq!(0, session.snapshots[0].tick);
+        // every five seconds over fifty seconds
+        assert_eq!(11, session.snapshots.len());
+        assert!(session
+            .snapshots
+            .windows(2)
+            .all(|w| w[1].tick - w[0].tick == SNAPSHOT_TICKS));
+        assert_eq!(session.snapshots[10].tick, session.end());
+    }
+
+    #[test]
+    fn test_session_errors() {
+        assert!(matches!(
+            "not json".parse::<Session>(),
+            Err(SessionError::Parse(_))
+        ));
+        assert!(matches!(
+            "".parse::<Session>(),
+            Err(SessionError::MissingScenario)
+        ));
+
+        let log = record_session();
+        let mut session: Session = log.parse().unwrap();
+        session.performance = "[A320]".into();
+        assert!(matches!(
+            Replay::new(session),
+            Err(SessionError::Performance(_))
+        ));
+
+        let without_start: String = log.lines().skip(1).collect::<Vec<_>>().join("\n");
+        assert!(matches!(
+            without_start.parse::<Session>(),
+            Err(SessionError::MissingScenario)
+        ));
+    }
+
+    #[test]
+    fn test_replay_reproduces_session() {
+        let session: Session = record_session().parse().unwrap();
+        let last = session.snapshots.last().unwrap().clone();
+        let (mut replay, mut sim) = Replay::new(session).unwrap();
+
+        let mut commands = 0;
+        while !replay.is_finished(&sim) {
+            for event in replay.step(&mut sim) {
+                match event {
+                    ReplayEvent::Command(_) => commands += 1,
+                    ReplayEvent::Rejected(recorded, e) => {
+                        panic!("rejected {:?}: {}", recorded, e)
+                    }
+                    ReplayEvent::Diverged(snapshot) => {
+                        panic!("diverged at {}", snapshot.time)
+                    }
+                }
+            }
+        }
+        assert_eq!(3, commands);
+        assert_eq!(last, Snapshot::new(&sim));
+    }
+
+    #[test]
+    fn test_replay_detects_divergence() {
+        let mut session: Session = record_session().parse().unwrap();
+        session.commands.remove(0);
+        let (mut replay, mut sim) = Replay::new(session).unwrap();
+
+        let mut diverged = Vec::new();
+        while !replay.is_finished(&sim) {
+            for event in replay.step(&mut sim) {
+                if let ReplayEvent::Diverged(snapshot) = event {
+                    diverged.push(snapshot.time);
+                }
+            }
+        }
+        // reported once, at the first snapshot after the missing heading
+        assert_eq!(1, diverged.len());
+        assert_eq!(15., diverged[0].round());
+    }
+
+    #[test]
+    fn test_replay_seek() {
+        let session: Session = record_session().parse().unwrap();
+        let last = session.snapshots.last().unwrap().clone();
+        let (mut replay, mut sim) = Replay::new(session).unwrap();
+        let aircraft = sim.aircraft.clone();
+
+        replay.seek(&mut sim, 40.).unwrap();
+        assert_eq!(40., sim.time().round());
+        assert_eq!(180., sim.aircraft.read().unwrap()[0].speed.intended());
+
+        // going back starts over, still sharing the traffic
+        replay.seek(&mut sim, 11.).unwrap();
+        assert_eq!(11., sim.time().round());
+        assert!(Arc::ptr_eq(&aircraft, &sim.aircraft));
+        assert_eq!(240., sim.aircraft.read().unwrap()[0].speed.intended());
+
+        // past the end stops at the end
+        replay.seek(&mut sim, 600.).unwrap();
+        assert!(replay.is_finished(&sim));
+        assert_eq!(last, Snapshot::new(&sim));
+    }
+
+    #[test]
+    fn test_replay_speed_and_pause() {
+        let session: Session = record_session().parse().unwrap();
+        let (mut replay, mut sim) = Replay::new(session).unwrap();
+
+        replay.change_speed(4.);
+        replay.update(&mut sim, 1.);
+        assert_eq!(4., sim.time().round());
+
+        replay.is_paused = true;
+        replay.update(&mut sim, 1.);
+        assert_eq!(4., sim.time().round());
+
+        replay.change_speed(100.);
+        assert_eq!(MAX_REPLAY_SPEED, replay.speed());
+
+        // pauses at the end of the session
+        replay.is_paused = false;
+        replay.update(&mut sim, 600.);
+        assert!(replay.is_finished(&sim));
+        assert!(replay.is_paused);
+    }
+}
@@ -0,0 +1,1042 @@
+use std::path::Path;
+use std::str::FromStr;
+
+use serde::Deserialize;
+
+use crate::aircraft::*;
+use crate::airspace::{Airspace, AirspaceClass};
+use crate::error::impl_from;
+use crate::fix::{Fix, FixDatabase};
+use crate::geo::{Cardinal, LatLon, DMS};
+use crate::geom::{polygon_edges, segments_intersect};
+use crate::performance::PerformanceDatabase;
+use crate::sector::{Sector, Unit};
+use crate::simulation::Simulation;
+use crate::terrain::{Terrain, TerrainError};
+use crate::traffic::{Airline, Arrivals, Gate, Traffic};
+use crate::wind::{Wind, WindLayer};
+
+#[derive(Debug)]
+pub enum ScenarioError {
+    Io(std::io::Error),
+    Parse(toml::de::Error),
+    InvalidCallsign(String),
+    InvalidAircraft(String, &'static str),
+    UnknownAircraftType(String),
+    InvalidFix(String, &'static str),
+    InvalidRunway(String, &'static str),
+    InvalidSector(&'static str),
+    InvalidAirspace(String, &'static str),
+    InvalidTraffic(&'static str),
+    Terrain(String, TerrainError),
+}
+
+impl std::fmt::Display for ScenarioError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "failed to read scenario: {}", e),
+            Self::Parse(e) => write!(f, "failed to parse scenario: {}", e),
+            Self::InvalidCallsign(callsign) => write!(f, "invalid callsign: {}", callsign),
+            Self::InvalidAircraft(callsign, reason) => write!(f, "{}: {}", callsign, reason),
+            Self::UnknownAircraftType(aircraft_type) => {
+                write!(f, "unknown aircraft type: {}", aircraft_type)
+            }
+            Self::InvalidFix(name, reason) => write!(f, "{}: {}", name, reason),
+            Self::InvalidRunway(name, reason) => write!(f, "runway {}: {}", name, reason),
+            Self::InvalidSector(reason) => write!(f, "sector: {}", reason),
+            Self::InvalidAirspace(name, reason) => write!(f, "airspace {}: {}", name, reason),
+            Self::InvalidTraffic(reason) => write!(f, "traffic: {}", reason),
+            Self::Terrain(path, e) => write!(f, "{}: {}", path, e),
+        }
+    }
+}
+
+impl_from!(ScenarioError {
+    Io(std::io::Error),
+    Parse(toml::de::Error),
+});
+
+#[derive(Clone, Debug, Deserialize)]
+struct RunwayConfig {
+    name: String,
+    heading: u32,
+    /// meters
+    length: u32,
+    /// meters
+    width: u32,
+    /// feet
+    ils_max_altitude: u32,
+    /// feet
+    #[serde(default = "initial_altitude")]
+    initial_altitude: u32,
+    /// meters east and north of the airport reference point
+    #[serde(default)]
+    offset: [f32; 2],
+    #[serde(default = "enabled")]
+    takeoff: bool,
+    #[serde(default = "enabled")]
+    landing: bool,
+    /// straight ahead to the initial altitude when not published
+    missed_approach: Option<MissedApproachConfig>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct MissedApproachConfig {
+    /// feet
+    altitude: u32,
+    /// heading to turn onto, the runway heading if neither it nor a fix is
+    /// given
+    heading: Option<u32>,
+    /// fix to proceed direct to
+    fix: Option<String>,
+}
+
+impl RunwayConfig {
+    fn missed_approach(&self) -> MissedApproach {
+        match &self.missed_approach {
+            Some(config) => MissedApproach {
+                altitude: config.altitude,
+                route: match (&config.fix, config.heading) {
+                    (Some(fix), _) => MissedApproachRoute::Fix(fix.to_uppercase()),
+                    (None, heading) => {
+                        MissedApproachRoute::Heading(heading.unwrap_or(self.heading))
+                    }
+                },
+            },
+            None => MissedApproach {
+                altitude: self.initial_altitude,
+                route: MissedApproachRoute::Heading(self.heading),
+            },
+        }
+    }
+}
+
+fn enabled() -> bool {
+    true
+}
+
+fn initial_altitude() -> u32 {
+    3000
+}
+
+fn atis() -> char {
+    'A'
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct AirportConfig {
+    icao_code: String,
+    latitude: f64,
+    longitude: f64,
+    /// ATIS information letter
+    #[serde(default = "atis")]
+    atis: char,
+    runways: Vec<RunwayConfig>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct AircraftConfig {
+    /// coded callsign, e.g. "CYP2202"
+    callsign: String,
+    /// spoken airline name, e.g. "Cyprus Airways"
+    airline: String,
+    /// ICAO type designator, e.g. "A320"
+    #[serde(rename = "type")]
+    aircraft_type: String,
+    /// meters east and north of the airport reference point, departures
+    /// start at the holding point of their runway instead
+    position: Option<[f32; 2]>,
+    heading: Option<f32>,
+    /// feet
+    #[serde(default)]
+    altitude: f32,
+    /// knots
+    #[serde(default)]
+    speed: f32,
+    #[serde(default = "flight")]
+    status: AircraftStatus,
+    /// departure runway designator
+    runway: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct FixConfig {
+    name: String,
+    /// degrees, minutes and seconds, e.g. "34 43 05.08 N"
+    latitude: String,
+    /// degrees, minutes and seconds, e.g. "032 29 06.26 E"
+    longitude: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct UnitConfig {
+    /// as given in handoffs, e.g. "TWR"
+    name: String,
+    /// MHz, e.g. "119.9"
+    frequency: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct SectorConfig {
+    /// latitude and longitude of each corner, in degrees, minutes and
+    /// seconds, in order around it
+    vertices: Vec<[String; 2]>,
+    /// feet
+    #[serde(default)]
+    floor: f32,
+    /// feet, unlimited when not given
+    ceiling: Option<f32>,
+    #[serde(default)]
+    units: Vec<UnitConfig>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct AirspaceConfig {
+    name: String,
+    class: AirspaceClass,
+    /// latitude and longitude of each corner, in degrees, minutes and
+    /// seconds, in order around it
+    vertices: Vec<[String; 2]>,
+    /// feet
+    #[serde(default)]
+    floor: f32,
+    /// feet, unlimited when not given
+    ceiling: Option<f32>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct GateConfig {
+    fix: String,
+    /// lowest and highest, in feet
+    altitude: [u32; 2],
+    /// knots
+    speed: f32,
+    heading: f32,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct AirlineConfig {
+    /// ICAO code, e.g. "CYP"
+    code: String,
+    /// spoken name, e.g. "Cyprus Airways"
+    name: String,
+    /// ICAO type designators
+    types: Vec<String>,
+    /// share of the arrivals, relative to the other airlines
+    #[serde(default = "weight")]
+    weight: u32,
+}
+
+fn weight() -> u32 {
+    1
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct TrafficConfig {
+    #[serde(default)]
+    seed: u64,
+    /// arrivals per hour
+    rate: Option<f32>,
+    /// minutes after the start
+    schedule: Option<Vec<f32>>,
+    gates: Vec<GateConfig>,
+    airlines: Vec<AirlineConfig>,
+}
+
+fn flight() -> AircraftStatus {
+    AircraftStatus::Flight
+}
+
+/// Airport layout and initial traffic, as loaded from a scenario file.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Scenario {
+    airport: AirportConfig,
+    #[serde(default)]
+    aircraft: Vec<AircraftConfig>,
+    #[serde(default)]
+    wind: Vec<WindLayer>,
+    #[serde(default)]
+    fixes: Vec<FixConfig>,
+    /// unbounded, with no adjacent units, when not given
+    sector: Option<SectorConfig>,
+    /// control zones, restricted and danger areas
+    #[serde(default)]
+    airspaces: Vec<AirspaceConfig>,
+    /// arrivals after the initial traffic
+    traffic: Option<TrafficConfig>,
+    /// elevation file, an ESRI ASCII grid or an SRTM `.hgt` tile, relative
+    /// to the working directory
+    terrain: Option<String>,
+}
+
+impl Scenario {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ScenarioError> {
+        std::fs::read_to_string(path)?.parse()
+    }
+
+    /// Real world position of the airport, the origin of the game world.
+    pub fn origin(&self) -> LatLon {
+        LatLon::new(self.airport.latitude, self.airport.longitude)
+    }
+
+    pub fn airport(&self) -> Airport {
+        let runways = |f: fn(&RunwayConfig) -> bool| {
+            self.airport
+                .runways
+                .iter()
+                .filter(|r| f(r))
+                .map(|r| Runway {
+                    name: r.name.clone(),
+                    offset: glm::vec2(r.offset[0], r.offset[1]),
+                    heading: r.heading,
+                    length: r.length,
+                    width: r.width,
+                    ils_max_altitude: r.ils_max_altitude,
+                    initial_altitude: r.initial_altitude,
+                    missed_approach: r.missed_approach(),
+                })
+                .collect()
+        };
+
+        Airport {
+            position: glm::zero(),
+            icao_code: self.airport.icao_code.clone(),
+            atis: self.airport.atis.to_ascii_uppercase(),
+            takeoff_runways: runways(|r| r.takeoff),
+            landing_runways: runways(|r| r.landing),
+        }
+    }
+
+    pub fn aircraft(
+        &self,
+        performance: &PerformanceDatabase,
+    ) -> Result<Vec<Aircraft>, ScenarioError> {
+        let airport = self.airport();
+        self.aircraft
+            .iter()
+            .map(|config| {
+                let invalid =
+                    |reason| ScenarioError::InvalidAircraft(config.callsign.clone(), reason);
+
+                let mut callsign = Callsign::from_string(config.callsign.clone())
+                    .ok_or_else(|| ScenarioError::InvalidCallsign(config.callsign.clone()))?;
+                callsign.name = config.airline.clone();
+
+                let performance = performance.get(&config.aircraft_type).ok_or_else(|| {
+                    ScenarioError::UnknownAircraftType(config.aircraft_type.clone())
+                })?;
+
+                let (position, heading) = if config.status == AircraftStatus::Taxi {
+                    let runway = config
+                        .runway
+                        .as_ref()
+                        .ok_or_else(|| invalid("departures need a runway"))?;
+                    let runway = airport
+                        .takeoff_runway(runway)
+                        .ok_or_else(|| invalid("unknown takeoff runway"))?;
+                    let origin = airport.origin(runway);
+                    (runway.holding_point(origin), runway.heading as f32)
+                } else {
+                    let position = config.position.ok_or_else(|| invalid("missing position"))?;
+                    let heading = config.heading.ok_or_else(|| invalid("missing heading"))?;
+                    (glm::vec2(position[0], position[1]), heading)
+                };
+
+                let mut aircraft = Aircraft::new(
+                    callsign,
+                    performance.clone(),
+                    position,
+                    heading,
+                    config.altitude,
+                    config.speed,
+                );
+                aircraft.status = config.status.clone();
+                aircraft.runway = config.runway.clone();
+                Ok(aircraft)
+            })
+            .collect()
+    }
+
+    pub fn wind(&self) -> Wind {
+        Wind::new(self.wind.clone())
+    }
+
+    pub fn fixes(&self) -> Result<FixDatabase, ScenarioError> {
+        let origin = self.origin();
+        let mut fixes = FixDatabase::default();
+        for config in &self.fixes {
+            let invalid = |reason| ScenarioError::InvalidFix(config.name.clone(), reason);
+            let latlon = latlon(&config.latitude, &config.longitude).map_err(invalid)?;
+            let fix = Fix::new(&config.name, latlon, &origin);
+            if !fixes.insert(fix) {
+                return Err(invalid("duplicate fix"));
+            }
+        }
+        Ok(fixes)
+    }
+
+    /// An airspace with its corners projected into the game world.
+    fn airspace(
+        &self,
+        name: &str,
+        class: AirspaceClass,
+        vertices: &[[String; 2]],
+        floor: f32,
+        ceiling: Option<f32>,
+    ) -> Result<Airspace, &'static str> {
+        let origin = self.origin();
+        let boundary = vertices
+            .iter()
+            .map(|[latitude, longitude]| {
+                let position = latlon(latitude, longitude)?.to_game_world(&origin);
+                Ok(glm::vec2(position.x, position.y))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if boundary.len() < 3 {
+            return Err("needs at least three vertices");
+        }
+        // only edges next to each other may meet, at the vertex between them
+        let edges: Vec<[glm::Vec2; 2]> = polygon_edges(&boundary).collect();
+        let is_crossed = (0..edges.len()).any(|i| {
+            (i + 2..edges.len())
+                .filter(|&j| !(i == 0 && j == edges.len() - 1))
+                .any(|j| segments_intersect(&edges[i], &edges[j]))
+        });
+        if is_crossed {
+            return Err("boundary crosses itself");
+        }
+        let ceiling = ceiling.unwrap_or(f32::INFINITY);
+        if floor > ceiling {
+            return Err("ceiling is below the floor");
+        }
+        Ok(Airspace {
+            name: name.to_string(),
+            class,
+            boundary,
+            floor,
+            ceiling,
+        })
+    }
+
+    pub fn sector(&self) -> Result<Sector, ScenarioError> {
+        let config = match &self.sector {
+            Some(config) => config,
+            None => return Ok(Sector::default()),
+        };
+        let airspace = self
+            .airspace(
+                &self.airport.icao_code,
+                AirspaceClass::Sector,
+                &config.vertices,
+                config.floor,
+                config.ceiling,
+            )
+            .map_err(ScenarioError::InvalidSector)?;
+        let mut units: Vec<Unit> = Vec::new();
+        for unit in &config.units {
+            if units.iter().any(|other| other.name == unit.name) {
+                return Err(ScenarioError::InvalidSector("duplicate unit"));
+            }
+            if !matches!(unit.frequency.parse::<f32>(), Ok(frequency) if frequency > 0.) {
+                return Err(ScenarioError::InvalidSector("invalid frequency"));
+            }
+            units.push(Unit::new(&unit.name, &unit.frequency));
+        }
+        Ok(Sector {
+            airspace: Some(airspace),
+            units,
+        })
+    }
+
+    pub fn airspaces(&self) -> Result<Vec<Airspace>, ScenarioError> {
+        let mut airspaces: Vec<Airspace> = Vec::new();
+        for config in &self.airspaces {
+            let invalid = |reason| ScenarioError::InvalidAirspace(config.name.clone(), reason);
+            if config.class == AirspaceClass::Sector {
+                return Err(invalid("the sector is given on its own"));
+            }
+            if airspaces.iter().any(|other| other.name == config.name) {
+                return Err(invalid("duplicate airspace"));
+            }
+            airspaces.push(
+                self.airspace(
+                    &config.name,
+                    config.class,
+                    &config.vertices,
+                    config.floor,
+                    config.ceiling,
+                )
+                .map_err(invalid)?,
+            );
+        }
+        Ok(airspaces)
+    }
+
+    pub fn traffic(
+        &self,
+        performance: &PerformanceDatabase,
+        fixes: &FixDatabase,
+    ) -> Result<Option<Traffic>, ScenarioError> {
+        let config = match &self.traffic {
+            Some(config) => config,
+            None => return Ok(None),
+        };
+        let invalid = |reason| Err(ScenarioError::InvalidTraffic(reason));
+        let arrivals = match (config.rate, &config.schedule) {
+            (Some(rate), None) if rate > 0. => Arrivals::Rate(rate),
+            (Some(_), None) => return invalid("rate must be positive"),
+            (None, Some(schedule)) if schedule.iter().all(|&minutes| minutes >= 0.) => {
+                let mut times: Vec<f32> = schedule.iter().map(|minutes| minutes * 60.).collect();
+                times.sort_by(|a, b| a.total_cmp(b));
+                Arrivals::Schedule(times)
+            }
+            (None, Some(_)) => return invalid("schedule times can't be negative"),
+            (None, None) => return invalid("needs a rate or a schedule"),
+            (Some(_), Some(_)) => return invalid("has both a rate and a schedule"),
+        };
+
+        if config.gates.is_empty() {
+            return invalid("no entry gates");
+        }
+        let mut gates = Vec::new();
+        for gate in &config.gates {
+            let fix = fixes
+                .get(&gate.fix)
+                .ok_or_else(|| ScenarioError::InvalidFix(gate.fix.clone(), "unknown gate fix"))?;
+            let [lowest, highest] = gate.altitude;
+            if lowest > highest {
+                return invalid("altitude band is upside down");
+            }
+            gates.push(Gate {
+                position: fix.position,
+                altitudes: (lowest, highest),
+                speed: gate.speed,
+                heading: gate.heading,
+            });
+        }
+
+        if config.airlines.iter().all(|airline| airline.weight == 0) {
+            return invalid("no airlines");
+        }
+        let mut airlines = Vec::new();
+        for airline in &config.airlines {
+            let code = airline.code.to_uppercase();
+            if code.len() != 3 || !code.chars().all(|c| c.is_ascii_alphabetic()) {
+                return invalid("airline codes are three letters");
+            }
+            if airline.types.is_empty() {
+                return invalid("airline without aircraft types");
+            }
+            let fleet = airline
+                .types
+                .iter()
+                .map(|aircraft_type| {
+                    performance
+                        .get(aircraft_type)
+                        .cloned()
+                        .ok_or_else(|| ScenarioError::UnknownAircraftType(aircraft_type.clone()))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            airlines.push(Airline {
+                code,
+                name: airline.name.clone(),
+                fleet,
+                weight: airline.weight,
+            });
+        }
+        Ok(Some(Traffic::new(gates, airlines, arrivals, config.seed)))
+    }
+
+    pub fn terrain(&self) -> Result<Option<Terrain>, ScenarioError> {
+        self.terrain
+            .as_ref()
+            .map(|path| {
+                Terrain::load(path, &self.origin())
+                    .map_err(|e| ScenarioError::Terrain(path.clone(), e))
+            })
+            .transpose()
+    }
+
+    /// Check the missed approaches of the runways against the fixes.
+    fn check_runways(&self, fixes: &FixDatabase) -> Result<(), ScenarioError> {
+        for runway in &self.airport.runways {
+            let invalid = |reason| ScenarioError::InvalidRunway(runway.name.clone(), reason);
+            if let Some(config) = &runway.missed_approach {
+                if config.heading.is_some() && config.fix.is_some() {
+                    return Err(invalid("missed approach has both a heading and a fix"));
+                }
+                if matches!(&config.fix, Some(fix) if fixes.get(fix).is_none()) {
+                    return Err(invalid("unknown missed approach fix"));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn simulation(
+        &self,
+        performance: &PerformanceDatabase,
+    ) -> Result<Simulation, ScenarioError> {
+        let mut sim = Simulation::new(self.airport(), self.aircraft(performance)?);
+        sim.wind = self.wind();
+        sim.fixes = self.fixes()?;
+        sim.sector = self.sector()?;
+        sim.airspaces = self.airspaces()?;
+        sim.terrain = self.terrain()?;
+        sim.traffic = self.traffic(performance, &sim.fixes)?;
+        self.check_runways(&sim.fixes)?;
+        Ok(sim)
+    }
+}
+
+/// A position given in degrees, minutes and seconds.
+fn latlon(latitude: &str, longitude: &str) -> Result<LatLon, &'static str> {
+    let coordinate = |s: &str, cardinals: [Cardinal; 2]| {
+        s.parse::<DMS>()
+            .ok()
+            .filter(|dms| matches!(dms.cardinal, Some(c) if cardinals.contains(&c)))
+    };
+    let latitude =
+        coordinate(latitude, [Cardinal::North, Cardinal::South]).ok_or("invalid latitude")?;
+    let longitude =
+        coordinate(longitude, [Cardinal::East, Cardinal::West]).ok_or("invalid longitude")?;
+    Ok(LatLon::from_dms(latitude, longitude))
+}
+
+impl FromStr for Scenario {
+    type Err = ScenarioError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(toml::from_str(s)?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::performance::test_database;
+
+    const LCPH: &str = include_str!("../scenarios/lcph.toml");
+    const LCLK: &str = include_str!("../scenarios/lclk.toml");
+
+    #[test]
+    fn test_scenario_airport() {
+        let scenario: Scenario = LCPH.parse().unwrap();
+        let airport = scenario.airport();
+        assert_eq!("LCPH", airport.icao_code);
+        assert_eq!('C', airport.atis);
+        assert_eq!(1, airport.landing_runways.len());
+        assert_eq!(1, airport.takeoff_runways.len());
+
+        let runway = &airport.landing_runways[0];
+        assert_eq!("29", runway.name);
+        assert_eq!(285, runway.heading);
+        assert_eq!(2700, runway.length);
+        assert_eq!(45, runway.width);
+        assert_eq!(2000, runway.ils_max_altitude);
+        assert_eq!(glm::vec2(0.0, 0.0), runway.offset);
+        assert_eq!(
+            MissedApproach {
+                altitude: 4000,
+                route: MissedApproachRoute::Fix("TOMBA".into()),
+            },
+            runway.missed_approach
+        );
+
+        assert_eq!(34.718, (scenario.origin().latitude() * 1000.).round() / 1000.);
+        assert_eq!(32.485, (scenario.origin().longitude() * 1000.).round() / 1000.);
+    }
+
+    #[test]
+    fn test_scenario_aircraft() {
+        let scenario: Scenario = LCPH.parse().unwrap();
+        let aircraft = scenario.aircraft(&test_database()).unwrap();
+        assert_eq!(5, aircraft.len());
+
+        let fedex = &aircraft[1];
+        assert_eq!("FDX261", fedex.callsign.coded());
+        assert_eq!("Fedex 261", fedex.callsign.spoken());
+        assert_eq!(glm::vec2(14000.0, -2000.0), fedex.position);
+        assert_eq!(245.0, fedex.heading.current);
+        assert_eq!(1000.0, fedex.altitude.current);
+        assert_eq!(180.0, fedex.speed.current);
+        assert_eq!(AircraftStatus::Flight, fedex.status);
+        assert_eq!("B752", fedex.performance.aircraft_type);
+        assert_eq!(None, fedex.runway);
+
+        let departure = &aircraft[3];
+        let airport = scenario.airport();
+        let runway = airport.takeoff_runway("29").unwrap();
+        assert_eq!("CYP326", departure.callsign.coded());
+        assert_eq!(AircraftStatus::Taxi, departure.status);
+        assert_eq!(Some("29".into()), departure.runway);
+        assert_eq!(
+            runway.holding_point(airport.origin(runway)),
+            departure.position
+        );
+        assert_eq!(0.0, departure.altitude.current);
+        assert_eq!(0.0, departure.speed.current);
+        assert_eq!(3000, runway.initial_altitude);
+
+        assert_eq!(AircraftStatus::Inbound, aircraft[4].status);
+    }
+
+    #[test]
+    fn test_scenario_traffic() {
+        let mut scenario: Scenario = LCPH.parse().unwrap();
+        let traffic = scenario.traffic.as_mut().unwrap();
+        traffic.rate = None;
+        traffic.schedule = Some(vec![0.5]);
+        let mut sim = scenario.simulation(&test_database()).unwrap();
+        for _ in 0..(31. / crate::simulation::TIMESTEP) as u32 {
+            sim.tick(crate::simulation::TIMESTEP);
+        }
+        let aircraft = sim.aircraft.read().unwrap();
+        assert_eq!(6, aircraft.len());
+        let arrival = &aircraft[5];
+        assert!(["CYP", "AEE", "RYR"].contains(&arrival.callsign.code.as_str()));
+        // entered outside the sector, through either gate
+        assert_eq!(AircraftStatus::Inbound, arrival.status);
+        assert!(arrival.altitude.current >= 7000. && arrival.altitude.current <= 13000.);
+
+        let scenario: Scenario = LCLK.parse().unwrap();
+        assert!(scenario
+            .simulation(&test_database())
+            .unwrap()
+            .traffic
+            .is_none());
+    }
+
+    #[test]
+    fn test_scenario_sector() {
+        let scenario: Scenario = LCPH.parse().unwrap();
+        let sector = scenario.sector().unwrap();
+        let airspace = sector.airspace.as_ref().unwrap();
+        assert_eq!("LCPH", airspace.name);
+        assert_eq!(7, airspace.boundary.len());
+        assert!(sector.contains(&glm::zero(), 0.));
+        // entry gates outside it, other fixes inside
+        let fixes = scenario.fixes().unwrap();
+        let position = |name| fixes.get(name).unwrap().position;
+        assert!(!sector.contains(&position("NIKAS"), 10000.));
+        assert!(!sector.contains(&position("DOSER"), 8000.));
+        assert!(sector.contains(&position("KOLOS"), 10000.));
+        assert!(sector.contains(&position("TOMBA"), 4000.));
+        assert!(!sector.contains(&position("TOMBA"), 25000.));
+        assert_eq!(3, sector.units.len());
+        assert!(sector.unit("DEP").unwrap().is_frequency("120.2"));
+
+        // unbounded when not given
+        let scenario: Scenario = LCLK.parse().unwrap();
+        assert_eq!(Sector::default(), scenario.sector().unwrap());
+    }
+
+    #[test]
+    fn test_scenario_airspaces() {
+        let scenario: Scenario = LCPH.parse().unwrap();
+        let airspaces = scenario.airspaces().unwrap();
+        let classes: Vec<AirspaceClass> = airspaces.iter().map(|a| a.class).collect();
+        assert_eq!(
+            vec![
+                AirspaceClass::Ctr,
+                AirspaceClass::Restricted,
+                AirspaceClass::Danger
+            ],
+            classes
+        );
+        let ctr = &airspaces[0];
+        assert_eq!("PAPHOS CTR", ctr.name);
+        assert!(ctr.contains(&glm::zero(), 0.));
+        assert!(!ctr.contains(&glm::zero(), 4000.));
+        assert_eq!(f32::INFINITY, airspaces[2].ceiling);
+
+        let scenario: Scenario = LCLK.parse().unwrap();
+        assert!(scenario.airspaces().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_scenario_terrain() {
+        let scenario: Scenario = LCPH.parse().unwrap();
+        let terrain = scenario.terrain().unwrap().unwrap();
+        let fixes = scenario.fixes().unwrap();
+        // the coast, the hills inland and the sea
+        assert!(terrain.elevation(&glm::zero()) < 100.);
+        assert!(terrain.elevation(&fixes.get("KOLOS").unwrap().position) > 3000.);
+        assert_eq!(0., terrain.elevation(&fixes.get("TOMBA").unwrap().position));
+
+        let mut scenario: Scenario = LCPH.parse().unwrap();
+        scenario.terrain = Some("data/terrain/missing.asc".into());
+        assert!(matches!(
+            scenario.simulation(&test_database()),
+            Err(ScenarioError::Terrain(_, TerrainError::Io(_)))
+        ));
+
+        let scenario: Scenario = LCLK.parse().unwrap();
+        assert!(scenario.terrain().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_scenario_wind() {
+        let scenario: Scenario = LCPH.parse().unwrap();
+        let wind = scenario.wind();
+        assert_eq!(2, wind.layers.len());
+        assert_eq!(270, wind.layer(1000.).unwrap().direction);
+        assert_eq!(25, wind.layer(5000.).unwrap().speed);
+
+        let sim = scenario.simulation(&test_database()).unwrap();
+        assert_eq!(wind, sim.wind);
+
+        // calm without any layers
+        let scenario: Scenario = LCLK.parse().unwrap();
+        assert!(scenario.wind().layers.is_empty());
+    }
+
+    #[test]
+    fn test_scenario_fixes() {
+        let scenario: Scenario = LCPH.parse().unwrap();
+        let fixes = scenario.fixes().unwrap();
+        assert_eq!(6, fixes.iter().count());
+
+        // next to the airport
+        let pha = fixes.get("PHA").unwrap();
+        assert!(glm::length(&pha.position) < 1000.);
+
+        // east south east, about 29km away
+        let alsus = fixes.get("alsus").unwrap();
+        assert!(alsus.position.x > 0. && alsus.position.y < 0.);
+        assert_eq!(29., (glm::length(&alsus.position) / 1000.).round());
+
+        let sim = scenario.simulation(&test_database()).unwrap();
+        assert!(sim.fixes.get("KOLOS").is_some());
+
+        let scenario: Scenario = LCLK.parse().unwrap();
+        assert_eq!(0, scenario.fixes().unwrap().iter().count());
+    }
+
+    #[test]
+    fn test_scenario_other_airport() {
+        let scenario: Scenario = LCLK.parse().unwrap();
+        let sim = scenario.simulation(&test_database()).unwrap();
+        assert_eq!("LCLK", sim.airport.icao_code);
+        assert_eq!('A', sim.airport.atis);
+        assert_eq!("22", sim.airport.landing_runways[0].name);
+    }
+
+    #[test]
+    fn test_scenario_runway_usage() {
+        let scenario: Scenario = r#"
+            [airport]
+            icao_code = "TEST"
+            latitude = 0.0
+            longitude = 0.0
+
+            [[airport.runways]]
+            name = "09"
+            heading = 90
+            length = 2000
+            width = 45
+            ils_max_altitude = 2000
+            landing = false
+
+            [[airport.runways]]
+            name = "27"
+            heading = 270
+            length = 2000
+            width = 45
+            ils_max_altitude = 2000
+            offset = [0.0, 500.0]
+            takeoff = false
+        "#
+        .parse()
+        .unwrap();
+        let airport = scenario.airport();
+        assert_eq!("09", airport.takeoff_runways[0].name);
+        assert_eq!("27", airport.landing_runways[0].name);
+        assert_eq!(glm::vec2(0.0, 500.0), airport.landing_runways[0].offset);
+        assert!(scenario.aircraft(&test_database()).unwrap().is_empty());
+
+        // straight ahead to the initial altitude when not published
+        assert_eq!(
+            MissedApproach {
+                altitude: 3000,
+                route: MissedApproachRoute::Heading(270),
+            },
+            airport.landing_runways[0].missed_approach
+        );
+    }
+
+    #[test]
+    fn test_scenario_errors() {
+        assert!(matches!(
+            "[airport]".parse::<Scenario>(),
+            Err(ScenarioError::Parse(_))
+        ));
+        assert!(matches!(
+            Scenario::load("does/not/exist.toml"),
+            Err(ScenarioError::Io(_))
+        ));
+
+        let mut scenario: Scenario = LCPH.parse().unwrap();
+        scenario.aircraft[0].callsign = "CY".into();
+        assert!(matches!(
+            scenario.aircraft(&test_database()),
+            Err(ScenarioError::InvalidCallsign(_))
+        ));
+
+        let mut scenario: Scenario = LCPH.parse().unwrap();
+        scenario.aircraft[0].position = None;
+        assert!(matches!(
+            scenario.aircraft(&test_database()),
+            Err(ScenarioError::InvalidAircraft(_, "missing position"))
+        ));
+
+        let mut scenario: Scenario = LCPH.parse().unwrap();
+        scenario.aircraft[3].runway = Some("11".into());
+        assert!(matches!(
+            scenario.aircraft(&test_database()),
+            Err(ScenarioError::InvalidAircraft(_, "unknown takeoff runway"))
+        ));
+
+        let mut scenario: Scenario = LCPH.parse().unwrap();
+        scenario.aircraft[1].aircraft_type = "C172".into();
+        assert!(matches!(
+            scenario.aircraft(&test_database()),
+            Err(ScenarioError::UnknownAircraftType(t)) if t == "C172"
+        ));
+
+        let mut scenario: Scenario = LCPH.parse().unwrap();
+        scenario.fixes[0].latitude = "032 28 56.00 E".into();
+        assert!(matches!(
+            scenario.fixes(),
+            Err(ScenarioError::InvalidFix(name, "invalid latitude")) if name == "PHA"
+        ));
+
+        let mut scenario: Scenario = LCPH.parse().unwrap();
+        scenario.fixes[1].longitude = "32.78".into();
+        assert!(matches!(
+            scenario.fixes(),
+            Err(ScenarioError::InvalidFix(_, "invalid longitude"))
+        ));
+
+        let mut scenario: Scenario = LCPH.parse().unwrap();
+        scenario.fixes[2].name = "pha".into();
+        assert!(matches!(
+            scenario.simulation(&test_database()),
+            Err(ScenarioError::InvalidFix(_, "duplicate fix"))
+        ));
+
+        let mut scenario: Scenario = LCPH.parse().unwrap();
+        scenario.fixes.pop();
+        assert!(matches!(
+            scenario.simulation(&test_database()),
+            Err(ScenarioError::InvalidRunway(name, "unknown missed approach fix")) if name == "29"
+        ));
+
+        let mut scenario: Scenario = LCPH.parse().unwrap();
+        scenario.airport.runways[0]
+            .missed_approach
+            .as_mut()
+            .unwrap()
+            .heading = Some(285);
+        assert!(matches!(
+            scenario.simulation(&test_database()),
+            Err(ScenarioError::InvalidRunway(
+                _,
+                "missed approach has both a heading and a fix"
+            ))
+        ));
+
+        let traffic_error = |f: fn(&mut TrafficConfig)| {
+            let mut scenario: Scenario = LCPH.parse().unwrap();
+            f(scenario.traffic.as_mut().unwrap());
+            scenario.simulation(&test_database()).unwrap_err().to_string()
+        };
+        assert_eq!(
+            "traffic: has both a rate and a schedule",
+            traffic_error(|traffic| traffic.schedule = Some(vec![1.]))
+        );
+        assert_eq!(
+            "traffic: needs a rate or a schedule",
+            traffic_error(|traffic| traffic.rate = None)
+        );
+        assert_eq!(
+            "traffic: rate must be positive",
+            traffic_error(|traffic| traffic.rate = Some(0.))
+        );
+        assert_eq!(
+            "NIKOS: unknown gate fix",
+            traffic_error(|traffic| traffic.gates[0].fix = "NIKOS".into())
+        );
+        assert_eq!(
+            "traffic: altitude band is upside down",
+            traffic_error(|traffic| traffic.gates[1].altitude = [9000, 7000])
+        );
+        assert_eq!(
+            "traffic: airline codes are three letters",
+            traffic_error(|traffic| traffic.airlines[0].code = "CY".into())
+        );
+        assert_eq!(
+            "unknown aircraft type: B777",
+            traffic_error(|traffic| traffic.airlines[2].types.push("B777".into()))
+        );
+        assert_eq!(
+            "traffic: no airlines",
+            traffic_error(|traffic| traffic.airlines.clear())
+        );
+
+        let mut scenario: Scenario = LCPH.parse().unwrap();
+        scenario.sector.as_mut().unwrap().vertices.truncate(2);
+        assert!(matches!(
+            scenario.simulation(&test_database()),
+            Err(ScenarioError::InvalidSector(
+                "needs at least three vertices"
+            ))
+        ));
+
+        let mut scenario: Scenario = LCPH.parse().unwrap();
+        scenario.sector.as_mut().unwrap().vertices.swap(1, 2);
+        assert!(matches!(
+            scenario.sector(),
+            Err(ScenarioError::InvalidSector("boundary crosses itself"))
+        ));
+
+        let mut scenario: Scenario = LCPH.parse().unwrap();
+        scenario.sector.as_mut().unwrap().vertices[0][1] = "032 15 00 N".into();
+        assert!(matches!(
+            scenario.sector(),
+            Err(ScenarioError::InvalidSector("invalid longitude"))
+        ));
+
+        let airspace_error = |f: fn(&mut AirspaceConfig)| {
+            let mut scenario: Scenario = LCPH.parse().unwrap();
+            f(&mut scenario.airspaces[1]);
+            scenario.simulation(&test_database()).unwrap_err().to_string()
+        };
+        assert_eq!(
+            "airspace LCR13: ceiling is below the floor",
+            airspace_error(|airspace| airspace.floor = 9000.)
+        );
+        assert_eq!(
+            "airspace LCR13: the sector is given on its own",
+            airspace_error(|airspace| airspace.class = AirspaceClass::Sector)
+        );
+        assert_eq!(
+            "airspace PAPHOS CTR: duplicate airspace",
+            airspace_error(|airspace| airspace.name = "PAPHOS CTR".into())
+        );
+
+        let mut scenario: Scenario = LCPH.parse().unwrap();
+        scenario.sector.as_mut().unwrap().units[1].name = "TWR".into();
+        assert!(matches!(
+            scenario.sector(),
+            Err(ScenarioError::InvalidSector("duplicate unit"))
+        ));
+
+        let mut scenario: Scenario = LCPH.parse().unwrap();
+        scenario.sector.as_mut().unwrap().units[0].frequency = "tower".into();
+        assert!(matches!(
+            scenario.sector(),
+            Err(ScenarioError::InvalidSector("invalid frequency"))
+        ));
+    }
+}
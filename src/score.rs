@@ -0,0 +1,378 @@
+//! Points for the controller, kept beside the simulation from what happens
+//! in each of its steps.
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde::Serialize;
+
+use crate::aircraft::{Aircraft, AircraftStatus, Callsign};
+use crate::simulation::{GoAroundReason, MissedHandoffReason, SimEvent};
+use crate::units;
+
+/// times the direct distance an arrival may be flown before its track
+/// miles count against the controller
+const TRACK_MILES_FACTOR: f32 = 1.5;
+/// nautical miles allowed on top, for joining the approach
+const TRACK_MILES_ALLOWANCE: f32 = 5.;
+
+/// Something scored for or against.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Item {
+    Landing,
+    /// handed off to another unit, or accepted from one in time
+    Handoff,
+    SeparationLoss,
+    /// left the sector without a handoff
+    AirspaceExit,
+    /// entered a restricted area
+    Infringement,
+    /// flew into the terrain
+    Crash,
+    /// reached the runway without a landing clearance, and went around
+    LandingWithoutClearance,
+    GoAround,
+    /// each nautical mile flown beyond the allowance, on landing
+    TrackMile,
+}
+
+impl Item {
+    /// Points for each.
+    pub fn points(self) -> i32 {
+        match self {
+            Item::Landing => 100,
+            Item::Handoff => 50,
+            Item::SeparationLoss => -200,
+            Item::AirspaceExit => -100,
+            Item::Infringement => -100,
+            Item::Crash => -1000,
+            Item::LandingWithoutClearance => -100,
+            Item::GoAround => -50,
+            Item::TrackMile => -2,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Item::Landing => "landings",
+            Item::Handoff => "handoffs",
+            Item::SeparationLoss => "separation losses",
+            Item::AirspaceExit => "airspace exits",
+            Item::Infringement => "restricted area infringements",
+            Item::Crash => "crashes",
+            Item::LandingWithoutClearance => "landings without clearance",
+            Item::GoAround => "go-arounds",
+            Item::TrackMile => "excess track miles",
+        }
+    }
+}
+
+/// What is remembered of an aircraft since the last step.
+#[derive(Clone, Debug)]
+struct Track {
+    status: AircraftStatus,
+    position: glm::Vec2,
+    /// where it came under our control in the air
+    entry: Option<glm::Vec2>,
+    /// where it was handed off, to the tower for an arrival
+    exit: Option<glm::Vec2>,
+    /// meters flown under our control
+    flown: f32,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Score {
+    counts: BTreeMap<Item, u32>,
+    /// by coded callsign
+    tracks: HashMap<String, Track>,
+}
+
+impl Score {
+    /// Score a step that left `aircraft` and raised `events`.
+    pub fn update(&mut self, aircraft: &[Aircraft], events: &[SimEvent]) {
+        let is_ours = |callsign: &Callsign| {
+            aircraft
+                .iter()
+                .any(|aircraft| aircraft.callsign == *callsign && aircraft.is_under_control())
+        };
+        for event in events {
+            match event {
+                // not between another unit's traffic
+                SimEvent::ConflictAlert(conflict)
+                    if conflict.is_loss
+                        && (is_ours(&conflict.callsigns.0) || is_ours(&conflict.callsigns.1)) =>
+                {
+                    self.add(Item::SeparationLoss, 1)
+                }
+                SimEvent::MissedHandoff(_, MissedHandoffReason::LeftSector) => {
+                    self.add(Item::AirspaceExit, 1)
+                }
+                SimEvent::Infringement(..) => self.add(Item::Infringement, 1),
+                SimEvent::Crashed(_) => self.add(Item::Crash, 1),
+                SimEvent::GoAround(_, GoAroundReason::NoLandingClearance) => {
+                    self.add(Item::GoAround, 1);
+                    self.add(Item::LandingWithoutClearance, 1);
+                }
+                SimEvent::GoAround(..) => self.add(Item::GoAround, 1),
+                SimEvent::Landed(callsign) => {
+                    self.add(Item::Landing, 1);
+                    if let Some(track) = self.tracks.get(&callsign.coded()) {
+                        self.add(Item::TrackMile, track.excess_miles());
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let missed = |aircraft: &Aircraft| {
+            events.iter().any(
+                |event| matches!(event, SimEvent::MissedHandoff(callsign, _) if *callsign == aircraft.callsign),
+            )
+        };
+        let mut tracks = HashMap::new();
+        for aircraft in aircraft {
+            let callsign = aircraft.callsign.coded();
+            let mut track = self.tracks.remove(&callsign).unwrap_or(Track {
+                status: aircraft.status.clone(),
+                position: aircraft.position,
+                entry: None,
+                exit: None,
+                flown: 0.,
+            });
+            let is_handed_off = track.status != AircraftStatus::HandedOff
+                && aircraft.status == AircraftStatus::HandedOff;
+            let is_accepted =
+                track.status == AircraftStatus::Inbound && aircraft.is_under_control();
+            if (is_handed_off || is_accepted) && !missed(aircraft) {
+                self.add(Item::Handoff, 1);
+            }
+            if is_handed_off {
+                track.exit = Some(aircraft.position);
+            }
+
+            if aircraft.is_under_control() && !aircraft.is_grounded() {
+                track.entry.get_or_insert(aircraft.position);
+                track.flown += glm::distance(&track.position, &aircraft.position);
+            }
+            track.status = aircraft.status.clone();
+            track.position = aircraft.position;
+            tracks.insert(callsign, track);
+        }
+        self.tracks = tracks;
+    }
+
+    fn add(&mut self, item: Item, count: u32) {
+        if count > 0 {
+            *self.counts.entry(item).or_default() += count;
+        }
+    }
+
+    pub fn total(&self) -> i32 {
+        self.counts
+            .iter()
+            .map(|(item, count)| item.points() * *count as i32)
+            .sum()
+    }
+
+    /// Each item scored so far, how many times and the points for them.
+    pub fn breakdown(&self) -> Vec<(Item, u32, i32)> {
+        self.counts
+            .iter()
+            .map(|(item, count)| (*item, *count, item.points() * *count as i32))
+            .collect()
+    }
+
+    /// The score at `time` seconds into the session, for comparing
+    /// sessions.
+    pub fn report(&self, time: f32) -> Report {
+        Report {
+            time,
+            total: self.total(),
+            items: self
+                .breakdown()
+                .into_iter()
+                .map(|(item, count, points)| ReportItem {
+                    item: item.name(),
+                    count,
+                    points,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl Track {
+    /// Nautical miles flown beyond the allowance for the direct distance
+    /// from where it came under our control to where it left it, to the
+    /// nearest mile.
+    fn excess_miles(&self) -> u32 {
+        let meters_per_mile = units::NM_to_KM as f32 * 1000.;
+        let end = self.exit.unwrap_or(self.position);
+        let direct = self.entry.map_or(0., |entry| glm::distance(&entry, &end));
+        let allowed = direct * TRACK_MILES_FACTOR + TRACK_MILES_ALLOWANCE * meters_per_mile;
+        ((self.flown - allowed) / meters_per_mile).max(0.).round() as u32
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Report {
+    /// seconds
+    pub time: f32,
+    pub total: i32,
+    pub items: Vec<ReportItem>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReportItem {
+    pub item: &'static str,
+    pub count: u32,
+    pub points: i32,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::aircraft::test_aircraft;
+    use crate::stca::Conflict;
+
+    const NM: f32 = units::NM_to_KM as f32 * 1000.;
+
+    fn callsign(s: &str) -> Callsign {
+        Callsign::from_string(s.into()).unwrap()
+    }
+
+    #[test]
+    fn test_score_events() {
+        let mut score = Score::default();
+        let conflict = |is_loss| Conflict {
+            callsigns: (callsign("CYP2202"), callsign("FDX261")),
+            time_to_cpa: 0.,
+            cpa_distance: 0.,
+            vertical_distance: 0.,
+            is_loss,
+        };
+        let mut inbound = test_aircraft(glm::zero(), 270., 3000.);
+        inbound.callsign = callsign("FDX261");
+        inbound.status = AircraftStatus::Inbound;
+        score.update(
+            &[test_aircraft(glm::zero(), 270., 3000.), inbound.clone()],
+            &[
+                SimEvent::ConflictAlert(conflict(false)),
+                SimEvent::ConflictAlert(conflict(true)),
+                SimEvent::GoAround(callsign("CYP2202"), GoAroundReason::NoLandingClearance),
+                SimEvent::GoAround(callsign("FDX261"), GoAroundReason::RunwayOccupied),
+                SimEvent::GoAround(callsign("FDX261"), GoAroundReason::Instructed),
+                SimEvent::MissedHandoff(callsign("TRA1112"), MissedHandoffReason::LeftSector),
+                SimEvent::Infringement(callsign("TRA1112"), "LCR13".into()),
+            ],
+        );
+        assert_eq!(
+            vec![
+                (Item::SeparationLoss, 1, -200),
+                (Item::AirspaceExit, 1, -100),
+                (Item::Infringement, 1, -100),
+                (Item::LandingWithoutClearance, 1, -100),
+                (Item::GoAround, 3, -150),
+            ],
+            score.breakdown()
+        );
+        assert_eq!(-650, score.total());
+
+        let report = serde_json::to_value(score.report(90.)).unwrap();
+        assert_eq!(-650, report["total"]);
+        assert_eq!("go-arounds", report["items"][4]["item"]);
+
+        // not when neither is ours
+        let mut score = Score::default();
+        let mut other = inbound.clone();
+        other.callsign = callsign("CYP2202");
+        score.update(
+            &[other, inbound],
+            &[SimEvent::ConflictAlert(conflict(true))],
+        );
+        assert_eq!(0, score.total());
+    }
+
+    #[test]
+    fn test_score_handoffs() {
+        let mut score = Score::default();
+        let mut inbound = test_aircraft(glm::zero(), 270., 3000.);
+        inbound.status = AircraftStatus::Inbound;
+        let mut outbound = test_aircraft(glm::zero(), 270., 3000.);
+        outbound.callsign = callsign("FDX261");
+        score.update(&[inbound.clone(), outbound.clone()], &[]);
+        assert_eq!(0, score.total());
+
+        inbound.status = AircraftStatus::Flight;
+        outbound.status = AircraftStatus::HandedOff;
+        score.update(&[inbound.clone(), outbound.clone()], &[]);
+        assert_eq!(vec![(Item::Handoff, 2, 100)], score.breakdown());
+
+        // not when the boundary was crossed first
+        let mut late = test_aircraft(glm::zero(), 270., 3000.);
+        late.callsign = callsign("TRA1112");
+        late.status = AircraftStatus::Inbound;
+        score.update(&[late.clone()], &[]);
+        late.status = AircraftStatus::Flight;
+        score.update(
+            &[late],
+            &[SimEvent::MissedHandoff(
+                callsign("TRA1112"),
+                MissedHandoffReason::NotAccepted,
+            )],
+        );
+        assert_eq!(100, score.total());
+    }
+
+    #[test]
+    fn test_score_track_miles() {
+        let fly = |legs: &[glm::Vec2]| {
+            let mut score = Score::default();
+            for position in legs {
+                score.update(&[test_aircraft(*position, 270., 3000.)], &[]);
+            }
+            score.update(&[], &[SimEvent::Landed(callsign("CYP2202"))]);
+            score.breakdown()
+        };
+        // 20nm direct
+        assert_eq!(
+            vec![(Item::Landing, 1, 100)],
+            fly(&[glm::vec2(20. * NM, 0.), glm::zero()])
+        );
+        // 20nm out and 20nm back, for 10nm direct
+        assert_eq!(
+            vec![(Item::Landing, 1, 100), (Item::TrackMile, 30, -60)],
+            fly(&[
+                glm::vec2(10. * NM, 0.),
+                glm::vec2(30. * NM, 0.),
+                glm::vec2(10. * NM, 0.),
+                glm::zero(),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_score_track_miles_to_tower() {
+        let mut score = Score::default();
+        let mut arrival = test_aircraft(glm::zero(), 270., 3000.);
+        // 20nm out and 20nm back, for no distance direct
+        for x in [10., 30., 10.] {
+            arrival.position = glm::vec2(x * NM, 0.);
+            score.update(&[arrival.clone()], &[]);
+        }
+        arrival.status = AircraftStatus::HandedOff;
+        score.update(&[arrival.clone()], &[]);
+
+        // the tower's miles are not ours
+        arrival.position = glm::zero();
+        score.update(&[arrival], &[]);
+        score.update(&[], &[SimEvent::Landed(callsign("CYP2202"))]);
+        assert_eq!(
+            vec![
+                (Item::Landing, 1, 100),
+                (Item::Handoff, 1, 50),
+                (Item::TrackMile, 35, -70)
+            ],
+            score.breakdown()
+        );
+    }
+}
@@ -0,0 +1,106 @@
+//! The airspace under our control, and the units around it that traffic is
+//! handed off to and accepted from.
+
+use crate::airspace::Airspace;
+
+/// An adjacent unit, e.g. the tower or the next sector.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Unit {
+    /// as given in handoffs, e.g. "TWR"
+    pub name: String,
+    /// MHz, as written, e.g. "119.9"
+    pub frequency: String,
+}
+
+impl Unit {
+    pub fn new(name: &str, frequency: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            frequency: frequency.to_string(),
+        }
+    }
+
+    /// Whether `frequency` is this unit's, however many decimals it is
+    /// written with.
+    pub fn is_frequency(&self, frequency: &str) -> bool {
+        match (self.frequency.parse::<f32>(), frequency.parse::<f32>()) {
+            (Ok(own), Ok(other)) => (own - other).abs() < 0.001,
+            _ => self.frequency == frequency,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Sector {
+    /// unbounded when not given
+    pub airspace: Option<Airspace>,
+    pub units: Vec<Unit>,
+}
+
+impl Sector {
+    /// Whether `position` at `altitude` in feet is inside the sector.
+    pub fn contains(&self, position: &glm::Vec2, altitude: f32) -> bool {
+        match &self.airspace {
+            Some(airspace) => airspace.contains(position, altitude),
+            None => true,
+        }
+    }
+
+    /// Meters from `position` to the lateral boundary, negative inside the
+    /// sector.
+    pub fn distance(&self, position: &glm::Vec2) -> f32 {
+        match &self.airspace {
+            Some(airspace) => airspace.distance(position),
+            None => f32::NEG_INFINITY,
+        }
+    }
+
+    pub fn unit(&self, name: &str) -> Option<&Unit> {
+        self.units.iter().find(|unit| unit.name == name)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::airspace::AirspaceClass;
+
+    #[test]
+    fn test_sector_boundary() {
+        let sector = Sector {
+            airspace: Some(Airspace {
+                name: "LCPH".into(),
+                class: AirspaceClass::Sector,
+                boundary: vec![
+                    glm::vec2(0., 1000.),
+                    glm::vec2(1000., -1000.),
+                    glm::vec2(-1000., -1000.),
+                ],
+                floor: 0.,
+                ceiling: 10000.,
+            }),
+            units: vec![],
+        };
+        assert!(sector.contains(&glm::zero(), 5000.));
+        assert!(!sector.contains(&glm::vec2(0., 1001.), 5000.));
+        assert!(!sector.contains(&glm::zero(), 11000.));
+        assert_eq!(-447., sector.distance(&glm::zero()).round());
+        assert_eq!(500., sector.distance(&glm::vec2(0., -1500.)));
+        // everywhere, when not given
+        assert!(Sector::default().contains(&glm::vec2(1e9, 1e9), 1e9));
+    }
+
+    #[test]
+    fn test_sector_units() {
+        let sector = Sector {
+            airspace: None,
+            units: vec![Unit::new("TWR", "119.9"), Unit::new("DEP", "120.2")],
+        };
+        let departure = sector.unit("DEP").unwrap();
+        assert!(departure.is_frequency("120.2"));
+        assert!(departure.is_frequency("120.200"));
+        assert!(!departure.is_frequency("120.25"));
+        assert!(!departure.is_frequency("DEP"));
+        assert_eq!(None, sector.unit("APP"));
+    }
+}
@@ -0,0 +1,1622 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock};
+
+use crate::aircraft::*;
+use crate::airspace::{Airspace, AirspaceClass};
+use crate::atc::{AtcReply, AtcRequest};
+use crate::command::{AtcCommand, Condition};
+use crate::fix::FixDatabase;
+use crate::msaw::{self, TerrainWarning};
+use crate::pilot::{Pilot, PilotMessage};
+use crate::score::Score;
+use crate::sector::Sector;
+use crate::stca::{self, Conflict};
+use crate::terrain::Terrain;
+use crate::traffic::Traffic;
+use crate::wind::Wind;
+
+/// Length of a single simulation step, in seconds.
+pub const TIMESTEP: f32 = 1. / 60.;
+/// meters short of the sector boundary inbound aircraft are offered at,
+/// 10nm
+const HANDOFF_OFFER_DISTANCE: f32 = 18_520.;
+
+/// Why an aircraft went around.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum GoAroundReason {
+    /// reached the runway without a landing clearance
+    NoLandingClearance,
+    RunwayOccupied,
+    /// told to by the controller
+    Instructed,
+}
+
+impl std::fmt::Display for GoAroundReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            GoAroundReason::NoLandingClearance => write!(f, "no landing clearance"),
+            GoAroundReason::RunwayOccupied => write!(f, "runway occupied"),
+            GoAroundReason::Instructed => write!(f, "as instructed"),
+        }
+    }
+}
+
+/// How an aircraft crossed the sector boundary before its handoff.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum MissedHandoffReason {
+    /// left while still under our control
+    LeftSector,
+    /// entered while still inbound
+    NotAccepted,
+}
+
+impl std::fmt::Display for MissedHandoffReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            MissedHandoffReason::LeftSector => write!(f, "left the sector without a handoff"),
+            MissedHandoffReason::NotAccepted => {
+                write!(f, "entered the sector without being accepted")
+            }
+        }
+    }
+}
+
+/// Something that happened during a step, for the controller's attention.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SimEvent {
+    /// a new conflict, or a predicted conflict that became a loss of separation
+    ConflictAlert(Conflict),
+    /// an aircraft went around, for the reason given
+    GoAround(Callsign, GoAroundReason),
+    /// a pilot called without being spoken to
+    Pilot(Callsign, PilotMessage),
+    /// an aircraft touched down, and is gone
+    Landed(Callsign),
+    /// the adjacent unit offered an inbound aircraft, to be accepted
+    HandoffOffered(Callsign),
+    /// an aircraft crossed the sector boundary before its handoff, for the
+    /// reason given
+    MissedHandoff(Callsign, MissedHandoffReason),
+    /// an aircraft under our control entered the restricted area named
+    Infringement(Callsign, String),
+    /// a new terrain warning, or a predicted one that became a low altitude
+    TerrainAlert(TerrainWarning),
+    /// an aircraft flew into the terrain, and is gone
+    Crashed(Callsign),
+}
+
+impl std::fmt::Display for SimEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            SimEvent::ConflictAlert(conflict) => write!(f, "STCA: {}", conflict),
+            SimEvent::GoAround(callsign, reason) => {
+                write!(f, "{} going around, {}", callsign, reason)
+            }
+            SimEvent::Pilot(callsign, message) => {
+                write!(f, "<== {}, {}", callsign, message.as_string())
+            }
+            SimEvent::Landed(callsign) => write!(f, "{} landed", callsign),
+            SimEvent::HandoffOffered(callsign) => write!(f, "Handoff offered: {}", callsign),
+            SimEvent::MissedHandoff(callsign, reason) => {
+                write!(f, "Missed handoff: {} {}", callsign, reason)
+            }
+            SimEvent::Infringement(callsign, airspace) => {
+                write!(f, "Infringement: {} entered {}", callsign, airspace)
+            }
+            SimEvent::TerrainAlert(warning) => write!(f, "MSAW: {}", warning),
+            SimEvent::Crashed(callsign) => write!(f, "{} crashed into terrain", callsign),
+        }
+    }
+}
+
+/// Why a command wasn't given to an aircraft.
+#[derive(Clone, Debug, PartialEq)]
+pub enum CommandError {
+    UnknownAircraft(Callsign),
+    /// accepting a handoff that wasn't offered
+    NotOffered(Callsign),
+    NotUnderControl(Callsign),
+    UnknownFix(String),
+    UnknownRunway(String),
+    UnknownUnit(String),
+    /// the unit named, its frequency and the one given
+    WrongFrequency(String, String, String),
+}
+
+impl std::fmt::Display for CommandError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            CommandError::UnknownAircraft(callsign) => {
+                write!(f, "Aircraft with callsign {} doesn't exist", callsign)
+            }
+            CommandError::NotOffered(callsign) => write!(f, "{} hasn't been offered", callsign),
+            CommandError::NotUnderControl(callsign) => {
+                write!(f, "{} is not under our control", callsign)
+            }
+            CommandError::UnknownFix(fix) => write!(f, "Fix {} doesn't exist", fix),
+            CommandError::UnknownRunway(runway) => write!(f, "Runway {} doesn't exist", runway),
+            CommandError::UnknownUnit(unit) => write!(f, "Unit {} doesn't exist", unit),
+            CommandError::WrongFrequency(unit, frequency, given) => {
+                write!(f, "{} is on {}, not {}", unit, frequency, given)
+            }
+        }
+    }
+}
+
+/// Headless simulation of the airport and its traffic, advanced in fixed
+/// steps independently of any renderer.
+#[derive(Debug)]
+pub struct Simulation {
+    pub airport: Airport,
+    pub wind: Wind,
+    pub fixes: FixDatabase,
+    pub sector: Sector,
+    /// control zones, restricted and danger areas in and around the sector
+    pub airspaces: Vec<Airspace>,
+    /// elevation for the minimum safe altitude warnings and crashes, neither
+    /// checked when not given
+    pub terrain: Option<Terrain>,
+    /// arrivals yet to enter, if the scenario has any
+    pub traffic: Option<Traffic>,
+    // shared with the MSFS monitor thread
+    pub aircraft: Arc<RwLock<Vec<Aircraft>>>,
+    /// current short term conflict alerts
+    pub conflicts: Vec<Conflict>,
+    /// current minimum safe altitude warnings
+    pub terrain_warnings: Vec<TerrainWarning>,
+    pub score: Score,
+    /// by coded callsign
+    pilots: HashMap<String, Pilot>,
+    /// coded callsigns of the inbound aircraft offered so far
+    offered: HashSet<String>,
+    events: Vec<SimEvent>,
+    /// elapsed simulation time in seconds
+    time: f32,
+    /// steps run so far
+    ticks: u64,
+    /// real time not yet consumed by a fixed step
+    accumulator: f32,
+}
+
+impl Simulation {
+    pub fn new(airport: Airport, aircraft: Vec<Aircraft>) -> Self {
+        Self {
+            airport,
+            wind: Wind::default(),
+            fixes: FixDatabase::default(),
+            sector: Sector::default(),
+            airspaces: Vec::new(),
+            terrain: None,
+            traffic: None,
+            aircraft: Arc::new(RwLock::new(aircraft)),
+            conflicts: Vec::new(),
+            terrain_warnings: Vec::new(),
+            score: Score::default(),
+            pilots: HashMap::new(),
+            offered: HashSet::new(),
+            events: Vec::new(),
+            time: 0.0,
+            ticks: 0,
+            accumulator: 0.0,
+        }
+    }
+
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    /// Check the command can be given to the aircraft with the callsign: that
+    /// the aircraft is ours to give it to, and that the fixes, runways and
+    /// units it names exist.
+    pub fn check_command(&self, callsign: &Callsign, cmd: &AtcCommand) -> Result<(), CommandError> {
+        let aircraft = self.aircraft.read().unwrap();
+        match aircraft.iter().find(|a| &a.callsign == callsign) {
+            Some(aircraft) => self.check(aircraft, cmd),
+            None => Err(CommandError::UnknownAircraft(callsign.clone())),
+        }
+    }
+
+    /// Pass a command to the aircraft with the callsign, once checked, returning
+    /// its readback. Applied before the next step.
+    pub fn command(
+        &mut self,
+        callsign: &Callsign,
+        cmd: AtcCommand,
+    ) -> Result<AtcReply, CommandError> {
+        let mut aircraft = self.aircraft.write().unwrap();
+        match aircraft.iter_mut().find(|a| &a.callsign == callsign) {
+            Some(aircraft) => {
+                self.check(aircraft, &cmd)?;
+                Ok(aircraft.command(AtcRequest(cmd)))
+            }
+            None => Err(CommandError::UnknownAircraft(callsign.clone())),
+        }
+    }
+
+    fn check(&self, aircraft: &Aircraft, cmd: &AtcCommand) -> Result<(), CommandError> {
+        match (&aircraft.status, cmd) {
+            (AircraftStatus::Inbound, AtcCommand::AcceptHandoff) => {}
+            (_, AtcCommand::AcceptHandoff) => {
+                return Err(CommandError::NotOffered(aircraft.callsign.clone()))
+            }
+            _ if !aircraft.is_under_control() => {
+                return Err(CommandError::NotUnderControl(aircraft.callsign.clone()))
+            }
+            _ => {}
+        }
+        if let Some(fix) = cmd
+            .fixes()
+            .into_iter()
+            .find(|fix| self.fixes.get(fix).is_none())
+        {
+            return Err(CommandError::UnknownFix(fix.to_string()));
+        }
+        if let Some(runway) = cmd
+            .runways()
+            .into_iter()
+            .find(|runway| self.airport.landing_runway(runway).is_none())
+        {
+            return Err(CommandError::UnknownRunway(runway.to_string()));
+        }
+        match cmd.handoff() {
+            Some((name, frequency)) => match (self.sector.unit(name), frequency) {
+                (None, _) => Err(CommandError::UnknownUnit(name.to_string())),
+                (Some(unit), Some(frequency)) if !unit.is_frequency(frequency) => {
+                    Err(CommandError::WrongFrequency(
+                        name.to_string(),
+                        unit.frequency.to_string(),
+                        frequency.to_string(),
+                    ))
+                }
+                _ => Ok(()),
+            },
+            None => Ok(()),
+        }
+    }
+
+    /// Take the events raised since the last call.
+    pub fn drain_events(&mut self) -> Vec<SimEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Consume real elapsed time, running as many fixed steps as fit in it.
+    pub fn update(&mut self, dt: f32) {
+        self.accumulator += dt;
+        while self.accumulator >= TIMESTEP {
+            self.tick(TIMESTEP);
+            self.accumulator -= TIMESTEP;
+        }
+    }
+
+    /// Advance the simulation by a single step of `dt` seconds.
+    pub fn tick(&mut self, dt: f32) {
+        let mut aircraft = self.aircraft.write().unwrap();
+        let first_event = self.events.len();
+        // runways with departures lined up or rolling on them
+        let occupied: Vec<String> = aircraft
+            .iter()
+            .filter(|a| a.status == AircraftStatus::LinedUp || a.is_rolling())
+            .filter_map(|a| a.runway.clone())
+            .collect();
+        // coded callsigns of the aircraft that flew into the terrain
+        let mut crashed: Vec<String> = Vec::new();
+        for aircraft in aircraft.iter_mut() {
+            let (was_at, was_altitude) = (aircraft.position, aircraft.altitude.current);
+            let was_inside = self.sector.contains(&was_at, was_altitude);
+            aircraft.wind = self.wind.at(aircraft.altitude.current);
+            aircraft.update(dt);
+
+            let departure_runway = aircraft
+                .runway
+                .as_ref()
+                .and_then(|name| self.airport.takeoff_runway(name));
+            if let Some(runway) = departure_runway {
+                let origin = self.airport.origin(runway);
+                match aircraft.status {
+                    AircraftStatus::Taxi | AircraftStatus::LinedUp => {
+                        let is_cleared =
+                            aircraft.cleared_to_line_up || aircraft.cleared_for_takeoff;
+                        if aircraft.status == AircraftStatus::Taxi && is_cleared {
+                            aircraft.line_up(runway.threshold(origin), runway.heading as f32);
+                        }
+                        if aircraft.cleared_for_takeoff {
+                            aircraft.start_takeoff();
+                        }
+                    }
+                    AircraftStatus::Takeoff => aircraft.climb_out(runway.initial_altitude),
+                    _ => {}
+                }
+            }
+
+            let fix = aircraft
+                .direct
+                .as_ref()
+                .and_then(|name| self.fixes.get(name));
+            if let Some(fix) = fix {
+                aircraft.fly_direct(fix.position);
+            }
+            let hold_fix = aircraft
+                .hold
+                .as_ref()
+                .and_then(|hold| self.fixes.get(&hold.fix));
+            if let Some(fix) = hold_fix {
+                aircraft.fly_hold(fix.position, dt);
+            }
+            let reach_by_fix = match &aircraft.vertical {
+                Some(VerticalInstruction::ReachBy { fix, .. }) => self.fixes.get(fix),
+                _ => None,
+            };
+            if let Some(fix) = reach_by_fix {
+                aircraft.reach_by(fix.position);
+            }
+            aircraft.check_pending(dt, |condition| match condition {
+                Condition::FixDistance(fix, _) => self.fixes.get(fix).map(|fix| fix.position),
+                Condition::ThresholdDistance(runway, _) => self
+                    .airport
+                    .landing_runway(runway)
+                    .map(|runway| runway.threshold(self.airport.origin(runway))),
+                _ => None,
+            });
+
+            let approach_runway = aircraft
+                .runway
+                .as_ref()
+                .filter(|_| aircraft.approach.is_some())
+                .and_then(|name| self.airport.landing_runway(name));
+            if let Some(runway) = approach_runway {
+                let origin = self.airport.origin(runway);
+                if aircraft.go_around_instructed {
+                    aircraft.go_around(&runway.missed_approach);
+                    self.events.push(SimEvent::GoAround(
+                        aircraft.callsign.clone(),
+                        GoAroundReason::Instructed,
+                    ));
+                } else if runway.has_landed(origin, aircraft) {
+                    let reason = if !aircraft.cleared_to_land() {
+                        Some(GoAroundReason::NoLandingClearance)
+                    } else if occupied.contains(&runway.name) {
+                        Some(GoAroundReason::RunwayOccupied)
+                    } else {
+                        None
+                    };
+                    match reason {
+                        // once handed off to the tower, it lands on the
+                        // tower's clearance
+                        Some(reason) if aircraft.is_under_control() => {
+                            aircraft.go_around(&runway.missed_approach);
+                            self.events
+                                .push(SimEvent::GoAround(aircraft.callsign.clone(), reason));
+                        }
+                        _ => {
+                            aircraft.status = AircraftStatus::Landed;
+                            self.events
+                                .push(SimEvent::Landed(aircraft.callsign.clone()));
+                        }
+                    }
+                } else {
+                    aircraft.fly_approach(&runway.ils(origin));
+                }
+            }
+            aircraft.fly_missed_approach();
+
+            if matches!(&self.terrain, Some(terrain) if msaw::has_crashed(aircraft, terrain)) {
+                crashed.push(aircraft.callsign.coded());
+                self.events
+                    .push(SimEvent::Crashed(aircraft.callsign.clone()));
+                continue;
+            }
+
+            // handoffs are due before the aircraft crosses the boundary
+            let is_inside = self
+                .sector
+                .contains(&aircraft.position, aircraft.altitude.current);
+            if aircraft.status == AircraftStatus::Inbound {
+                if !was_inside && is_inside {
+                    aircraft.status = AircraftStatus::Flight;
+                    self.events.push(SimEvent::MissedHandoff(
+                        aircraft.callsign.clone(),
+                        MissedHandoffReason::NotAccepted,
+                    ));
+                } else if self.sector.distance(&aircraft.position) <= HANDOFF_OFFER_DISTANCE
+                    && self.offered.insert(aircraft.callsign.coded())
+                {
+                    self.events
+                        .push(SimEvent::HandoffOffered(aircraft.callsign.clone()));
+                }
+            } else if aircraft.is_under_control() && was_inside && !is_inside {
+                aircraft.status = AircraftStatus::HandedOff;
+                self.events.push(SimEvent::MissedHandoff(
+                    aircraft.callsign.clone(),
+                    MissedHandoffReason::LeftSector,
+                ));
+            }
+
+            if aircraft.is_under_control() {
+                let infringed = self.airspaces.iter().filter(|airspace| {
+                    airspace.class == AirspaceClass::Restricted
+                        && !airspace.contains(&was_at, was_altitude)
+                        && airspace.contains(&aircraft.position, aircraft.altitude.current)
+                });
+                for airspace in infringed {
+                    self.events.push(SimEvent::Infringement(
+                        aircraft.callsign.clone(),
+                        airspace.name.clone(),
+                    ));
+                }
+            }
+
+            let pilot = self.pilots.entry(aircraft.callsign.coded()).or_default();
+            if let Some(message) = pilot.update(aircraft, dt, self.airport.atis) {
+                self.events
+                    .push(SimEvent::Pilot(aircraft.callsign.clone(), message));
+            }
+        }
+
+        // remove landed and crashed aircraft, and those handed off once out of
+        // the sector
+        let sector = &self.sector;
+        aircraft.retain(|a| match a.status {
+            _ if crashed.contains(&a.callsign.coded()) => false,
+            AircraftStatus::Landed => false,
+            AircraftStatus::HandedOff => sector.contains(&a.position, a.altitude.current),
+            _ => true,
+        });
+        let exists = |callsign: &String| aircraft.iter().any(|a| a.callsign.coded() == *callsign);
+        self.pilots.retain(|callsign, _| exists(callsign));
+        self.offered.retain(|callsign| exists(callsign));
+
+        let arrival = self
+            .traffic
+            .as_mut()
+            .and_then(|traffic| traffic.update(dt, &aircraft, &self.sector));
+        if let Some(arrival) = arrival {
+            aircraft.push(arrival);
+        }
+
+        let conflicts = stca::conflicts(&aircraft);
+        for conflict in &conflicts {
+            let is_new = !self.conflicts.iter().any(|old| {
+                old.is_same_pair(conflict) && (old.is_loss || !conflict.is_loss)
+            });
+            if is_new {
+                self.events.push(SimEvent::ConflictAlert(conflict.clone()));
+            }
+        }
+        self.conflicts = conflicts;
+
+        if let Some(terrain) = &self.terrain {
+            let warnings = msaw::warnings(&aircraft, terrain);
+            for warning in &warnings {
+                let is_new = !self.terrain_warnings.iter().any(|old| {
+                    old.callsign == warning.callsign && (old.is_low() || !warning.is_low())
+                });
+                if is_new {
+                    self.events.push(SimEvent::TerrainAlert(warning.clone()));
+                }
+            }
+            self.terrain_warnings = warnings;
+        }
+        self.score.update(&aircraft, &self.events[first_event..]);
+
+        self.time += dt;
+        self.ticks += 1;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::aircraft::test_aircraft;
+    use crate::fix::Fix;
+    use crate::geom::{distance_line_and_point, heading_to_point};
+    use crate::hold::{HoldEntry, HoldState};
+    use crate::math::{degrees_normalize, short_angle_distance};
+    use crate::score::Item;
+    use crate::sector::Unit;
+    use crate::terrain::test_terrain;
+    use crate::wind::WindLayer;
+
+    fn run_for(sim: &mut Simulation, duration: f32) {
+        let steps = (duration / TIMESTEP).round() as u32;
+        for _ in 0..steps {
+            sim.tick(TIMESTEP);
+        }
+    }
+
+    fn test_airport() -> Airport {
+        let runway = Runway {
+            name: "29".into(),
+            offset: glm::zero(),
+            heading: 285,
+            length: 2700,
+            width: 45,
+            ils_max_altitude: 2000,
+            initial_altitude: 3000,
+            missed_approach: MissedApproach {
+                altitude: 3000,
+                route: MissedApproachRoute::Heading(285),
+            },
+        };
+        Airport {
+            position: glm::zero(),
+            icao_code: "LCPH".into(),
+            atis: 'A',
+            takeoff_runways: vec![runway.clone()],
+            landing_runways: vec![runway],
+        }
+    }
+
+    #[test]
+    fn test_simulation_time() {
+        let mut sim = Simulation::new(test_airport(), vec![]);
+        run_for(&mut sim, 10.0);
+        assert_eq!(10.0, sim.time().round());
+        assert_eq!(600, sim.ticks());
+
+        // less than a single step doesn't advance the simulation
+        sim.update(TIMESTEP / 2.);
+        assert_eq!(10.0, sim.time().round());
+        assert_eq!(600, sim.ticks());
+    }
+
+    #[test]
+    fn test_simulation_command() {
+        let mut sim = Simulation::new(
+            test_airport(),
+            vec![test_aircraft(glm::zero(), 0.0, 4000.0)],
+        );
+        let callsign = Callsign::from_string("CYP2202".into()).unwrap();
+        assert!(sim
+            .command(&callsign, AtcCommand::ChangeAltitude(6000))
+            .is_ok());
+        assert_eq!(6000.0, sim.aircraft.read().unwrap()[0].altitude.intended());
+
+        let unknown = Callsign::from_string("FDX261".into()).unwrap();
+        assert_eq!(
+            Err(CommandError::UnknownAircraft(unknown.clone())),
+            sim.check_command(&unknown, &AtcCommand::ChangeAltitude(6000))
+        );
+        assert!(sim
+            .command(&unknown, AtcCommand::ChangeAltitude(6000))
+            .is_err());
+    }
+
+    #[test]
+    fn test_simulation_command_errors() {
+        let mut sim = sector_simulation(test_aircraft(glm::zero(), 0.0, 4000.0));
+        let callsign = Callsign::from_string("CYP2202".into()).unwrap();
+        let mut check = |cmd: AtcCommand| sim.command(&callsign, cmd).map(|_| ());
+
+        assert_eq!(
+            Err(CommandError::UnknownFix("KOLOS".into())),
+            check(AtcCommand::DirectTo("KOLOS".into()))
+        );
+        assert_eq!(
+            Err(CommandError::UnknownRunway("11".into())),
+            check(AtcCommand::ClearedForApproach("11".into()))
+        );
+        assert_eq!(
+            Err(CommandError::UnknownUnit("APP".into())),
+            check(AtcCommand::Handoff {
+                unit: "APP".into(),
+                frequency: None,
+            })
+        );
+        assert_eq!(
+            Err(CommandError::WrongFrequency(
+                "TWR".into(),
+                "119.9".into(),
+                "128.4".into()
+            )),
+            check(AtcCommand::Handoff {
+                unit: "TWR".into(),
+                frequency: Some("128.4".into()),
+            })
+        );
+        assert_eq!(
+            Err(CommandError::NotOffered(callsign.clone())),
+            check(AtcCommand::AcceptHandoff)
+        );
+        // rejected commands aren't flown
+        assert_eq!(
+            AircraftStatus::Flight,
+            sim.aircraft.read().unwrap()[0].status
+        );
+
+        sim.aircraft.write().unwrap()[0].status = AircraftStatus::HandedOff;
+        assert_eq!(
+            Err(CommandError::NotUnderControl(callsign.clone())),
+            sim.command(&callsign, AtcCommand::ChangeAltitude(6000))
+                .map(|_| ())
+        );
+    }
+
+    #[test]
+    fn test_simulation_event_display() {
+        let callsign = Callsign::from_string("CYP2202".into()).unwrap();
+        assert_eq!(
+            "CYP2202 going around, runway occupied",
+            SimEvent::GoAround(callsign.clone(), GoAroundReason::RunwayOccupied).to_string()
+        );
+        assert_eq!(
+            "Missed handoff: CYP2202 left the sector without a handoff",
+            SimEvent::MissedHandoff(callsign, MissedHandoffReason::LeftSector).to_string()
+        );
+    }
+
+    #[test]
+    fn test_simulation_moves_aircraft() {
+        let mut sim = Simulation::new(
+            test_airport(),
+            vec![test_aircraft(glm::zero(), 0.0, 4000.0)],
+        );
+        run_for(&mut sim, 60.0);
+
+        let aircraft = sim.aircraft.read().unwrap();
+        // 200kt for 1 minute
+        let expected = 200.0 * crate::units::KT_TO_MS as f32 * 60.0;
+        assert_eq!(0.0, aircraft[0].position.x.round());
+        assert!((aircraft[0].position.y - expected).abs() < 10.0);
+    }
+
+    #[test]
+    fn test_simulation_conflict_alerts() {
+        let mut other = test_aircraft(glm::vec2(10_000.0, 0.0), 270.0, 4000.0);
+        other.callsign = Callsign::from_string("FDX261".into()).unwrap();
+        let mut sim = Simulation::new(
+            test_airport(),
+            vec![test_aircraft(glm::zero(), 90.0, 4000.0), other],
+        );
+
+        let alerts = |sim: &mut Simulation| -> Vec<SimEvent> {
+            sim.drain_events()
+                .into_iter()
+                .filter(|event| matches!(event, SimEvent::ConflictAlert(_)))
+                .collect()
+        };
+
+        sim.tick(TIMESTEP);
+        assert_eq!(1, sim.conflicts.len());
+        let events = alerts(&mut sim);
+        assert_eq!(1, events.len());
+        assert!(matches!(&events[0], SimEvent::ConflictAlert(c) if !c.is_loss));
+
+        // an ongoing conflict is only raised once
+        sim.tick(TIMESTEP);
+        assert!(alerts(&mut sim).is_empty());
+
+        // until separation is lost
+        run_for(&mut sim, 25.0);
+        let events = alerts(&mut sim);
+        assert_eq!(1, events.len());
+        assert!(matches!(&events[0], SimEvent::ConflictAlert(c) if c.is_loss));
+    }
+
+    #[test]
+    fn test_simulation_departure() {
+        let airport = test_airport();
+        let runway = &airport.takeoff_runways[0];
+        let origin = airport.origin(runway);
+        let threshold = runway.threshold(origin);
+
+        let mut aircraft = test_aircraft(runway.holding_point(origin), 0.0, 0.0);
+        aircraft.speed = AircraftParameter::new(0.0);
+        aircraft.status = AircraftStatus::Taxi;
+        aircraft.runway = Some("29".into());
+        let mut sim = Simulation::new(airport.clone(), vec![aircraft]);
+
+        // holds short until cleared
+        run_for(&mut sim, 10.0);
+        {
+            let aircraft = &sim.aircraft.read().unwrap()[0];
+            assert_eq!(AircraftStatus::Taxi, aircraft.status);
+            assert_eq!(runway.holding_point(origin), aircraft.position);
+        }
+
+        sim.aircraft.write().unwrap()[0].command(AtcRequest(AtcCommand::LineUp));
+        run_for(&mut sim, 10.0);
+        {
+            let aircraft = &sim.aircraft.read().unwrap()[0];
+            assert_eq!(AircraftStatus::LinedUp, aircraft.status);
+            assert_eq!(threshold, aircraft.position);
+            assert_eq!(285.0, aircraft.heading.current);
+        }
+
+        sim.aircraft.write().unwrap()[0].command(AtcRequest(AtcCommand::ClearedForTakeoff));
+        run_for(&mut sim, 20.0);
+        {
+            let aircraft = &sim.aircraft.read().unwrap()[0];
+            assert_eq!(AircraftStatus::Takeoff, aircraft.status);
+            assert!(aircraft.is_rolling());
+            assert_eq!(0.0, aircraft.altitude.current);
+            // accelerating along the centreline
+            assert!(aircraft.speed.current > 50.0);
+            assert!(distance_line_and_point(&runway.as_line(origin), &aircraft.position) < 1.0);
+            assert!(glm::distance(&threshold, &aircraft.position) < runway.length as f32);
+        }
+
+        // rotates after ~40s, then climbs through 1000ft
+        run_for(&mut sim, 60.0);
+        {
+            let aircraft = &sim.aircraft.read().unwrap()[0];
+            assert!(!aircraft.is_rolling());
+            assert_eq!(AircraftStatus::Flight, aircraft.status);
+            assert_eq!(285.0, aircraft.heading.current);
+        }
+
+        run_for(&mut sim, 120.0);
+        let aircraft = &sim.aircraft.read().unwrap()[0];
+        assert_eq!(3000.0, aircraft.altitude.current);
+        assert_eq!(210.0, aircraft.speed.current);
+    }
+
+    /// Aircraft `distance` meters out on the approach to runway 29 and
+    /// `offset` meters right of the centreline, cleared to land.
+    fn approaching_aircraft(distance: f32, offset: f32, heading: f32) -> Simulation {
+        let airport = test_airport();
+        let runway = &airport.landing_runways[0];
+        let threshold = runway.threshold(airport.origin(runway));
+        let position = threshold
+            + heading_to_point(runway.heading as i32 + 180) * distance
+            + heading_to_point(runway.heading as i32 + 90) * offset;
+
+        let mut aircraft = test_aircraft(position, heading, 1500.0);
+        aircraft.speed = AircraftParameter::new(160.0);
+        aircraft.command(AtcRequest(AtcCommand::ClearedToLand(Some("29".into()))));
+        Simulation::new(airport, vec![aircraft])
+    }
+
+    /// Fly the approach until the glideslope is captured, returning the
+    /// states passed through and the furthest the aircraft overshot the
+    /// centreline.
+    fn fly_intercept(sim: &mut Simulation, offset: f32) -> (Vec<ApproachState>, f32) {
+        let runway = sim.airport.landing_runways[0].clone();
+        let ils = runway.ils(sim.airport.origin(&runway));
+        let mut states = vec![];
+        let mut overshoot: f32 = 0.;
+
+        for _ in 0..(300. / TIMESTEP) as u32 {
+            sim.tick(TIMESTEP);
+            let aircraft = &sim.aircraft.read().unwrap()[0];
+            let state = aircraft.approach.unwrap();
+            if states.last() != Some(&state) {
+                states.push(state);
+            }
+            let crosstrack = ils.crosstrack_distance(&aircraft.position);
+            if crosstrack.signum() != offset.signum() {
+                overshoot = overshoot.max(crosstrack.abs());
+            }
+            if state == ApproachState::GlideslopeCaptured {
+                break;
+            }
+        }
+        (states, overshoot)
+    }
+
+    #[test]
+    fn test_simulation_localizer_intercept() {
+        use ApproachState::*;
+
+        for offset in [-2000.0f32, 2000.0] {
+            for angle in [20.0, 30.0, 45.0, 90.0] {
+                // turned towards the centreline
+                let heading = degrees_normalize(285.0 - angle * offset.signum());
+                let mut sim = approaching_aircraft(16000.0, offset, heading);
+                let (states, overshoot) = fly_intercept(&mut sim, offset);
+
+                let case = format!("offset {} angle {}", offset, angle);
+                assert_eq!(
+                    vec![
+                        LocalizerArmed,
+                        LocalizerCaptured,
+                        GlideslopeArmed,
+                        GlideslopeCaptured
+                    ],
+                    states,
+                    "{}",
+                    case
+                );
+                assert!(overshoot < 150.0, "{}: overshot by {}", case, overshoot);
+
+                // settled on the centreline
+                run_for(&mut sim, 30.0);
+                let aircraft = &sim.aircraft.read().unwrap()[0];
+                let runway = &sim.airport.landing_runways[0];
+                let ils = runway.ils(sim.airport.origin(runway));
+                assert_eq!(AircraftStatus::Landing, aircraft.status, "{}", case);
+                assert_eq!(Some("29".into()), aircraft.runway, "{}", case);
+                assert!(
+                    ils.crosstrack_distance(&aircraft.position).abs() < 20.0,
+                    "{}",
+                    case
+                );
+                assert!(
+                    short_angle_distance(aircraft.heading.current, 285.0).abs() < 2.0,
+                    "{}",
+                    case
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_simulation_localizer_crosswind() {
+        for direction in [195, 15] {
+            let mut sim = approaching_aircraft(18000.0, 2500.0, 255.0);
+            sim.wind = Wind::new(vec![WindLayer {
+                ceiling: 10000,
+                direction,
+                speed: 25,
+            }]);
+            fly_intercept(&mut sim, 2500.0);
+            run_for(&mut sim, 30.0);
+
+            let runway = &sim.airport.landing_runways[0];
+            let ils = runway.ils(sim.airport.origin(runway));
+            let aircraft = &sim.aircraft.read().unwrap()[0];
+            let correction = aircraft.wind_correction_angle(285.0);
+            assert!(correction.abs() > 5.0);
+
+            // crabbed into the wind, tracking the centreline
+            assert!(ils.crosstrack_distance(&aircraft.position).abs() < 20.0);
+            assert!(short_angle_distance(aircraft.track(), 285.0).abs() < 1.0);
+            assert!(
+                (short_angle_distance(285.0, aircraft.heading.current) - correction).abs() < 1.0
+            );
+        }
+    }
+
+    #[test]
+    fn test_simulation_wind() {
+        let mut sim = Simulation::new(
+            test_airport(),
+            vec![test_aircraft(glm::zero(), 0.0, 4000.0)],
+        );
+        sim.wind = Wind::new(vec![
+            WindLayer {
+                ceiling: 3000,
+                direction: 360,
+                speed: 10,
+            },
+            WindLayer {
+                ceiling: 10000,
+                direction: 270,
+                speed: 30,
+            },
+        ]);
+        run_for(&mut sim, 60.0);
+
+        // wind of the layer at the aircraft's altitude
+        let aircraft = &sim.aircraft.read().unwrap()[0];
+        assert_eq!(sim.wind.at(4000.0), aircraft.wind);
+        let drift = 30.0 * crate::units::KT_TO_MS as f32 * 60.0;
+        assert!((aircraft.position.x - drift).abs() < 10.0);
+    }
+
+    #[test]
+    fn test_simulation_glideslope() {
+        let mut sim = approaching_aircraft(16000.0, 2000.0, 255.0);
+        fly_intercept(&mut sim, 2000.0);
+
+        let runway = sim.airport.landing_runways[0].clone();
+        let ils = runway.ils(sim.airport.origin(&runway));
+        let mut last_altitude = sim.aircraft.read().unwrap()[0].altitude.current;
+        // captured level at 1500ft
+        assert!((last_altitude - 1500.0).abs() <= 50.0);
+
+        for _ in 0..10 {
+            run_for(&mut sim, 10.0);
+            let aircraft = &sim.aircraft.read().unwrap()[0];
+            let altitude = aircraft.altitude.current;
+            assert!(altitude < last_altitude);
+            // about 800ft/min at 160kt
+            assert!((-900.0..-700.0).contains(&aircraft.vertical_speed));
+            assert_eq!(ils.glideslope_altitude(&aircraft.position), altitude);
+            last_altitude = altitude;
+        }
+
+        // follows the glidepath down to the runway
+        run_for(&mut sim, 120.0);
+        assert!(sim.aircraft.read().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_simulation_localizer_not_captured() {
+        // heading away from the runway
+        let mut sim = approaching_aircraft(16000.0, 500.0, 105.0);
+        run_for(&mut sim, 30.0);
+        let aircraft = &sim.aircraft.read().unwrap()[0];
+        assert_eq!(Some(ApproachState::LocalizerArmed), aircraft.approach);
+        assert_eq!(AircraftStatus::Flight, aircraft.status);
+
+        // above the highest intercept altitude
+        let mut sim = approaching_aircraft(16000.0, 2000.0, 255.0);
+        sim.aircraft.write().unwrap()[0].altitude = AircraftParameter::new(3000.0);
+        run_for(&mut sim, 60.0);
+        let aircraft = &sim.aircraft.read().unwrap()[0];
+        assert_eq!(Some(ApproachState::LocalizerArmed), aircraft.approach);
+
+        // outside of the localizer coverage
+        let mut sim = approaching_aircraft(16000.0, 12000.0, 195.0);
+        sim.tick(TIMESTEP);
+        let aircraft = &sim.aircraft.read().unwrap()[0];
+        assert_eq!(Some(ApproachState::LocalizerArmed), aircraft.approach);
+    }
+
+    #[test]
+    fn test_simulation_approach_cancelled() {
+        let mut sim = approaching_aircraft(16000.0, 2000.0, 255.0);
+        fly_intercept(&mut sim, 2000.0);
+
+        let mut aircraft = sim.aircraft.write().unwrap();
+        aircraft[0].command(AtcRequest(AtcCommand::ClearedToLand(None)));
+        assert_eq!(None, aircraft[0].approach);
+        assert_eq!(None, aircraft[0].runway);
+        assert_eq!(AircraftStatus::Flight, aircraft[0].status);
+    }
+
+    #[test]
+    fn test_simulation_approach_runway() {
+        use ApproachState::*;
+
+        let mut sim = approaching_aircraft(16000.0, 2000.0, 255.0);
+        // the reciprocal runway, its localizer on the far side of the airport
+        let mut reciprocal = sim.airport.landing_runways[0].clone();
+        reciprocal.name = "11".into();
+        reciprocal.heading = 105;
+        sim.airport.landing_runways.push(reciprocal);
+        let callsign = Callsign::from_string("CYP2202".into()).unwrap();
+
+        // crossing the localizer of 29 while cleared for 11
+        sim.command(&callsign, AtcCommand::ClearedForApproach("11".into()))
+            .unwrap();
+        {
+            let aircraft = &sim.aircraft.read().unwrap()[0];
+            assert_eq!(Some("11".into()), aircraft.runway);
+            assert!(!aircraft.cleared_to_land());
+        }
+        run_for(&mut sim, 60.0);
+        {
+            let aircraft = &sim.aircraft.read().unwrap()[0];
+            assert_eq!(Some(LocalizerArmed), aircraft.approach);
+            assert_eq!(AircraftStatus::Flight, aircraft.status);
+        }
+
+        // turned back and cleared for 29
+        sim.command(&callsign, AtcCommand::ChangeHeading(15))
+            .unwrap();
+        sim.command(&callsign, AtcCommand::ClearedForApproach("29".into()))
+            .unwrap();
+        let (states, _) = fly_intercept(&mut sim, -2000.0);
+        assert_eq!(Some(&GlideslopeCaptured), states.last());
+
+        // switching runways on the approach starts it over
+        sim.command(&callsign, AtcCommand::ClearedToLand(Some("11".into())))
+            .unwrap();
+        let aircraft = &sim.aircraft.read().unwrap()[0];
+        assert_eq!(Some("11".into()), aircraft.runway);
+        assert_eq!(Some(LocalizerArmed), aircraft.approach);
+        assert_eq!(AircraftStatus::Flight, aircraft.status);
+        assert!(aircraft.cleared_to_land());
+    }
+
+    #[test]
+    fn test_simulation_approach_not_for_departures() {
+        let mut aircraft = test_aircraft(glm::zero(), 285.0, 0.0);
+        aircraft.status = AircraftStatus::Taxi;
+        aircraft.runway = Some("29".into());
+        aircraft.command(AtcRequest(AtcCommand::ClearedToLand(Some("29".into()))));
+        assert_eq!(None, aircraft.approach);
+        assert!(!aircraft.cleared_to_land());
+    }
+
+    #[test]
+    fn test_simulation_pilot_messages() {
+        let mut sim = approaching_aircraft(16000.0, 2000.0, 255.0);
+        let mut departure = test_aircraft(glm::zero(), 285.0, 0.0);
+        departure.callsign = Callsign::from_string("FDX261".into()).unwrap();
+        departure.status = AircraftStatus::Taxi;
+        departure.runway = Some("29".into());
+        sim.aircraft.write().unwrap().push(departure);
+
+        let mut messages = vec![];
+        for _ in 0..(120. / TIMESTEP) as u32 {
+            sim.tick(TIMESTEP);
+            for event in sim.drain_events() {
+                if let SimEvent::Pilot(callsign, message) = event {
+                    messages.push((callsign.coded(), message));
+                }
+            }
+        }
+        assert_eq!(
+            vec![
+                (
+                    "CYP2202".to_string(),
+                    PilotMessage::CheckIn {
+                        altitude: 1500,
+                        cleared: 1500,
+                        atis: 'A'
+                    }
+                ),
+                (
+                    "FDX261".to_string(),
+                    PilotMessage::ReadyForDeparture {
+                        runway: "29".into(),
+                        atis: 'A'
+                    }
+                ),
+                (
+                    "CYP2202".to_string(),
+                    PilotMessage::EstablishedLocalizer("29".into())
+                ),
+            ],
+            messages
+        );
+    }
+
+    /// Run until the first go around, returning its reason.
+    fn run_to_go_around(sim: &mut Simulation) -> Option<GoAroundReason> {
+        for _ in 0..(300. / TIMESTEP) as u32 {
+            sim.tick(TIMESTEP);
+            for event in sim.drain_events() {
+                if let SimEvent::GoAround(_, reason) = event {
+                    return Some(reason);
+                }
+            }
+        }
+        None
+    }
+
+    #[test]
+    fn test_simulation_go_around_without_clearance() {
+        let mut sim = approaching_aircraft(8000.0, 0.0, 285.0);
+        sim.aircraft.write().unwrap()[0].cleared_to_land = false;
+        assert_eq!(
+            Some(GoAroundReason::NoLandingClearance),
+            run_to_go_around(&mut sim)
+        );
+        {
+            let aircraft = &sim.aircraft.read().unwrap()[0];
+            assert_eq!(AircraftStatus::GoAround, aircraft.status);
+            assert_eq!(None, aircraft.approach);
+            assert_eq!(3000.0, aircraft.altitude.intended());
+            assert_eq!(285.0, aircraft.heading.intended());
+        }
+
+        // climbs out straight ahead, then back under radar control
+        run_for(&mut sim, 20.0);
+        assert_eq!(
+            AircraftStatus::GoAround,
+            sim.aircraft.read().unwrap()[0].status
+        );
+        run_for(&mut sim, 120.0);
+        let aircraft = &sim.aircraft.read().unwrap()[0];
+        assert_eq!(AircraftStatus::Flight, aircraft.status);
+        assert_eq!(3000.0, aircraft.altitude.current);
+        assert_eq!(None, aircraft.runway);
+    }
+
+    #[test]
+    fn test_simulation_go_around_runway_occupied() {
+        let mut sim = approaching_aircraft(8000.0, 0.0, 285.0);
+        let runway = &sim.airport.takeoff_runways[0];
+        let threshold = runway.threshold(sim.airport.origin(runway));
+        let mut departure = test_aircraft(glm::zero(), 0.0, 0.0);
+        departure.callsign = Callsign::from_string("CYP326".into()).unwrap();
+        departure.runway = Some("29".into());
+        departure.line_up(threshold, 285.0);
+        sim.aircraft.write().unwrap().push(departure);
+
+        assert_eq!(
+            Some(GoAroundReason::RunwayOccupied),
+            run_to_go_around(&mut sim)
+        );
+        let aircraft = sim.aircraft.read().unwrap();
+        assert_eq!(2, aircraft.len());
+        assert_eq!(AircraftStatus::GoAround, aircraft[0].status);
+        assert!(!aircraft[0].cleared_to_land());
+    }
+
+    #[test]
+    fn test_simulation_handed_off_arrival_lands() {
+        let mut sim = approaching_aircraft(8000.0, 0.0, 285.0);
+        sim.sector.units = vec![Unit::new("TWR", "119.9")];
+        let callsign = Callsign::from_string("CYP2202".into()).unwrap();
+        run_for(&mut sim, 30.0);
+        assert_eq!(
+            AircraftStatus::Landing,
+            sim.aircraft.read().unwrap()[0].status
+        );
+        // cleared to land by the tower, not us
+        sim.aircraft.write().unwrap()[0].cleared_to_land = false;
+        sim.command(
+            &callsign,
+            AtcCommand::Handoff {
+                unit: "TWR".into(),
+                frequency: None,
+            },
+        )
+        .unwrap();
+
+        assert_eq!(None, run_to_go_around(&mut sim));
+        assert!(sim.aircraft.read().unwrap().is_empty());
+        assert_eq!(
+            vec![(Item::Landing, 1, 100), (Item::Handoff, 1, 50)],
+            sim.score.breakdown()
+        );
+    }
+
+    #[test]
+    fn test_simulation_go_around_instructed() {
+        let mut sim = approaching_aircraft(16000.0, 2000.0, 255.0);
+        sim.airport.landing_runways[0].missed_approach = MissedApproach {
+            altitude: 4000,
+            route: MissedApproachRoute::Fix("KOLOS".into()),
+        };
+        sim.fixes.insert(Fix {
+            name: "KOLOS".into(),
+            position: glm::vec2(-20000., 0.),
+        });
+        let callsign = Callsign::from_string("CYP2202".into()).unwrap();
+
+        // not on an approach
+        let mut aircraft = test_aircraft(glm::zero(), 0.0, 5000.0);
+        assert_eq!(
+            AtcReply::Unable(AtcCommand::GoAround, "not on an approach".into()),
+            aircraft.command(AtcRequest(AtcCommand::GoAround))
+        );
+        assert!(!aircraft.go_around_instructed);
+
+        fly_intercept(&mut sim, 2000.0);
+        sim.command(&callsign, AtcCommand::GoAround).unwrap();
+        sim.tick(TIMESTEP);
+        {
+            let aircraft = &sim.aircraft.read().unwrap()[0];
+            assert_eq!(AircraftStatus::GoAround, aircraft.status);
+            assert_eq!(Some("KOLOS".into()), aircraft.direct);
+            assert_eq!(4000.0, aircraft.altitude.intended());
+        }
+        assert!(sim.drain_events().contains(&SimEvent::GoAround(
+            callsign.clone(),
+            GoAroundReason::Instructed
+        )));
+        assert_eq!(vec![(Item::GoAround, 1, -50)], sim.score.breakdown());
+
+        // cleared for the approach again
+        run_for(&mut sim, 30.0);
+        sim.command(&callsign, AtcCommand::ClearedForApproach("29".into()))
+            .unwrap();
+        let aircraft = &sim.aircraft.read().unwrap()[0];
+        assert_eq!(AircraftStatus::Flight, aircraft.status);
+        assert_eq!(Some(ApproachState::LocalizerArmed), aircraft.approach);
+        assert_eq!(Some("29".into()), aircraft.runway);
+    }
+
+    /// Altitude passing a fix 13km ahead, descending from 8000ft to 3000ft.
+    fn altitude_at_fix(commands: &[AtcCommand]) -> f32 {
+        let mut sim = Simulation::new(
+            test_airport(),
+            vec![test_aircraft(glm::vec2(0., -13000.), 0.0, 8000.0)],
+        );
+        sim.fixes.insert(Fix {
+            name: "KOLOS".into(),
+            position: glm::zero(),
+        });
+        let callsign = Callsign::from_string("CYP2202".into()).unwrap();
+        for command in commands {
+            sim.command(&callsign, command.clone()).unwrap();
+        }
+        while sim.aircraft.read().unwrap()[0].position.y < 0. {
+            sim.tick(TIMESTEP);
+        }
+        let altitude = sim.aircraft.read().unwrap()[0].altitude.current;
+        altitude
+    }
+
+    #[test]
+    fn test_simulation_reach_by_fix() {
+        // not quite down at the usual rate
+        assert!(altitude_at_fix(&[AtcCommand::ChangeAltitude(3000)]) > 3500.);
+        assert_eq!(
+            3000.,
+            altitude_at_fix(&[
+                AtcCommand::ChangeAltitude(3000),
+                AtcCommand::ReachBy("KOLOS".into())
+            ])
+        );
+    }
+
+    #[test]
+    fn test_simulation_conditional_instructions() {
+        let mut sim = Simulation::new(
+            test_airport(),
+            vec![test_aircraft(glm::vec2(0., -20000.), 0.0, 5000.0)],
+        );
+        // west, after the turn
+        let kolos = glm::vec2(-25000., -16000.);
+        sim.fixes.insert(Fix {
+            name: "KOLOS".into(),
+            position: kolos,
+        });
+        let callsign = Callsign::from_string("CYP2202".into()).unwrap();
+        let conditional = |condition, command| AtcCommand::Conditional {
+            condition,
+            command: Box::new(command),
+        };
+        sim.command(&callsign, AtcCommand::ChangeAltitude(8000))
+            .unwrap();
+        sim.command(
+            &callsign,
+            conditional(
+                Condition::Altitude(6000),
+                AtcCommand::ChangeHeadingWithTurnDirection(270, TurnDirection::Left),
+            ),
+        )
+        .unwrap();
+        sim.command(
+            &callsign,
+            conditional(
+                Condition::FixDistance("KOLOS".into(), 5.),
+                AtcCommand::ChangeSpeed(180),
+            ),
+        )
+        .unwrap();
+        assert_eq!(2, sim.aircraft.read().unwrap()[0].pending.len());
+
+        // still below 6000ft
+        run_for(&mut sim, 20.0);
+        {
+            let aircraft = &sim.aircraft.read().unwrap()[0];
+            assert_eq!(0.0, aircraft.heading.intended());
+            assert_eq!(2, aircraft.pending.len());
+        }
+
+        run_for(&mut sim, 10.0);
+        {
+            let aircraft = &sim.aircraft.read().unwrap()[0];
+            assert!(aircraft.altitude.current >= 6000.0);
+            assert_eq!(270.0, aircraft.heading.intended());
+            assert_eq!(1, aircraft.pending.len());
+            assert_eq!(200.0, aircraft.speed.intended());
+        }
+
+        // then within 5nm of the fix
+        for _ in 0..(180. / TIMESTEP) as u32 {
+            if sim.aircraft.read().unwrap()[0].pending.is_empty() {
+                break;
+            }
+            sim.tick(TIMESTEP);
+        }
+        let aircraft = &sim.aircraft.read().unwrap()[0];
+        assert!(aircraft.pending.is_empty());
+        assert_eq!(180.0, aircraft.speed.intended());
+        let distance =
+            glm::length(&(kolos - aircraft.position)) / (crate::units::NM_to_KM as f32 * 1000.);
+        assert!((distance - 5.0).abs() < 0.05, "{}", distance);
+    }
+
+    #[test]
+    fn test_simulation_direct_to_fix() {
+        let mut sim = Simulation::new(
+            test_airport(),
+            vec![test_aircraft(glm::zero(), 0.0, 4000.0)],
+        );
+        sim.wind = Wind::new(vec![WindLayer {
+            ceiling: 10000,
+            direction: 360,
+            speed: 30,
+        }]);
+        sim.fixes.insert(Fix {
+            name: "KOLOS".into(),
+            position: glm::vec2(-15_000., -10_000.),
+        });
+        let callsign = Callsign::from_string("CYP2202".into()).unwrap();
+        sim.command(&callsign, AtcCommand::DirectTo("KOLOS".into()))
+            .unwrap();
+
+        // keeps re-tracking the fix, passing overhead despite the wind
+        let mut closest = f32::MAX;
+        for _ in 0..300 {
+            run_for(&mut sim, 1.0);
+            let aircraft = &sim.aircraft.read().unwrap()[0];
+            closest = closest.min(glm::distance(
+                &aircraft.position,
+                &glm::vec2(-15_000., -10_000.),
+            ));
+        }
+        assert!(closest < 1000., "{}", closest);
+
+        // then carries on along its heading
+        let aircraft = &sim.aircraft.read().unwrap()[0];
+        assert_eq!(None, aircraft.direct);
+        assert_eq!(aircraft.heading.intended(), aircraft.heading.current);
+    }
+
+    /// Fly a right hand hold at a fix at the origin, inbound north, from
+    /// 15km away on `heading`. Returns the entry flown, the distances from
+    /// the fix each time round and the furthest the aircraft went.
+    fn fly_hold(heading: f32, wind: Wind) -> (HoldEntry, Vec<f32>, f32) {
+        let position = -heading_to_point(heading as i32) * 15_000.;
+        let mut sim = Simulation::new(
+            test_airport(),
+            vec![test_aircraft(position, heading, 5000.0)],
+        );
+        sim.wind = wind;
+        sim.fixes.insert(Fix {
+            name: "KOLOS".into(),
+            position: glm::zero(),
+        });
+        let callsign = Callsign::from_string("CYP2202".into()).unwrap();
+        sim.command(
+            &callsign,
+            AtcCommand::Hold {
+                fix: "KOLOS".into(),
+                course: 360,
+                direction: TurnDirection::Right,
+                leg_time: None,
+            },
+        )
+        .unwrap();
+
+        let mut entry = None;
+        let mut laps = Vec::new();
+        let mut furthest: f32 = 0.;
+        let mut previous = HoldState::Entering;
+        for _ in 0..(25. * 60. / TIMESTEP) as u32 {
+            sim.tick(TIMESTEP);
+            let aircraft = &sim.aircraft.read().unwrap()[0];
+            let state = aircraft.hold.as_ref().unwrap().state;
+            let distance = glm::length(&aircraft.position);
+            match (previous, state) {
+                (HoldState::Entering, HoldState::Outbound(_)) => entry = Some(HoldEntry::Direct),
+                (HoldState::Entering, HoldState::Entry(e, _)) => entry = Some(e),
+                (HoldState::Inbound(_), HoldState::Outbound(_)) => laps.push(distance),
+                _ => {}
+            }
+            if entry.is_some() {
+                furthest = furthest.max(distance);
+            }
+            previous = state;
+        }
+        (entry.unwrap(), laps, furthest)
+    }
+
+    #[test]
+    fn test_simulation_hold_entries() {
+        for (heading, expected) in [
+            (0., HoldEntry::Direct),
+            (150., HoldEntry::Teardrop),
+            (240., HoldEntry::Parallel),
+        ] {
+            let (entry, laps, furthest) = fly_hold(heading, Wind::default());
+            assert_eq!(expected, entry);
+            // nearly five minutes round at a minute a leg
+            assert!(laps.len() >= 3, "{:?} {:?}", entry, laps);
+            assert!(laps.iter().all(|&d| d < 1000.), "{:?} {:?}", entry, laps);
+            // a minute's flying and a turn's width from the fix
+            assert!(furthest < 12_000., "{:?} {}", entry, furthest);
+        }
+    }
+
+    #[test]
+    fn test_simulation_hold_wind() {
+        let wind = Wind::new(vec![WindLayer {
+            ceiling: 10000,
+            direction: 270,
+            speed: 30,
+        }]);
+        let (entry, laps, furthest) = fly_hold(0., wind);
+        assert_eq!(HoldEntry::Direct, entry);
+        assert!(laps.len() >= 3, "{:?}", laps);
+        // blown off the inbound course on the way round, but back on it by
+        // the fix
+        assert!(laps.iter().all(|&d| d < 1000.), "{:?}", laps);
+        assert!(furthest < 14_000., "{}", furthest);
+    }
+
+    #[test]
+    fn test_simulation_hold_cleared_onward() {
+        let mut aircraft = test_aircraft(glm::zero(), 0.0, 5000.0);
+        aircraft.command(AtcRequest(AtcCommand::Hold {
+            fix: "KOLOS".into(),
+            course: 90,
+            direction: TurnDirection::Left,
+            leg_time: Some(1.5),
+        }));
+        let hold = aircraft.hold.as_ref().unwrap();
+        assert_eq!(90., hold.leg_time);
+        assert_eq!(HoldState::Entering, hold.state);
+
+        aircraft.command(AtcRequest(AtcCommand::DirectTo("PHA".into())));
+        assert!(aircraft.hold.is_none());
+        assert_eq!(Some("PHA".into()), aircraft.direct);
+
+        // standard legs are longer up high
+        aircraft.altitude = AircraftParameter::new(20000.);
+        aircraft.command(AtcRequest(AtcCommand::Hold {
+            fix: "KOLOS".into(),
+            course: 90,
+            direction: TurnDirection::Left,
+            leg_time: None,
+        }));
+        assert_eq!(90., aircraft.hold.as_ref().unwrap().leg_time);
+        assert!(aircraft.direct.is_none());
+
+        aircraft.command(AtcRequest(AtcCommand::ChangeHeading(180)));
+        assert!(aircraft.hold.is_none());
+    }
+
+    fn sector_simulation(aircraft: Aircraft) -> Simulation {
+        let mut sim = Simulation::new(test_airport(), vec![aircraft]);
+        sim.sector = Sector {
+            airspace: Some(test_airspace(AirspaceClass::Sector, f32::INFINITY)),
+            units: vec![Unit::new("CTR", "128.4"), Unit::new("TWR", "119.9")],
+        };
+        sim
+    }
+
+    /// 20km square around the airport, from the ground up to `ceiling` feet.
+    fn test_airspace(class: AirspaceClass, ceiling: f32) -> Airspace {
+        Airspace {
+            name: "LCR1".into(),
+            class,
+            boundary: vec![
+                glm::vec2(-10_000., -10_000.),
+                glm::vec2(10_000., -10_000.),
+                glm::vec2(10_000., 10_000.),
+                glm::vec2(-10_000., 10_000.),
+            ],
+            floor: 0.,
+            ceiling,
+        }
+    }
+
+    fn missed_handoffs(sim: &mut Simulation) -> Vec<MissedHandoffReason> {
+        sim.drain_events()
+            .into_iter()
+            .filter_map(|event| match event {
+                SimEvent::MissedHandoff(_, reason) => Some(reason),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_simulation_inbound_handoff() {
+        let mut inbound = test_aircraft(glm::vec2(0., 14_000.), 180., 5000.);
+        inbound.status = AircraftStatus::Inbound;
+        let callsign = inbound.callsign.clone();
+
+        let mut sim = sector_simulation(inbound.clone());
+        sim.tick(TIMESTEP);
+        assert_eq!(
+            vec![SimEvent::HandoffOffered(callsign.clone())],
+            sim.drain_events()
+        );
+        run_for(&mut sim, 60.);
+        assert_eq!(
+            vec![MissedHandoffReason::NotAccepted],
+            missed_handoffs(&mut sim)
+        );
+        assert_eq!(
+            AircraftStatus::Flight,
+            sim.aircraft.read().unwrap()[0].status
+        );
+
+        let mut sim = sector_simulation(inbound);
+        run_for(&mut sim, 10.);
+        assert!(sim.command(&callsign, AtcCommand::AcceptHandoff).is_ok());
+        run_for(&mut sim, 50.);
+        let events = sim.drain_events();
+        assert!(!events
+            .iter()
+            .any(|event| matches!(event, SimEvent::MissedHandoff(..))));
+        // calls once accepted
+        assert!(events
+            .iter()
+            .any(|event| matches!(event, SimEvent::Pilot(_, PilotMessage::CheckIn { .. }))));
+    }
+
+    #[test]
+    fn test_simulation_outbound_handoff() {
+        let outbound = test_aircraft(glm::vec2(0., 9_000.), 0., 5000.);
+        let callsign = outbound.callsign.clone();
+        let handoff = AtcCommand::Handoff {
+            unit: "CTR".into(),
+            frequency: None,
+        };
+
+        let mut sim = sector_simulation(outbound.clone());
+        assert!(sim.command(&callsign, handoff).is_ok());
+        assert_eq!(
+            AircraftStatus::HandedOff,
+            sim.aircraft.read().unwrap()[0].status
+        );
+        run_for(&mut sim, 5.);
+        assert_eq!(1, sim.aircraft.read().unwrap().len());
+        // gone once out of the sector
+        run_for(&mut sim, 15.);
+        assert!(missed_handoffs(&mut sim).is_empty());
+        assert!(sim.aircraft.read().unwrap().is_empty());
+
+        let mut sim = sector_simulation(outbound);
+        run_for(&mut sim, 20.);
+        assert_eq!(
+            vec![MissedHandoffReason::LeftSector],
+            missed_handoffs(&mut sim)
+        );
+        assert!(sim.aircraft.read().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_simulation_restricted_area() {
+        let infringements = |aircraft: Aircraft, class| {
+            let mut sim = Simulation::new(test_airport(), vec![aircraft]);
+            sim.airspaces = vec![test_airspace(class, 6000.)];
+            run_for(&mut sim, 20.);
+            sim.drain_events()
+                .into_iter()
+                .filter(|event| matches!(event, SimEvent::Infringement(..)))
+                .collect::<Vec<_>>()
+        };
+        let aircraft = test_aircraft(glm::vec2(0., 11_000.), 180., 5000.);
+        assert_eq!(
+            vec![SimEvent::Infringement(
+                aircraft.callsign.clone(),
+                "LCR1".into()
+            )],
+            infringements(aircraft.clone(), AirspaceClass::Restricted)
+        );
+        // only restricted areas
+        assert!(infringements(aircraft, AirspaceClass::Danger).is_empty());
+        // above it
+        let above = test_aircraft(glm::vec2(0., 11_000.), 180., 7000.);
+        assert!(infringements(above, AirspaceClass::Restricted).is_empty());
+        // not ours
+        let mut inbound = test_aircraft(glm::vec2(0., 11_000.), 180., 5000.);
+        inbound.status = AircraftStatus::Inbound;
+        assert!(infringements(inbound, AirspaceClass::Restricted).is_empty());
+    }
+
+    #[test]
+    fn test_simulation_terrain() {
+        // towards the ridge
+        let aircraft = test_aircraft(glm::zero(), 90., 2000.);
+        let callsign = aircraft.callsign.clone();
+        let mut sim = Simulation::new(test_airport(), vec![aircraft]);
+        sim.terrain = Some(test_terrain());
+
+        sim.tick(TIMESTEP);
+        assert_eq!(1, sim.terrain_warnings.len());
+        run_for(&mut sim, 120.);
+        let events: Vec<SimEvent> = sim
+            .drain_events()
+            .into_iter()
+            .filter(|event| matches!(event, SimEvent::TerrainAlert(_) | SimEvent::Crashed(_)))
+            .collect();
+        assert!(matches!(
+            events.as_slice(),
+            [
+                SimEvent::TerrainAlert(predicted),
+                SimEvent::TerrainAlert(low),
+                SimEvent::Crashed(crashed),
+            ] if !predicted.is_low() && low.is_low() && *crashed == callsign
+        ));
+        assert!(sim.aircraft.read().unwrap().is_empty());
+        assert!(sim.terrain_warnings.is_empty());
+    }
+
+    #[test]
+    fn test_simulation_removes_landed_aircraft() {
+        let mut aircraft = test_aircraft(glm::zero(), 105.0, 0.0);
+        aircraft.command(AtcRequest(AtcCommand::ClearedToLand(Some("29".into()))));
+        let mut sim = Simulation::new(test_airport(), vec![aircraft]);
+        sim.tick(TIMESTEP);
+
+        assert!(sim.aircraft.read().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,238 @@
+//! Short term conflict alert, predicts losses of separation between pairs of
+//! aircraft from their current heading and speed.
+
+use crate::aircraft::{Aircraft, Callsign};
+use crate::units;
+
+/// 3nm, in meters
+pub const LATERAL_SEPARATION: f32 = 3. * units::NM_to_KM as f32 * 1000.;
+/// feet
+pub const VERTICAL_SEPARATION: f32 = 1000.;
+/// How far ahead conflicts are predicted, in seconds.
+pub const LOOKAHEAD: f32 = 120.;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Conflict {
+    pub callsigns: (Callsign, Callsign),
+    /// seconds until the closest point of approach
+    pub time_to_cpa: f32,
+    /// lateral distance at the closest point of approach, in meters
+    pub cpa_distance: f32,
+    /// feet
+    pub vertical_distance: f32,
+    /// separation has already been lost, instead of being predicted to
+    pub is_loss: bool,
+}
+
+impl Conflict {
+    pub fn involves(&self, callsign: &Callsign) -> bool {
+        self.callsigns.0 == *callsign || self.callsigns.1 == *callsign
+    }
+
+    pub fn is_same_pair(&self, other: &Conflict) -> bool {
+        self.involves(&other.callsigns.0) && self.involves(&other.callsigns.1)
+    }
+}
+
+impl std::fmt::Display for Conflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let distance = self.cpa_distance / 1000. * units::KM_TO_NM as f32;
+        if self.is_loss {
+            write!(
+                f,
+                "{}/{} loss of separation, {:.1}NM {}FT",
+                self.callsigns.0, self.callsigns.1, distance, self.vertical_distance as u32
+            )
+        } else {
+            write!(
+                f,
+                "{}/{} conflict in {}s, {:.1}NM {}FT",
+                self.callsigns.0,
+                self.callsigns.1,
+                self.time_to_cpa.round(),
+                distance,
+                self.vertical_distance as u32
+            )
+        }
+    }
+}
+
+/// Time until, and distance at, the closest point of approach of two
+/// points moving at constant velocity. The time is limited to the
+/// lookahead, diverging points are closest now.
+pub fn closest_point_of_approach(
+    p1: glm::Vec2,
+    v1: glm::Vec2,
+    p2: glm::Vec2,
+    v2: glm::Vec2,
+) -> (f32, f32) {
+    let dp = p2 - p1;
+    let dv = v2 - v1;
+    let dv2 = glm::dot(&dv, &dv);
+
+    let t = if dv2 > f32::EPSILON {
+        (-glm::dot(&dp, &dv) / dv2).clamp(0., LOOKAHEAD)
+    } else {
+        // same velocity, distance never changes
+        0.
+    };
+    (t, glm::length(&(dp + dv * t)))
+}
+
+/// All pairs of airborne aircraft that have lost, or are predicted to lose,
+/// separation within the lookahead time.
+pub fn conflicts(aircraft: &[Aircraft]) -> Vec<Conflict> {
+    let mut conflicts = Vec::new();
+    let airborne: Vec<&Aircraft> = aircraft.iter().filter(|a| !a.is_grounded()).collect();
+
+    for (i, a) in airborne.iter().enumerate() {
+        for b in &airborne[i + 1..] {
+            let vertical_distance = (a.altitude.current - b.altitude.current).abs();
+            if vertical_distance >= VERTICAL_SEPARATION {
+                continue;
+            }
+
+            let (time_to_cpa, cpa_distance) =
+                closest_point_of_approach(a.position, a.velocity(), b.position, b.velocity());
+            let is_loss = glm::distance(&a.position, &b.position) < LATERAL_SEPARATION;
+
+            if is_loss || cpa_distance < LATERAL_SEPARATION {
+                conflicts.push(Conflict {
+                    callsigns: (a.callsign.clone(), b.callsign.clone()),
+                    time_to_cpa,
+                    cpa_distance,
+                    vertical_distance,
+                    is_loss,
+                });
+            }
+        }
+    }
+    conflicts
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::aircraft::{test_aircraft, AircraftParameter};
+
+    const NM: f32 = units::NM_to_KM as f32 * 1000.;
+
+    /// At 240kt.
+    fn aircraft(callsign: &str, position: glm::Vec2, heading: f32, altitude: f32) -> Aircraft {
+        let mut aircraft = test_aircraft(position, heading, altitude);
+        aircraft.callsign = Callsign::from_string(callsign.into()).unwrap();
+        aircraft.speed = AircraftParameter::new(240.);
+        aircraft
+    }
+
+    #[test]
+    fn test_closest_point_of_approach() {
+        // head on, 10 units apart closing at 2 units/s
+        let (t, d) = closest_point_of_approach(
+            glm::vec2(0., 0.),
+            glm::vec2(1., 0.),
+            glm::vec2(10., 0.),
+            glm::vec2(-1., 0.),
+        );
+        assert_eq!(5., t);
+        assert_eq!(0., d);
+
+        // diverging, closest now
+        let (t, d) = closest_point_of_approach(
+            glm::vec2(0., 0.),
+            glm::vec2(-1., 0.),
+            glm::vec2(10., 0.),
+            glm::vec2(1., 0.),
+        );
+        assert_eq!(0., t);
+        assert_eq!(10., d);
+
+        // same velocity
+        let (t, d) = closest_point_of_approach(
+            glm::vec2(0., 0.),
+            glm::vec2(1., 1.),
+            glm::vec2(0., 5.),
+            glm::vec2(1., 1.),
+        );
+        assert_eq!(0., t);
+        assert_eq!(5., d);
+    }
+
+    #[test]
+    fn test_conflicts_head_on() {
+        let conflicts = conflicts(&[
+            aircraft("CYP2202", glm::vec2(0., 0.), 90., 4000.),
+            aircraft("FDX261", glm::vec2(10. * NM, 0.), 270., 4500.),
+        ]);
+        assert_eq!(1, conflicts.len());
+
+        let conflict = &conflicts[0];
+        assert_eq!("CYP2202", conflict.callsigns.0.coded());
+        assert_eq!("FDX261", conflict.callsigns.1.coded());
+        assert!(!conflict.is_loss);
+        // 10nm closing at 480kt
+        assert_eq!(75., conflict.time_to_cpa.round());
+        assert!(conflict.cpa_distance < 100.);
+        assert_eq!(500., conflict.vertical_distance);
+    }
+
+    #[test]
+    fn test_conflicts_loss_of_separation() {
+        let conflicts = conflicts(&[
+            aircraft("CYP2202", glm::vec2(0., 0.), 0., 4000.),
+            aircraft("FDX261", glm::vec2(2. * NM, 0.), 0., 4000.),
+        ]);
+        assert_eq!(1, conflicts.len());
+        assert!(conflicts[0].is_loss);
+    }
+
+    #[test]
+    fn test_conflicts_vertically_separated() {
+        assert!(conflicts(&[
+            aircraft("CYP2202", glm::vec2(0., 0.), 90., 4000.),
+            aircraft("FDX261", glm::vec2(5. * NM, 0.), 270., 5000.),
+        ])
+        .is_empty());
+    }
+
+    #[test]
+    fn test_conflicts_laterally_separated() {
+        // parallel tracks 5nm apart
+        assert!(conflicts(&[
+            aircraft("CYP2202", glm::vec2(0., 0.), 0., 4000.),
+            aircraft("FDX261", glm::vec2(5. * NM, 0.), 0., 4000.),
+        ])
+        .is_empty());
+
+        // diverging
+        assert!(conflicts(&[
+            aircraft("CYP2202", glm::vec2(0., 0.), 270., 4000.),
+            aircraft("FDX261", glm::vec2(4. * NM, 0.), 90., 4000.),
+        ])
+        .is_empty());
+    }
+
+    #[test]
+    fn test_conflicts_beyond_lookahead() {
+        // head on, but 30nm apart
+        assert!(conflicts(&[
+            aircraft("CYP2202", glm::vec2(0., 0.), 90., 4000.),
+            aircraft("FDX261", glm::vec2(30. * NM, 0.), 270., 4000.),
+        ])
+        .is_empty());
+    }
+
+    #[test]
+    fn test_conflicts_multiple_pairs() {
+        let conflicts = conflicts(&[
+            aircraft("CYP2202", glm::vec2(0., 0.), 0., 4000.),
+            aircraft("FDX261", glm::vec2(1. * NM, 0.), 0., 4000.),
+            aircraft("TRA1112", glm::vec2(0., 1. * NM), 0., 4500.),
+            aircraft("WZZ3221", glm::vec2(0., 0.), 0., 8000.),
+        ]);
+        assert_eq!(3, conflicts.len());
+        assert!(conflicts
+            .iter()
+            .all(|c| !c.involves(&Callsign::from_string("WZZ3221".into()).unwrap())));
+    }
+}
@@ -0,0 +1,338 @@
+//! Ground elevation around the airport, from a grid of elevations at evenly
+//! spaced latitudes and longitudes.
+
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::error::impl_from;
+use crate::geo::LatLon;
+use crate::units;
+
+/// elevation of a void in an SRTM tile, where there was no data
+const HGT_VOID: i16 = -32768;
+
+#[derive(Debug)]
+pub enum TerrainError {
+    Io(std::io::Error),
+    Invalid(&'static str),
+}
+
+impl std::fmt::Display for TerrainError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "failed to read terrain: {}", e),
+            Self::Invalid(reason) => write!(f, "invalid terrain: {}", reason),
+        }
+    }
+}
+
+impl_from!(TerrainError {
+    Io(std::io::Error),
+});
+
+/// Elevations as read from a file, before projecting into the game world.
+#[derive(Clone, Debug, PartialEq)]
+struct Grid {
+    /// degrees, of the southernmost row
+    south: f64,
+    /// degrees, of the westernmost column
+    west: f64,
+    /// degrees between rows and between columns
+    spacing: f64,
+    columns: usize,
+    rows: usize,
+    /// meters, rows from north to south
+    elevations: Vec<f32>,
+}
+
+impl Grid {
+    /// An SRTM tile, named after its south west corner, e.g. "N34E032".
+    fn from_hgt(name: &str, bytes: &[u8]) -> Result<Self, TerrainError> {
+        let degrees = |s: &str, positive: char, negative: char| {
+            let sign = match s.chars().next() {
+                Some(c) if c == positive => 1.,
+                Some(c) if c == negative => -1.,
+                _ => return None,
+            };
+            s[1..].parse::<f64>().ok().map(|degrees| sign * degrees)
+        };
+        let name = name.to_uppercase();
+        let corner = match (name.is_ascii() && name.len() == 7).then(|| name.split_at(3)) {
+            Some((latitude, longitude)) => {
+                degrees(latitude, 'N', 'S').zip(degrees(longitude, 'E', 'W'))
+            }
+            None => None,
+        };
+        let (south, west) = corner.ok_or(TerrainError::Invalid("tile name isn't a corner"))?;
+
+        let size = ((bytes.len() / 2) as f64).sqrt() as usize;
+        if size < 2 || size * size * 2 != bytes.len() {
+            return Err(TerrainError::Invalid("tile isn't square"));
+        }
+        let elevations = bytes
+            .chunks_exact(2)
+            .map(|bytes| match i16::from_be_bytes([bytes[0], bytes[1]]) {
+                HGT_VOID => 0.,
+                elevation => elevation as f32,
+            })
+            .collect();
+        Ok(Self {
+            south,
+            west,
+            spacing: 1. / (size - 1) as f64,
+            columns: size,
+            rows: size,
+            elevations,
+        })
+    }
+}
+
+impl FromStr for Grid {
+    type Err = TerrainError;
+
+    /// An ESRI ASCII grid, the header giving the size and lower left corner
+    /// or center of the grid before its rows.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason| Err(TerrainError::Invalid(reason));
+        let mut lines = s.lines().filter(|line| !line.trim().is_empty()).peekable();
+        let mut header = std::collections::HashMap::new();
+        while let Some(line) = lines.next_if(|line| {
+            line.trim_start()
+                .starts_with(|c: char| c.is_ascii_alphabetic())
+        }) {
+            let mut parts = line.split_whitespace();
+            match (
+                parts.next(),
+                parts.next().and_then(|v| v.parse::<f64>().ok()),
+            ) {
+                (Some(key), Some(value)) => header.insert(key.to_lowercase(), value),
+                _ => return invalid("invalid header"),
+            };
+        }
+
+        let get = |key: &str| header.get(key).copied();
+        let (columns, rows, spacing) = match (get("ncols"), get("nrows"), get("cellsize")) {
+            (Some(columns), Some(rows), Some(spacing)) => {
+                (columns as usize, rows as usize, spacing)
+            }
+            _ => return invalid("missing size"),
+        };
+        if columns < 2 || rows < 2 || spacing <= 0. {
+            return invalid("needs at least two rows and columns");
+        }
+        // corners are of the cells around the grid points
+        let (west, south) = match (
+            get("xllcenter").zip(get("yllcenter")),
+            get("xllcorner").zip(get("yllcorner")),
+        ) {
+            (Some(center), _) => center,
+            (None, Some((west, south))) => (west + spacing / 2., south + spacing / 2.),
+            (None, None) => return invalid("missing lower left corner"),
+        };
+
+        let no_data = get("nodata_value");
+        let elevations = lines
+            .flat_map(|line| line.split_whitespace())
+            .map(|value| match value.parse::<f64>() {
+                Ok(elevation) if Some(elevation) == no_data => Ok(0.),
+                Ok(elevation) => Ok(elevation as f32),
+                Err(_) => Err(TerrainError::Invalid("invalid elevation")),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if elevations.len() != columns * rows {
+            return invalid("wrong number of elevations");
+        }
+        Ok(Self {
+            south,
+            west,
+            spacing,
+            columns,
+            rows,
+            elevations,
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Terrain {
+    /// meters east and north of the airport reference point, of the south
+    /// west grid point
+    south_west: glm::Vec2,
+    /// meters east and north between grid points
+    spacing: glm::Vec2,
+    columns: usize,
+    rows: usize,
+    /// feet, rows from south to north
+    elevations: Vec<f32>,
+}
+
+impl Terrain {
+    /// Load an ESRI ASCII grid, or an SRTM tile with the `.hgt` extension,
+    /// projected into the game world around `origin`.
+    pub fn load<P: AsRef<Path>>(path: P, origin: &LatLon) -> Result<Self, TerrainError> {
+        let path = path.as_ref();
+        let grid = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("hgt") => {
+                let name = path.file_stem().and_then(|stem| stem.to_str());
+                Grid::from_hgt(name.unwrap_or_default(), &std::fs::read(path)?)?
+            }
+            _ => return Self::from_ascii_grid(&std::fs::read_to_string(path)?, origin),
+        };
+        Ok(Self::new(grid, origin))
+    }
+
+    pub fn from_ascii_grid(s: &str, origin: &LatLon) -> Result<Self, TerrainError> {
+        Ok(Self::new(s.parse()?, origin))
+    }
+
+    fn new(grid: Grid, origin: &LatLon) -> Self {
+        let north = grid.south + grid.spacing * (grid.rows - 1) as f64;
+        let east = grid.west + grid.spacing * (grid.columns - 1) as f64;
+        let middle = LatLon::new((grid.south + north) / 2., (grid.west + east) / 2.);
+        let width = LatLon::new(middle.latitude(), grid.west)
+            .distance(&LatLon::new(middle.latitude(), east));
+        let height = LatLon::new(grid.south, middle.longitude())
+            .distance(&LatLon::new(north, middle.longitude()));
+        let south_west = LatLon::new(grid.south, grid.west).to_game_world(origin);
+
+        Self {
+            south_west: glm::vec2(south_west.x, south_west.y),
+            spacing: glm::vec2(
+                (width / (grid.columns - 1) as f64) as f32,
+                (height / (grid.rows - 1) as f64) as f32,
+            ),
+            columns: grid.columns,
+            rows: grid.rows,
+            elevations: grid
+                .elevations
+                .chunks(grid.columns)
+                .rev()
+                .flatten()
+                .map(|meters| meters / units::FT_TO_M as f32)
+                .collect(),
+        }
+    }
+
+    /// Feet at `position`, between the grid points around it, and at sea
+    /// level off the grid.
+    pub fn elevation(&self, position: &glm::Vec2) -> f32 {
+        let offset = position - self.south_west;
+        let column = offset.x / self.spacing.x;
+        let row = offset.y / self.spacing.y;
+        let is_off_grid = column < 0.
+            || row < 0.
+            || column > (self.columns - 1) as f32
+            || row > (self.rows - 1) as f32;
+        if is_off_grid {
+            return 0.;
+        }
+
+        // the cell it's in, and how far across it
+        let c = (column as usize).min(self.columns - 2);
+        let r = (row as usize).min(self.rows - 2);
+        let (x, y) = (column - c as f32, row - r as f32);
+        let at = |c: usize, r: usize| self.elevations[r * self.columns + c];
+        let south = at(c, r) * (1. - x) + at(c + 1, r) * x;
+        let north = at(c, r + 1) * (1. - x) + at(c + 1, r + 1) * x;
+        south * (1. - y) + north * y
+    }
+}
+
+/// Flat ground around an airport at 35N 32E, rising to a 1000m ridge 9km
+/// east of it.
+#[cfg(test)]
+pub fn test_terrain() -> Terrain {
+    let grid = "\
+ncols 3
+nrows 3
+xllcenter 31.9
+yllcenter 34.9
+cellsize 0.1
+0 0 1000
+0 0 1000
+0 0 1000
+";
+    Terrain::from_ascii_grid(grid, &LatLon::new(35., 32.)).unwrap()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const GRID: &str = "\
+ncols 3
+nrows 2
+xllcorner 31.995
+yllcorner 34.995
+cellsize 0.01
+NODATA_value -9999
+100 200 -9999
+0 100 400
+";
+
+    #[test]
+    fn test_ascii_grid() {
+        let grid: Grid = GRID.parse().unwrap();
+        assert_eq!((3, 2), (grid.columns, grid.rows));
+        assert_eq!(35., (grid.south * 1e6).round() / 1e6);
+        assert_eq!(32., (grid.west * 1e6).round() / 1e6);
+        assert_eq!(vec![100., 200., 0., 0., 100., 400.], grid.elevations);
+
+        let centered: Grid = GRID
+            .replace("xllcorner 31.995", "xllcenter 32")
+            .replace("yllcorner 34.995", "yllcenter 35")
+            .parse()
+            .unwrap();
+        assert_eq!((35., 32.), (centered.south, centered.west));
+
+        let error = |s: String| s.parse::<Grid>().unwrap_err().to_string();
+        assert_eq!(
+            "invalid terrain: wrong number of elevations",
+            error(GRID.replace("0 100 400", "0 100"))
+        );
+        assert_eq!(
+            "invalid terrain: invalid elevation",
+            error(GRID.replace("0 100 400", "0 100 high"))
+        );
+        assert_eq!(
+            "invalid terrain: missing lower left corner",
+            error(GRID.replace("xllcorner", "xll"))
+        );
+    }
+
+    #[test]
+    fn test_hgt() {
+        let elevations: [i16; 9] = [0, 10, 20, 30, 40, 50, HGT_VOID, 70, 80];
+        let bytes: Vec<u8> = elevations.iter().flat_map(|e| e.to_be_bytes()).collect();
+        let grid = Grid::from_hgt("S34W032", &bytes).unwrap();
+        assert_eq!((-34., -32.), (grid.south, grid.west));
+        assert_eq!(0.5, grid.spacing);
+        assert_eq!(0., grid.elevations[6]);
+
+        assert!(matches!(
+            Grid::from_hgt("N34E032", &bytes[1..]),
+            Err(TerrainError::Invalid("tile isn't square"))
+        ));
+        assert!(matches!(
+            Grid::from_hgt("lcph", &bytes),
+            Err(TerrainError::Invalid("tile name isn't a corner"))
+        ));
+    }
+
+    #[test]
+    fn test_terrain_elevation() {
+        let origin = LatLon::new(35., 32.);
+        let terrain = Terrain::from_ascii_grid(GRID, &origin).unwrap();
+        let meters = |feet: f32| (feet * units::FT_TO_M as f32).round();
+        // at the grid points, the southern row given last
+        assert_eq!(0., meters(terrain.elevation(&glm::zero())));
+        let south_east = terrain.spacing.component_mul(&glm::vec2(2., 0.));
+        assert_eq!(400., meters(terrain.elevation(&south_east)));
+        // between them
+        let middle = terrain.spacing.component_mul(&glm::vec2(1.5, 0.5));
+        assert_eq!(175., meters(terrain.elevation(&middle)));
+        // sea level off the grid
+        assert_eq!(0., terrain.elevation(&glm::vec2(-100., 0.)));
+        assert_eq!(0., terrain.elevation(&(terrain.spacing * 3.)));
+    }
+}
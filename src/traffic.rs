@@ -0,0 +1,262 @@
+//! Arrivals entering the scenario as it runs, through its entry gates.
+
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+use crate::aircraft::{Aircraft, AircraftStatus, Callsign};
+use crate::performance::Performance;
+use crate::sector::Sector;
+
+/// meters around a gate that must be clear of traffic for an arrival to
+/// enter through it, 5nm
+const GATE_SEPARATION: f32 = 9_260.;
+
+/// Where arrivals enter, on the heading and at the speed given.
+#[derive(Clone, Debug)]
+pub struct Gate {
+    /// of the fix it's at
+    pub position: glm::Vec2,
+    /// lowest and highest altitude in feet, entered at a thousand feet
+    /// within it
+    pub altitudes: (u32, u32),
+    /// knots
+    pub speed: f32,
+    pub heading: f32,
+}
+
+#[derive(Clone, Debug)]
+pub struct Airline {
+    /// ICAO code, e.g. "CYP"
+    pub code: String,
+    /// spoken name, e.g. "Cyprus Airways"
+    pub name: String,
+    /// the aircraft types it flies
+    pub fleet: Vec<Performance>,
+    /// share of the arrivals, relative to the other airlines
+    pub weight: u32,
+}
+
+/// When arrivals are due.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Arrivals {
+    /// on average per hour, spaced between half and one and a half times
+    /// the average apart
+    Rate(f32),
+    /// seconds after the start
+    Schedule(Vec<f32>),
+}
+
+#[derive(Debug)]
+pub struct Traffic {
+    gates: Vec<Gate>,
+    airlines: Vec<Airline>,
+    arrivals: Arrivals,
+    rng: StdRng,
+    /// seconds since the start
+    elapsed: f32,
+    /// seconds after the start the next arrival is due, if any
+    next: Option<f32>,
+    /// arrivals so far
+    count: usize,
+}
+
+impl Traffic {
+    /// Arrivals through `gates`, from `airlines`, the same for every `seed`.
+    pub fn new(gates: Vec<Gate>, airlines: Vec<Airline>, arrivals: Arrivals, seed: u64) -> Self {
+        let mut traffic = Self {
+            gates,
+            airlines,
+            arrivals,
+            rng: StdRng::seed_from_u64(seed),
+            elapsed: 0.,
+            next: None,
+            count: 0,
+        };
+        traffic.next = traffic.schedule_next();
+        traffic
+    }
+
+    fn schedule_next(&mut self) -> Option<f32> {
+        match &self.arrivals {
+            Arrivals::Rate(per_hour) => {
+                let interval = 3600. / per_hour * self.rng.gen_range(0.5..1.5);
+                Some(self.elapsed + interval)
+            }
+            Arrivals::Schedule(times) => times.get(self.count).copied(),
+        }
+    }
+
+    /// The arrival due after another `dt` seconds, if any, held back while
+    /// every gate has `existing` traffic near it. Arrivals from outside the
+    /// `sector` have to be handed off to us.
+    pub fn update(&mut self, dt: f32, existing: &[Aircraft], sector: &Sector) -> Option<Aircraft> {
+        self.elapsed += dt;
+        if !matches!(self.next, Some(due) if self.elapsed >= due) {
+            return None;
+        }
+        let clear: Vec<&Gate> = self
+            .gates
+            .iter()
+            .filter(|gate| {
+                existing
+                    .iter()
+                    .all(|a| glm::distance(&a.position, &gate.position) > GATE_SEPARATION)
+            })
+            .collect();
+        let gate = *clear.choose(&mut self.rng)?;
+
+        let weights = self.airlines.iter().map(|airline| airline.weight);
+        let airline = &self.airlines[WeightedIndex::new(weights).ok()?.sample(&mut self.rng)];
+        let performance = airline.fleet.choose(&mut self.rng)?.clone();
+        let callsign = loop {
+            let callsign = Callsign {
+                name: airline.name.clone(),
+                code: airline.code.clone(),
+                number: self.rng.gen_range(100..1000).to_string(),
+            };
+            if !existing.iter().any(|a| a.callsign == callsign) {
+                break callsign;
+            }
+        };
+        let (lowest, highest) = gate.altitudes;
+        let altitude = lowest + self.rng.gen_range(0..=(highest - lowest) / 1000) * 1000;
+
+        let mut aircraft = Aircraft::new(
+            callsign,
+            performance,
+            gate.position,
+            gate.heading,
+            altitude as f32,
+            gate.speed,
+        );
+        if !sector.contains(&gate.position, altitude as f32) {
+            aircraft.status = AircraftStatus::Inbound;
+        }
+        self.count += 1;
+        self.next = self.schedule_next();
+        Some(aircraft)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::airspace::{Airspace, AirspaceClass};
+    use crate::performance::test_performance;
+
+    fn test_traffic(arrivals: Arrivals, seed: u64) -> Traffic {
+        let gates = vec![
+            Gate {
+                position: glm::vec2(30_000., 0.),
+                altitudes: (8000, 12000),
+                speed: 250.,
+                heading: 270.,
+            },
+            Gate {
+                position: glm::vec2(0., -30_000.),
+                altitudes: (6000, 6000),
+                speed: 220.,
+                heading: 360.,
+            },
+        ];
+        let airlines = vec![
+            Airline {
+                code: "CYP".into(),
+                name: "Cyprus Airways".into(),
+                fleet: vec![test_performance("A320")],
+                weight: 3,
+            },
+            Airline {
+                code: "FDX".into(),
+                name: "Fedex".into(),
+                fleet: vec![test_performance("B752")],
+                weight: 1,
+            },
+        ];
+        Traffic::new(gates, airlines, arrivals, seed)
+    }
+
+    /// Arrivals over `duration` seconds in steps of a second, each left
+    /// where it entered.
+    fn run(traffic: &mut Traffic, duration: u32, sector: &Sector) -> Vec<(f32, Aircraft)> {
+        let mut arrivals: Vec<(f32, Aircraft)> = Vec::new();
+        for _ in 0..duration {
+            let existing: Vec<Aircraft> = arrivals.iter().map(|(_, a)| a.clone()).collect();
+            if let Some(aircraft) = traffic.update(1., &existing, sector) {
+                arrivals.push((traffic.elapsed, aircraft));
+            }
+        }
+        arrivals
+    }
+
+    #[test]
+    fn test_traffic_schedule() {
+        let mut traffic = test_traffic(Arrivals::Schedule(vec![60., 90.5, 120.]), 1);
+        let arrivals = run(&mut traffic, 600, &Sector::default());
+        let times: Vec<f32> = arrivals.iter().map(|(time, _)| *time).collect();
+        // the last held back, with both gates taken
+        assert_eq!(vec![60., 91.], times);
+
+        let (_, aircraft) = &arrivals[0];
+        assert_eq!(AircraftStatus::Flight, aircraft.status);
+        assert!(["CYP", "FDX"].contains(&aircraft.callsign.code.as_str()));
+        assert_eq!(3, aircraft.callsign.number.len());
+        assert!([8000., 9000., 10000., 11000., 12000., 6000.].contains(&aircraft.altitude.current));
+
+        // from outside the sector
+        let sector = Sector {
+            airspace: Some(Airspace {
+                name: "LCPH".into(),
+                class: AirspaceClass::Sector,
+                boundary: vec![
+                    glm::vec2(-20_000., -20_000.),
+                    glm::vec2(20_000., -20_000.),
+                    glm::vec2(20_000., 20_000.),
+                    glm::vec2(-20_000., 20_000.),
+                ],
+                floor: 0.,
+                ceiling: f32::INFINITY,
+            }),
+            units: vec![],
+        };
+        let mut traffic = test_traffic(Arrivals::Schedule(vec![1.]), 1);
+        let arrivals = run(&mut traffic, 10, &sector);
+        assert_eq!(AircraftStatus::Inbound, arrivals[0].1.status);
+    }
+
+    #[test]
+    fn test_traffic_rate() {
+        let arrivals = |seed| {
+            run(
+                &mut test_traffic(Arrivals::Rate(30.), seed),
+                3600,
+                &Sector::default(),
+            )
+            .into_iter()
+            .map(|(time, aircraft)| (time, aircraft.callsign.coded()))
+            .collect::<Vec<_>>()
+        };
+        // gates stay blocked by the arrivals left on them
+        let first = arrivals(7);
+        assert_eq!(2, first.len());
+        assert!((60. ..=180.).contains(&first[0].0), "{:?}", first);
+        // repeatable
+        assert_eq!(first, arrivals(7));
+        assert_ne!(first, arrivals(8));
+    }
+
+    #[test]
+    fn test_traffic_airline_mix() {
+        let mut traffic = test_traffic(Arrivals::Rate(3600.), 3);
+        let cyprus = (0..400)
+            .filter_map(|_| {
+                traffic.elapsed = traffic.next.unwrap();
+                traffic.update(0., &[], &Sector::default())
+            })
+            .filter(|aircraft| aircraft.callsign.code == "CYP")
+            .count();
+        assert!((250..350).contains(&cyprus), "{}", cyprus);
+    }
+}
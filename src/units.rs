@@ -1,3 +1,4 @@
 pub const NM_to_KM: f64 = 1.852;
 pub const KM_TO_NM: f64 = 0.5399568;
 pub const KT_TO_MS: f64 = 0.5144444;
+pub const FT_TO_M: f64 = 0.3048;
@@ -0,0 +1,100 @@
+//! Wind field made of altitude layers, each with a constant wind.
+
+use serde::Deserialize;
+
+use crate::geom::heading_to_point;
+use crate::math::invert_bearing;
+use crate::units;
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct WindLayer {
+    /// feet, the layer applies from the one below it up to here
+    pub ceiling: u32,
+    /// bearing the wind blows from
+    pub direction: u32,
+    /// knots
+    pub speed: u32,
+}
+
+impl WindLayer {
+    /// Velocity of the air in meters per second.
+    pub fn velocity(&self) -> glm::Vec2 {
+        let towards = invert_bearing(self.direction as f32 % 360.);
+        heading_to_point(towards as i32) * (self.speed as f32 * units::KT_TO_MS as f32)
+    }
+}
+
+/// Wind by altitude, calm when there are no layers.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Wind {
+    /// ordered by ceiling
+    pub layers: Vec<WindLayer>,
+}
+
+impl Wind {
+    pub fn new(mut layers: Vec<WindLayer>) -> Self {
+        layers.sort_by_key(|layer| layer.ceiling);
+        Self { layers }
+    }
+
+    /// The layer at the altitude, the highest layer extending above its
+    /// ceiling.
+    pub fn layer(&self, altitude: f32) -> Option<&WindLayer> {
+        self.layers
+            .iter()
+            .find(|layer| altitude < layer.ceiling as f32)
+            .or_else(|| self.layers.last())
+    }
+
+    /// Velocity of the air at the altitude in meters per second.
+    pub fn at(&self, altitude: f32) -> glm::Vec2 {
+        self.layer(altitude)
+            .map(|layer| layer.velocity())
+            .unwrap_or_else(glm::zero)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn layer(ceiling: u32, direction: u32, speed: u32) -> WindLayer {
+        WindLayer {
+            ceiling,
+            direction,
+            speed,
+        }
+    }
+
+    #[test]
+    fn test_wind_layer_velocity() {
+        // from the west, blowing east
+        let velocity = layer(3000, 270, 20).velocity();
+        assert_eq!(10.29, (velocity.x * 100.).round() / 100.);
+        assert_eq!(0., velocity.y.round());
+
+        // from the north, blowing south
+        let velocity = layer(3000, 360, 10).velocity();
+        assert_eq!(0., velocity.x.round());
+        assert_eq!(-5.14, (velocity.y * 100.).round() / 100.);
+    }
+
+    #[test]
+    fn test_wind_layers() {
+        let wind = Wind::new(vec![layer(10000, 300, 40), layer(3000, 270, 20)]);
+        assert_eq!(3000, wind.layers[0].ceiling);
+
+        assert_eq!(Some(&wind.layers[0]), wind.layer(0.));
+        assert_eq!(Some(&wind.layers[0]), wind.layer(2999.));
+        assert_eq!(Some(&wind.layers[1]), wind.layer(3000.));
+        assert_eq!(Some(&wind.layers[1]), wind.layer(35000.));
+        assert_eq!(wind.layers[1].velocity(), wind.at(5000.));
+    }
+
+    #[test]
+    fn test_wind_calm() {
+        let wind = Wind::default();
+        assert_eq!(None, wind.layer(5000.));
+        assert_eq!(glm::vec2(0., 0.), wind.at(5000.));
+    }
+}